@@ -0,0 +1,138 @@
+use crate::*;
+use std::hash::*;
+use std::sync::*;
+use std::time::*;
+
+/// Records which modules request which assets from a [`WassetRegistry`], and when. Hosts which
+/// run untrusted plugins may load assets on a plugin's behalf through this layer, then review
+/// the resulting log for unexpected cross-module access. Loads which bypass the layer,
+/// by calling the registry directly, are not recorded.
+pub struct WassetAuditLayer<'r, 'a, K, A: AssetSchema> {
+    /// The registry from which assets are loaded.
+    registry: &'r WassetRegistry<'a, K, A>,
+    /// The accesses recorded so far, in the order that they occurred.
+    records: Mutex<Vec<WassetAuditRecord<K>>>
+}
+
+impl<'r, 'a, K: Clone + Eq + Hash, A: AssetSchema> WassetAuditLayer<'r, 'a, K, A> {
+    /// Creates a new layer with an empty log over the given registry.
+    pub fn new(registry: &'r WassetRegistry<'a, K, A>) -> Self {
+        Self {
+            registry,
+            records: Mutex::default()
+        }
+    }
+
+    /// Gets the registry from which assets are loaded.
+    pub fn registry(&self) -> &'r WassetRegistry<'a, K, A> {
+        self.registry
+    }
+
+    /// Loads an asset from the registry on behalf of the module identified by `requester`,
+    /// recording the access. Requests for assets which no module provides are recorded as well.
+    pub fn load(&self, requester: &K, id: WassetId) -> Result<Option<Arc<A>>, WassetError> {
+        self.record(requester, id);
+        self.registry.load(id)
+    }
+
+    /// Loads the given assets and all of their transitive dependencies on behalf of
+    /// the module identified by `requester`, recording an access for every asset loaded.
+    pub fn load_with_dependencies(&self, requester: &K, ids: impl IntoIterator<Item = WassetId>) -> Result<Vec<(WassetId, Arc<A>)>, WassetError> {
+        let result = self.registry.load_with_dependencies(ids)?;
+        for (id, _) in &result {
+            self.record(requester, *id);
+        }
+        Ok(result)
+    }
+
+    /// Gets a copy of the accesses recorded so far, in the order that they occurred.
+    pub fn records(&self) -> Vec<WassetAuditRecord<K>> {
+        self.records.lock().unwrap_or_else(PoisonError::into_inner).clone()
+    }
+
+    /// Removes and returns the accesses recorded so far, leaving the log empty.
+    pub fn take_records(&self) -> Vec<WassetAuditRecord<K>> {
+        std::mem::take(&mut *self.records.lock().unwrap_or_else(PoisonError::into_inner))
+    }
+
+    /// Serializes the accesses recorded so far as a JSON array.
+    pub fn to_json(&self) -> Result<String, WassetError> where K: Serialize {
+        serde_json::to_string(&*self.records.lock().unwrap_or_else(PoisonError::into_inner)).map_err(WassetError::from_serialize)
+    }
+
+    /// Appends an access by `requester` to the log.
+    fn record(&self, requester: &K, id: WassetId) {
+        let timestamp_ms = SystemTime::now().duration_since(UNIX_EPOCH).map(|x| x.as_millis() as u64).unwrap_or_default();
+        let record = WassetAuditRecord {
+            requester: requester.clone(),
+            id,
+            provider: self.registry.module_of(id),
+            timestamp_ms
+        };
+
+        self.records.lock().unwrap_or_else(PoisonError::into_inner).push(record);
+    }
+}
+
+/// Describes a single request for an asset, as recorded by a [`WassetAuditLayer`].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WassetAuditRecord<K> {
+    /// The key of the module which requested the asset.
+    pub requester: K,
+    /// The ID of the requested asset.
+    pub id: WassetId,
+    /// The key of the module which provided the asset at the time of the request,
+    /// or `None` if no module did.
+    pub provider: Option<K>,
+    /// The time of the request, in milliseconds since the Unix epoch.
+    pub timestamp_ms: u64
+}
+
+impl<K: PartialEq> WassetAuditRecord<K> {
+    /// Whether the asset was provided by a module other than the one which requested it.
+    pub fn is_cross_module(&self) -> bool {
+        self.provider.as_ref().is_some_and(|x| *x != self.requester)
+    }
+}
+
+#[cfg(all(test, feature = "testing"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accesses_are_recorded_with_their_provider() {
+        let [first, second] = [1, 2].map(|x| WassetId::from_bytes([x; 16]));
+        let a = testing::build_module([(first, 1u32)]).unwrap();
+        let b = testing::build_module([(second, 2u32)]).unwrap();
+        let registry = WassetRegistry::<&str, u32>::new();
+        registry.replace_module("a", WassetParser::parse(&a).unwrap()).unwrap();
+        registry.replace_module("b", WassetParser::parse(&b).unwrap()).unwrap();
+
+        let audit = WassetAuditLayer::new(&registry);
+        assert_eq!(audit.load(&"a", first).unwrap().as_deref(), Some(&1));
+        assert_eq!(audit.load(&"a", second).unwrap().as_deref(), Some(&2));
+        assert_eq!(audit.load(&"b", WassetId::from_bytes([3; 16])).unwrap(), None);
+
+        let records = audit.records();
+        assert_eq!(records.iter().map(|x| (x.requester, x.id, x.provider)).collect::<Vec<_>>(), [("a", first, Some("a")), ("a", second, Some("b")), ("b", WassetId::from_bytes([3; 16]), None)]);
+        assert_eq!(records.iter().map(WassetAuditRecord::is_cross_module).collect::<Vec<_>>(), [false, true, false]);
+    }
+
+    #[test]
+    fn records_are_taken_and_serialized() {
+        let id = WassetId::from_bytes([1; 16]);
+        let module = testing::build_module([(id, 1u32)]).unwrap();
+        let registry = WassetRegistry::<String, u32>::new();
+        registry.replace_module(String::from("plugin"), WassetParser::parse(&module).unwrap()).unwrap();
+
+        let audit = WassetAuditLayer::new(&registry);
+        audit.load(&String::from("plugin"), id).unwrap();
+        let json = audit.to_json().unwrap();
+        assert!(json.contains("\"requester\":\"plugin\""));
+
+        let records = audit.take_records();
+        assert_eq!(records.len(), 1);
+        assert_eq!(serde_json::from_str::<Vec<WassetAuditRecord<String>>>(&json).unwrap(), records);
+        assert!(audit.records().is_empty());
+    }
+}
@@ -0,0 +1,114 @@
+use crate::*;
+use bevy_asset::io::{AssetReader, AssetReaderError, AssetSourceBuilder, PathStream, Reader, VecReader};
+use std::marker::*;
+use std::path::*;
+use std::sync::*;
+
+/// Serves the assets of a module to Bevy's asset server, so that paths such as
+/// `wasset://<uuid>.png` resolve to embedded assets. Each asset is converted back into the
+/// contents of a file with an [`AssetDecoder`], so that Bevy's loaders may read it. Paths
+/// may name assets either by ID or by their name in the manifest, such as
+/// `wasset://assets/submodule/more_text.txt`. The extension is ignored when finding the asset,
+/// and only determines which loader Bevy uses.
+///
+/// ```no_run
+/// # use wasset::*;
+/// # struct MyDecoder;
+/// # impl AssetDecoder for MyDecoder {
+/// #     type Source = Vec<u8>;
+/// #     fn decode(asset: &Vec<u8>) -> Result<Vec<u8>, WassetError> {
+/// #         Ok(asset.clone())
+/// #     }
+/// # }
+/// # let plugin_bytes = std::fs::read("plugin.wasm")?;
+/// let parser = WassetOwnedParser::<Vec<u8>>::parse(plugin_bytes)?;
+/// let source = WassetAssetReader::<MyDecoder>::new(parser).into_source();
+/// // Then, before adding Bevy's plugins:
+/// // app.register_asset_source("wasset", source);
+/// // app.add_plugins(DefaultPlugins);
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+pub struct WassetAssetReader<D: 'static + AssetDecoder> {
+    /// The parser from which assets are read.
+    parser: WassetOwnedParser<D::Source>,
+    /// A marker type for `D`.
+    marker: PhantomData<fn(D)>
+}
+
+impl<D: 'static + AssetDecoder> WassetAssetReader<D> {
+    /// Creates a reader which serves the assets of the given parser.
+    pub fn new(parser: WassetOwnedParser<D::Source>) -> Self {
+        Self { parser, marker: PhantomData }
+    }
+
+    /// Gets the parser from which assets are read.
+    pub fn parser(&self) -> &WassetOwnedParser<D::Source> {
+        &self.parser
+    }
+
+    /// Creates an asset source which reads from this reader, for registration with
+    /// `App::register_asset_source`. This must happen before Bevy's `AssetPlugin` is added.
+    pub fn into_source(self) -> AssetSourceBuilder {
+        let parser = self.parser;
+        AssetSourceBuilder::new(move || Box::new(Self::new(parser.clone())))
+    }
+
+    /// Finds the ID of the asset to which a path refers.
+    fn resolve(&self, path: &Path) -> Result<WassetId, AssetReaderError> {
+        let not_found = || AssetReaderError::NotFound(path.to_path_buf());
+        let stem = path.with_extension("");
+        let stem = stem.to_str().ok_or_else(not_found)?;
+
+        stem.parse::<Uuid>().ok().map(WassetId::from)
+            .filter(|id| self.parser.manifest().get(*id).is_some())
+            .or_else(|| self.parser.manifest().lookup(&stem.replace('\\', "/")))
+            .ok_or_else(not_found)
+    }
+
+    /// Loads and decodes the asset with the given ID.
+    fn decode(&self, id: WassetId, path: &Path) -> Result<Vec<u8>, AssetReaderError> {
+        let asset = self.parser.load(id).map_err(io_error)?
+            .ok_or_else(|| AssetReaderError::NotFound(path.to_path_buf()))?;
+        D::decode(&asset).map_err(io_error)
+    }
+}
+
+impl<D: 'static + AssetDecoder> AssetReader for WassetAssetReader<D> {
+    async fn read<'a>(&'a self, path: &'a Path) -> Result<impl Reader + 'a, AssetReaderError> {
+        let id = self.resolve(path)?;
+        self.decode(id, path).map(VecReader::new)
+    }
+
+    async fn read_meta<'a>(&'a self, path: &'a Path) -> Result<impl Reader + 'a, AssetReaderError> {
+        Err::<VecReader, _>(AssetReaderError::NotFound(path.to_path_buf()))
+    }
+
+    async fn read_directory<'a>(&'a self, path: &'a Path) -> Result<Box<PathStream>, AssetReaderError> {
+        if !path.as_os_str().is_empty() {
+            return Err(AssetReaderError::NotFound(path.to_path_buf()));
+        }
+
+        let mut ids = self.parser.ids().collect::<Vec<_>>();
+        ids.sort();
+
+        let mut paths = Vec::with_capacity(ids.len());
+        for id in ids {
+            let mut name = PathBuf::from(Uuid::from(id).to_string());
+            if let Some(extension) = self.parser.load(id).map_err(io_error)?.as_ref().and_then(D::extension) {
+                name.set_extension(extension);
+            }
+            paths.push(name);
+        }
+
+        Ok(Box::new(futures_lite::stream::iter(paths)))
+    }
+
+    async fn is_directory<'a>(&'a self, path: &'a Path) -> Result<bool, AssetReaderError> {
+        Ok(path.as_os_str().is_empty())
+    }
+}
+
+/// Converts an error from loading an asset into one which Bevy can report.
+fn io_error(error: WassetError) -> AssetReaderError {
+    AssetReaderError::Io(Arc::new(std::io::Error::other(error)))
+}
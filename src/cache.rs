@@ -0,0 +1,188 @@
+use crate::*;
+use std::path::*;
+
+/// Stores parsed manifests on disk, keyed by a hash of the module bytes and the parse options
+/// which affect the manifest: the section prefix, expected schema, and duplicate ID policy.
+/// Parsing a module through the cache skips section scanning and manifest decoding whenever
+/// the same module has been parsed before, which speeds up repeated startups and tooling runs
+/// over the same set of plugins. The cache is best-effort: unreadable entries are ignored,
+/// and failures to write new entries do not cause parsing to fail.
+#[derive(Clone, Debug)]
+pub struct WassetManifestCache {
+    /// The folder in which cached manifests are stored.
+    directory: PathBuf
+}
+
+impl WassetManifestCache {
+    /// Creates a cache which stores manifests in the given folder. The folder
+    /// is created when the first manifest is stored.
+    pub fn new(directory: impl Into<PathBuf>) -> Self {
+        Self { directory: directory.into() }
+    }
+
+    /// Gets the folder in which cached manifests are stored.
+    pub fn directory(&self) -> &Path {
+        &self.directory
+    }
+
+    /// Parses the asset list from the given module, using the cached manifest if one exists.
+    pub fn parse<'a, A: AssetSchema>(&self, module: &'a [u8]) -> Result<WassetParser<'a, A>, WassetError> {
+        self.parse_with_options(module, WassetParseOptions::default())
+    }
+
+    /// Parses the asset list from the given module, using the provided options when reading
+    /// assets, and using the cached manifest if one exists. Otherwise, the module is parsed
+    /// as usual and its manifest is added to the cache. The result of a [`DuplicateIdPolicy::Custom`]
+    /// policy cannot be keyed, so such modules are always parsed without the cache.
+    pub fn parse_with_options<'a, A: AssetSchema>(&self, module: &'a [u8], options: WassetParseOptions) -> Result<WassetParser<'a, A>, WassetError> {
+        let Some(path) = self.entry_path(module, &options) else {
+            return WassetParser::parse_with_options(module, options);
+        };
+
+        let cached = std::fs::read(&path).ok()
+            .and_then(|x| rmp_serde::from_slice::<WassetManifest>(&x).ok())
+            .filter(|x| !x.has_external_data() && x.iter().flat_map(|(_, entry)| entry.payloads()).all(|x| x.start <= x.end && x.end as usize <= module.len()));
+
+        if let Some(manifest) = cached {
+            #[cfg(feature = "tracing")]
+            tracing::debug!(path = %path.display(), "loaded cached asset manifest");
+
+            return Ok(WassetParser::from_manifest(module, manifest, options));
+        }
+
+        let parser = WassetParser::parse_with_options(module, options)?;
+
+        // Entries do not record whether their data is external, so such manifests cannot be cached:
+        if parser.manifest().has_external_data() {
+            return Ok(parser);
+        }
+
+        #[allow(unused_variables)]
+        if let Err(error) = self.store(&path, parser.manifest()) {
+            #[cfg(feature = "tracing")]
+            tracing::warn!(path = %path.display(), %error, "failed to cache asset manifest");
+        }
+
+        Ok(parser)
+    }
+
+    /// Removes all cached manifests from the folder.
+    pub fn clear(&self) -> Result<(), WassetError> {
+        let Ok(entries) = std::fs::read_dir(&self.directory) else {
+            return Ok(());
+        };
+
+        for entry in entries {
+            let path = entry.map_err(WassetError::from_deserialize)?.path();
+            if path.extension().is_some_and(|x| x == CACHE_EXTENSION) {
+                std::fs::remove_file(&path).map_err(|e| WassetError::from_deserialize(e).in_file(&path))?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Gets the path at which the manifest for the given module is cached, or `None` if
+    /// the duplicate ID policy of the options is custom.
+    fn entry_path(&self, module: &[u8], options: &WassetParseOptions) -> Option<PathBuf> {
+        let duplicate_ids = match options.duplicate_ids {
+            DuplicateIdPolicy::Error => 0u8,
+            DuplicateIdPolicy::FirstWins => 1,
+            DuplicateIdPolicy::LastWins => 2,
+            DuplicateIdPolicy::Custom(_) => return None
+        };
+
+        let mut hasher = blake3::Hasher::new();
+        hasher.update(&CACHE_FORMAT_VERSION.to_le_bytes());
+        hasher.update(&(options.section_prefix.len() as u64).to_le_bytes());
+        hasher.update(options.section_prefix.as_bytes());
+        hasher.update(&[duplicate_ids]);
+        match &options.schema {
+            Some(schema) => {
+                hasher.update(&[1]);
+                hasher.update(&(schema.len() as u64).to_le_bytes());
+                hasher.update(schema.as_bytes());
+            },
+            None => {
+                hasher.update(&[0]);
+            }
+        }
+        hasher.update(module);
+        Some(self.directory.join(format!("{}.{CACHE_EXTENSION}", hasher.finalize().to_hex())))
+    }
+
+    /// Writes a manifest to the cache. The manifest is written to a temporary file first,
+    /// so that concurrent readers never observe a partially-written entry.
+    fn store(&self, path: &Path, manifest: &WassetManifest) -> Result<(), WassetError> {
+        std::fs::create_dir_all(&self.directory).map_err(WassetError::from_serialize)?;
+        let temporary = path.with_extension(format!("{}.tmp", std::process::id()));
+        std::fs::write(&temporary, rmp_serde::to_vec_named(manifest).map_err(WassetError::from_serialize)?).map_err(WassetError::from_serialize)?;
+        std::fs::rename(&temporary, path).map_err(WassetError::from_serialize)
+    }
+}
+
+/// The file extension of cached manifests.
+const CACHE_EXTENSION: &str = "wassetcache";
+
+/// The version of the cache format, which is hashed into every key so that
+/// changes to the manifest layout invalidate old entries.
+const CACHE_FORMAT_VERSION: u32 = 1;
+
+#[cfg(all(test, feature = "testing"))]
+mod tests {
+    use super::*;
+
+    /// Creates an empty cache in a temporary folder unique to the given test.
+    fn temporary_cache(name: &str) -> WassetManifestCache {
+        let cache = WassetManifestCache::new(std::env::temp_dir().join(format!("wasset-cache-{name}-{}", std::process::id())));
+        cache.clear().unwrap();
+        cache
+    }
+
+    #[test]
+    fn cached_manifest_is_reused() {
+        let cache = temporary_cache("reuse");
+        let id = WassetId::from_bytes([1; 16]);
+        let module = testing::build_module([(id, String::from("cached"))]).unwrap();
+
+        for _ in 0..2 {
+            let parser = cache.parse::<String>(&module).unwrap();
+            assert_eq!(parser.load(id).unwrap().as_deref(), Some("cached"));
+        }
+
+        assert_eq!(std::fs::read_dir(cache.directory()).unwrap().count(), 1);
+        cache.clear().unwrap();
+    }
+
+    #[test]
+    fn cached_manifest_is_checked_against_schema() {
+        let cache = temporary_cache("schema");
+        let id = WassetId::from_bytes([2; 16]);
+        let options = WassetEncodeOptions { schema: Some(String::from("v1")), ..Default::default() };
+        let module = testing::build_module_with_options([(id, 5u32)], &options).unwrap();
+
+        assert!(cache.parse::<u32>(&module).is_ok());
+        let expected = WassetParseOptions { schema: Some(String::from("v2")), ..Default::default() };
+        assert!(matches!(cache.parse_with_options::<u32>(&module, expected), Err(WassetError::SchemaMismatch { .. })));
+        cache.clear().unwrap();
+    }
+
+    #[test]
+    fn cached_manifest_is_checked_against_duplicate_policy() {
+        let cache = temporary_cache("duplicates");
+        let id = WassetId::from_bytes([3; 16]);
+        let options = WassetEncodeOptions::default();
+        let module = WassetModuleBuilder::new()
+            .add_assets(encode_assets([(id, 1u32)], &options).unwrap())
+            .add_assets(encode_assets([(id, 2u32)], &options).unwrap())
+            .build();
+
+        let last_wins = WassetParseOptions { duplicate_ids: DuplicateIdPolicy::LastWins, ..Default::default() };
+        assert_eq!(cache.parse_with_options::<u32>(&module, last_wins).unwrap().load(id).unwrap(), Some(2));
+        assert!(matches!(cache.parse::<u32>(&module), Err(WassetError::DuplicateId(x)) if x == id));
+
+        let first_wins = WassetParseOptions { duplicate_ids: DuplicateIdPolicy::FirstWins, ..Default::default() };
+        assert_eq!(cache.parse_with_options::<u32>(&module, first_wins).unwrap().load(id).unwrap(), Some(1));
+        cache.clear().unwrap();
+    }
+}
\ No newline at end of file
@@ -0,0 +1,78 @@
+use crate::*;
+use ::serde::de::*;
+#[cfg(feature = "bincode")]
+use bincode::Options;
+use std::marker::*;
+
+/// Identifies the format in which an asset is serialized. The codec of every asset is recorded
+/// in its manifest entry, so parsers read assets in any enabled format without configuration,
+/// and modules may mix formats freely. Formats other than MessagePack are not self-describing,
+/// so they suit schemas which do not rely upon `deserialize_any`, such as untagged enums.
+#[derive(Copy, Clone, Debug, Default, Serialize, Deserialize, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub enum WassetCodec {
+    /// The asset is serialized as MessagePack, with named fields.
+    #[default]
+    MessagePack,
+    /// The asset is serialized with `postcard`, a compact format suited to embedded engines.
+    Postcard,
+    /// The asset is serialized with `bincode`, using its default options.
+    Bincode,
+    /// The asset is serialized as JSON.
+    Json
+}
+
+impl WassetCodec {
+    /// All codecs, including those which are not enabled.
+    pub const ALL: [Self; 4] = [Self::MessagePack, Self::Postcard, Self::Bincode, Self::Json];
+
+    /// Whether this denotes the default MessagePack format.
+    pub fn is_message_pack(&self) -> bool {
+        *self == Self::MessagePack
+    }
+
+    /// Whether the crate feature required for this codec is enabled.
+    pub fn is_available(&self) -> bool {
+        match self {
+            Self::MessagePack => true,
+            Self::Postcard => cfg!(feature = "postcard"),
+            Self::Bincode => cfg!(feature = "bincode"),
+            Self::Json => cfg!(feature = "json")
+        }
+    }
+
+    /// Serializes the provided value with this codec.
+    pub fn serialize(&self, value: &impl Serialize) -> Result<Vec<u8>, WassetError> {
+        match self {
+            Self::MessagePack => rmp_serde::to_vec_named(value).map_err(WassetError::from_serialize),
+            #[cfg(feature = "postcard")]
+            Self::Postcard => postcard::to_allocvec(value).map_err(WassetError::from_serialize),
+            #[cfg(feature = "bincode")]
+            Self::Bincode => bincode::DefaultOptions::new().serialize(value).map_err(WassetError::from_serialize),
+            #[cfg(feature = "json")]
+            Self::Json => serde_json::to_vec(value).map_err(WassetError::from_serialize),
+            #[allow(unreachable_patterns)]
+            _ => Err(WassetError::from_serialize(format!("Codec {self:?} is not enabled")))
+        }
+    }
+
+    /// Deserializes a value which was serialized with this codec. The value may borrow from `data`.
+    pub fn deserialize<'de, T: Deserialize<'de>>(&self, data: &'de [u8]) -> Result<T, WassetError> {
+        self.deserialize_seed(data, PhantomData)
+    }
+
+    /// Deserializes a value which was serialized with this codec, using a stateful seed.
+    #[allow(unused_variables)]
+    pub fn deserialize_seed<'de, S: DeserializeSeed<'de>>(&self, data: &'de [u8], seed: S) -> Result<S::Value, WassetError> {
+        match self {
+            Self::MessagePack => seed.deserialize(&mut rmp_serde::Deserializer::from_read_ref(data)).map_err(WassetError::from_deserialize),
+            #[cfg(feature = "postcard")]
+            Self::Postcard => seed.deserialize(&mut postcard::Deserializer::from_bytes(data)).map_err(WassetError::from_deserialize),
+            #[cfg(feature = "bincode")]
+            Self::Bincode => seed.deserialize(&mut bincode::Deserializer::from_slice(data, bincode::DefaultOptions::new())).map_err(WassetError::from_deserialize),
+            #[cfg(feature = "json")]
+            Self::Json => seed.deserialize(&mut serde_json::Deserializer::from_slice(data)).map_err(WassetError::from_deserialize),
+            #[allow(unreachable_patterns)]
+            _ => Err(WassetError::from_deserialize(format!("Codec {self:?} is not enabled")))
+        }
+    }
+}
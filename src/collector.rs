@@ -0,0 +1,78 @@
+use crate::*;
+use wasmparser::*;
+
+/// Gathers asset sections from the payloads of a module as they are produced by an existing
+/// [`wasmparser::Parser`] loop. Hosts which already walk the sections of a module, such as to
+/// validate or compile it, may feed every payload to a collector and obtain a [`WassetParser`]
+/// afterward, without scanning the module a second time.
+///
+/// ```no_run
+/// # use wasset::*;
+/// # type MyAsset = Vec<u8>;
+/// # let module = std::fs::read("plugin.wasm")?;
+/// let mut collector = WassetCollector::new();
+/// for payload in wasmparser::Parser::new(0).parse_all(&module) {
+///     let payload = payload?;
+///     collector.feed(&payload)?;
+///     // ... handle the payload as usual ...
+/// }
+/// let parser = collector.finish::<MyAsset>(&module)?;
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+#[derive(Clone, Debug)]
+pub struct WassetCollector<'a> {
+    /// The asset sections that have been found so far.
+    offsets: FxHashMap<Uuid, WassetOffsets<'a>>,
+    /// The options used to recognize sections and read assets.
+    options: WassetParseOptions
+}
+
+impl<'a> WassetCollector<'a> {
+    /// Creates a new collector which uses the default options.
+    pub fn new() -> Self {
+        Self::with_options(WassetParseOptions::default())
+    }
+
+    /// Creates a new collector which recognizes sections using the provided options,
+    /// and passes them along to the resulting parser.
+    pub fn with_options(options: WassetParseOptions) -> Self {
+        Self {
+            offsets: FxHashMap::default(),
+            options
+        }
+    }
+
+    /// Examines a payload of the module, recording it if it is an asset section. Payloads
+    /// must come from a [`wasmparser::Parser`] which was created with an offset of zero
+    /// and given the entire module, so that section offsets refer to the module bytes.
+    pub fn feed(&mut self, payload: &Payload<'a>) -> Result<(), WassetError> {
+        if let Payload::CustomSection(reader) = payload {
+            parse_module_custom_section(reader.clone(), &self.options.section_prefix, &mut self.offsets)?;
+        }
+
+        Ok(())
+    }
+
+    /// Combines the manifests of all asset sections that were fed to the collector.
+    pub fn finish_manifest(self) -> Result<WassetManifest, WassetError> {
+        WassetParser::<()>::collect_manifests(self.offsets, &self.options)
+    }
+
+    /// Creates a parser over the given module from all asset sections that were fed to the
+    /// collector. The module must be the same bytes from which the payloads were parsed.
+    pub fn finish<A: AssetSchema>(self, module: &'a [u8]) -> Result<WassetParser<'a, A>, WassetError> {
+        let options = self.options.clone();
+        let manifest = self.finish_manifest()?;
+        if manifest.iter().flat_map(|(_, entry)| entry.payloads()).any(|x| x.end as usize > module.len()) {
+            return Err(WassetError::from_deserialize("Collected asset sections lie outside of the module"));
+        }
+
+        Ok(WassetParser::from_manifest(module, manifest, options))
+    }
+}
+
+impl<'a> Default for WassetCollector<'a> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,188 @@
+use crate::*;
+use std::collections::*;
+use std::path::*;
+
+/// Scans every `.wasm` file within a directory and its subdirectories for asset IDs which
+/// collide between modules, as described by [`find_id_collisions`]. This is intended to run
+/// over the built plugins of a workspace before release, in order to catch misconfigured
+/// deterministic IDs.
+pub fn find_id_collisions_in_dir(directory: &Path, options: &WassetParseOptions) -> Result<WassetCollisionReport, WassetError> {
+    let modules = WassetModuleDir::read(directory)?;
+    find_id_collisions(modules.modules().iter().map(|(path, module)| (path.clone(), module)), options)
+}
+
+/// Collects the assets of every given module, each identified by its path, and reports
+/// any IDs which are shared by assets with different contents, along with any contents
+/// which are shared by assets in different modules. Contents are compared by their recorded
+/// hashes, or by their decompressed bytes for assets which were encoded without one.
+pub fn find_id_collisions<B: AsRef<[u8]>>(modules: impl IntoIterator<Item = (PathBuf, B)>, options: &WassetParseOptions) -> Result<WassetCollisionReport, WassetError> {
+    let modules = modules.into_iter().collect::<Vec<_>>();
+    let parsers = modules.iter()
+        .map(|(path, module)| WassetParser::<()>::parse_with_options(module.as_ref(), options.clone()).map_err(|x| x.in_file(path)))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut by_id = BTreeMap::<WassetId, Vec<(usize, &WassetManifestEntry)>>::new();
+    for (index, parser) in parsers.iter().enumerate() {
+        for (id, entry) in parser.manifest().iter() {
+            by_id.entry(id).or_default().push((index, entry));
+        }
+    }
+
+    let occurrence = |index: usize, id: WassetId, entry: &WassetManifestEntry| WassetAssetOccurrence {
+        module: modules[index].0.clone(),
+        id,
+        name: entry.name().map(str::to_string)
+    };
+
+    let mut report = WassetCollisionReport {
+        modules: modules.iter().map(|(path, _)| path.clone()).collect(),
+        asset_count: by_id.values().map(Vec::len).sum(),
+        ..Default::default()
+    };
+
+    let mut by_content = BTreeMap::<ContentKey, Vec<WassetAssetOccurrence>>::new();
+    for (id, entries) in &by_id {
+        // Hashes are only comparable if every copy has one, so the bytes are compared otherwise.
+        let compare_bytes = entries.iter().any(|(_, entry)| entry.hash().is_none());
+        let keys = entries.iter()
+            .map(|(index, entry)| content_key(&parsers[*index], *id, entry, compare_bytes).map_err(|x| x.in_file(&modules[*index].0)))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        if keys.iter().any(|x| *x != keys[0]) {
+            report.collisions.push(WassetIdCollision {
+                id: *id,
+                occurrences: entries.iter().map(|(index, entry)| occurrence(*index, *id, entry)).collect()
+            });
+        }
+
+        for ((index, entry), key) in entries.iter().zip(keys) {
+            let key = if compare_bytes {
+                content_key(&parsers[*index], *id, entry, false).map_err(|x| x.in_file(&modules[*index].0))?
+            }
+            else {
+                key
+            };
+
+            by_content.entry(key).or_default().push(occurrence(*index, *id, entry));
+        }
+    }
+
+    for (key, occurrences) in by_content {
+        if occurrences.iter().any(|x| x.module != occurrences[0].module) {
+            report.shared.push(WassetSharedAsset {
+                hash: match key {
+                    ContentKey::Hash(hash) => Some(hash),
+                    ContentKey::Bytes(_) => None
+                },
+                occurrences
+            });
+        }
+    }
+
+    Ok(report)
+}
+
+/// Describes the asset IDs found across a set of modules by [`find_id_collisions`].
+#[derive(Clone, Debug, Default)]
+pub struct WassetCollisionReport {
+    /// The paths of the modules which were scanned.
+    pub modules: Vec<PathBuf>,
+    /// The total number of assets across all modules.
+    pub asset_count: usize,
+    /// The IDs which refer to assets with different contents.
+    pub collisions: Vec<WassetIdCollision>,
+    /// The contents which appear in more than one module.
+    pub shared: Vec<WassetSharedAsset>
+}
+
+impl WassetCollisionReport {
+    /// Whether no ID refers to assets with different contents.
+    pub fn is_clean(&self) -> bool {
+        self.collisions.is_empty()
+    }
+}
+
+/// An ID which refers to assets with different contents.
+#[derive(Clone, Debug)]
+pub struct WassetIdCollision {
+    /// The colliding ID.
+    pub id: WassetId,
+    /// Every asset with the ID.
+    pub occurrences: Vec<WassetAssetOccurrence>
+}
+
+/// Contents which appear in more than one module, possibly under different IDs.
+#[derive(Clone, Debug)]
+pub struct WassetSharedAsset {
+    /// The recorded hash of the contents, if the assets were encoded with one.
+    pub hash: Option<WassetHash>,
+    /// Every asset with the contents.
+    pub occurrences: Vec<WassetAssetOccurrence>
+}
+
+/// Identifies a single asset within a scanned module.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct WassetAssetOccurrence {
+    /// The path of the module which holds the asset.
+    pub module: PathBuf,
+    /// The ID of the asset.
+    pub id: WassetId,
+    /// The name of the asset, if it was encoded from a file.
+    pub name: Option<String>
+}
+
+/// Identifies the contents of an asset for comparison.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum ContentKey {
+    /// The contents are identified by their recorded hash.
+    Hash(WassetHash),
+    /// The contents are identified by their decompressed bytes.
+    Bytes(Vec<u8>)
+}
+
+/// Gets the key which identifies the contents of an asset, preferring its recorded hash unless `bytes` is set.
+fn content_key(parser: &WassetParser<()>, id: WassetId, entry: &WassetManifestEntry, bytes: bool) -> Result<ContentKey, WassetError> {
+    match entry.hash() {
+        Some(hash) if !bytes => Ok(ContentKey::Hash(hash)),
+        _ => Ok(ContentKey::Bytes(parser.load_raw(id)?.map(|x| x.to_vec()).unwrap_or_default()))
+    }
+}
+
+#[cfg(all(test, feature = "testing"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn colliding_and_shared_assets_are_reported() {
+        let [first, second, third] = [1, 2, 3].map(|x| WassetId::from_bytes([x; 16]));
+        let a = testing::build_module([(first, String::from("a")), (second, String::from("shared"))]).unwrap();
+        let b = testing::build_module([(first, String::from("b")), (third, String::from("shared"))]).unwrap();
+
+        let report = find_id_collisions([(PathBuf::from("a.wasm"), &a), (PathBuf::from("b.wasm"), &b)], &WassetParseOptions::default()).unwrap();
+        assert!(!report.is_clean());
+        assert_eq!(report.asset_count, 4);
+        assert_eq!(report.collisions.len(), 1);
+        assert_eq!(report.collisions[0].id, first);
+        assert_eq!(report.collisions[0].occurrences.iter().map(|x| x.module.clone()).collect::<Vec<_>>(), [PathBuf::from("a.wasm"), PathBuf::from("b.wasm")]);
+
+        assert_eq!(report.shared.len(), 1);
+        assert!(report.shared[0].hash.is_some());
+        assert_eq!(report.shared[0].occurrences.iter().map(|x| x.id).collect::<Vec<_>>(), [second, third]);
+    }
+
+    #[test]
+    fn identical_copies_are_not_collisions() {
+        let id = WassetId::from_bytes([1; 16]);
+        let module = testing::build_module([(id, String::from("same"))]).unwrap();
+
+        let report = find_id_collisions([(PathBuf::from("a.wasm"), &module), (PathBuf::from("b.wasm"), &module)], &WassetParseOptions::default()).unwrap();
+        assert!(report.is_clean());
+        assert_eq!(report.shared.len(), 1);
+    }
+
+    #[test]
+    fn invalid_modules_are_reported_with_their_path() {
+        let error = find_id_collisions([(PathBuf::from("broken.wasm"), b"broken")], &WassetParseOptions::default()).unwrap_err();
+        assert!(error.to_string().contains("broken.wasm"));
+    }
+}
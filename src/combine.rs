@@ -0,0 +1,98 @@
+use crate::*;
+
+/// Combines the assets of several modules or asset packs into a single set, which may be embedded
+/// with [`WassetModuleBuilder`] or [`inject_assets`]. Payloads whose stored bytes are identical are
+/// written once, and the entries of every copy refer to the same range, so assets which many modules
+/// share take up space only once. IDs provided by more than one module are resolved according to
+/// [`WassetParseOptions::duplicate_ids`], with later modules appearing after earlier ones.
+///
+/// The stored bytes of each asset are copied without decoding them, so assets keep their compression,
+/// codec, encryption, and alignment, along with their names, dependencies, versions, kinds, previews,
+/// and quality tiers.
+/// The manifest, index, and signature of the merged set are produced according to `options`.
+pub fn merge_modules(modules: &[&[u8]], parse_options: &WassetParseOptions, options: &WassetEncodeOptions) -> Result<EncodedAssets, WassetError> {
+    let mut data = Vec::new();
+    let mut previews = Vec::new();
+    let mut manifest = WassetManifest::default();
+    let mut payloads = FxHashMap::default();
+
+    for module in modules {
+        let parser = RawWassetParser::parse_with_options(module, parse_options.clone())?;
+        let mut entries = parser.manifest().iter().collect::<Vec<_>>();
+        entries.sort_by_key(|(_, entry)| (entry.start, entry.end));
+
+        for (id, entry) in entries {
+            if let Some(earlier) = manifest.assets.get(&id) {
+                if !parse_options.duplicate_ids.replaces(id, earlier, entry)? {
+                    continue;
+                }
+            }
+
+            let mut entry = entry.clone();
+            entry.check_local()?;
+            entry.relocate(|range| {
+                let stored = module.get(range.start as usize..range.end as usize)
+                    .ok_or_else(|| WassetError::from_deserialize(format!("Asset {id:?} lies outside of the module")))?;
+                Ok(*payloads.entry(*blake3::hash(stored).as_bytes()).or_insert_with(|| {
+                    data.resize(data.len() + payload_padding(data.len(), range.start, MAX_PAYLOAD_ALIGNMENT), 0);
+                    let start = data.len() as u32;
+                    data.extend_from_slice(stored);
+                    start
+                }))
+            })?;
+
+            entry.copy_preview(module, &mut previews);
+            manifest.assets.insert(id, entry);
+        }
+    }
+
+    #[cfg(feature = "tracing")]
+    tracing::debug!(modules = modules.len(), assets = manifest.assets.len(), payloads = payloads.len(), size = data.len(), "merged modules");
+
+    EncodedAssets::from_parts(data, previews, manifest, FxHashMap::default(), options)
+}
+
+#[cfg(all(test, feature = "testing"))]
+mod tests {
+    use super::*;
+
+    /// Gets the ID of the asset with the given number.
+    fn id(x: u8) -> WassetId {
+        WassetId::from_bytes([x; 16])
+    }
+
+    /// Builds a module holding the given numbered assets.
+    fn module(assets: &[(u8, &str)]) -> Vec<u8> {
+        testing::build_module(assets.iter().map(|(x, value)| (id(*x), value.to_string()))).unwrap()
+    }
+
+    #[test]
+    fn assets_of_all_modules_are_combined() {
+        let first = module(&[(1, "first"), (2, "shared")]);
+        let second = module(&[(3, "shared"), (4, "second")]);
+        let merged = merge_modules(&[&first, &second], &WassetParseOptions::default(), &WassetEncodeOptions::default()).unwrap();
+        let combined = WassetModuleBuilder::new().add_assets(merged).build();
+
+        let parser = WassetParser::<String>::parse(&combined).unwrap();
+        for (x, value) in [(1, "first"), (2, "shared"), (3, "shared"), (4, "second")] {
+            assert_eq!(parser.load(id(x)).unwrap().as_deref(), Some(value));
+        }
+
+        let manifest = parser.manifest();
+        assert_eq!(manifest.get(id(2)).unwrap().start, manifest.get(id(3)).unwrap().start);
+    }
+
+    #[test]
+    fn duplicate_ids_follow_policy() {
+        let first = module(&[(1, "first")]);
+        let second = module(&[(1, "second")]);
+        let merge = |duplicate_ids| merge_modules(&[&first, &second], &WassetParseOptions { duplicate_ids, ..Default::default() }, &WassetEncodeOptions::default())
+            .map(|merged| WassetModuleBuilder::new().add_assets(merged).build());
+
+        assert!(matches!(merge(DuplicateIdPolicy::Error), Err(WassetError::DuplicateId(x)) if x == id(1)));
+        let combined = merge(DuplicateIdPolicy::FirstWins).unwrap();
+        assert_eq!(WassetParser::<String>::parse(&combined).unwrap().load(id(1)).unwrap().as_deref(), Some("first"));
+        let combined = merge(DuplicateIdPolicy::LastWins).unwrap();
+        assert_eq!(WassetParser::<String>::parse(&combined).unwrap().load(id(1)).unwrap().as_deref(), Some("second"));
+    }
+}
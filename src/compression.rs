@@ -0,0 +1,149 @@
+use crate::*;
+#[cfg(feature = "zstd")]
+use std::io::Read;
+
+/// Identifies the compression scheme applied to a stored asset.
+#[derive(Copy, Clone, Debug, Default, Serialize, Deserialize, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub enum WassetCompression {
+    /// The asset is stored without compression.
+    #[default]
+    None,
+    /// The asset is compressed with LZ4, which favors decompression speed.
+    Lz4,
+    /// The asset is compressed with Zstandard, which favors compression ratio.
+    Zstd
+}
+
+impl WassetCompression {
+    /// All compression schemes, including those which are not enabled.
+    pub const ALL: [Self; 3] = [Self::None, Self::Lz4, Self::Zstd];
+
+    /// Whether this denotes uncompressed data.
+    pub fn is_none(&self) -> bool {
+        *self == Self::None
+    }
+
+    /// Whether the crate feature required for this compression scheme is enabled.
+    pub fn is_available(&self) -> bool {
+        match self {
+            Self::None => true,
+            Self::Lz4 => cfg!(feature = "lz4"),
+            Self::Zstd => cfg!(feature = "zstd")
+        }
+    }
+
+    /// Compresses the provided data with this scheme.
+    #[allow(unused_variables)]
+    pub fn compress(&self, data: &[u8]) -> Result<Vec<u8>, WassetError> {
+        match self {
+            Self::None => Ok(data.to_vec()),
+            #[cfg(feature = "lz4")]
+            Self::Lz4 => Ok(lz4_flex::compress_prepend_size(data)),
+            #[cfg(feature = "zstd")]
+            Self::Zstd => zstd::bulk::compress(data, zstd::DEFAULT_COMPRESSION_LEVEL).map_err(WassetError::from_serialize),
+            #[allow(unreachable_patterns)]
+            _ => Err(WassetError::from_serialize(format!("Compression scheme {self:?} is not enabled")))
+        }
+    }
+
+    /// Decompresses the provided data with this scheme, failing with [`WassetError::LimitExceeded`]
+    /// if the decompressed data would be larger than `limit` bytes.
+    pub fn decompress(&self, data: &[u8], limit: u64) -> Result<Vec<u8>, WassetError> {
+        let mut result = Vec::new();
+        self.decompress_into(data, limit, &mut result)?;
+        Ok(result)
+    }
+
+    /// Decompresses the provided data with this scheme into `output`, replacing its contents.
+    /// Fails with [`WassetError::LimitExceeded`] if the decompressed data would be larger
+    /// than `limit` bytes.
+    #[allow(unused_variables)]
+    pub fn decompress_into(&self, data: &[u8], limit: u64, output: &mut Vec<u8>) -> Result<(), WassetError> {
+        output.clear();
+        match self {
+            Self::None => {
+                check_limit(data.len() as u64, limit)?;
+                output.extend_from_slice(data);
+                Ok(())
+            },
+            #[cfg(feature = "lz4")]
+            Self::Lz4 => {
+                let (size, block) = lz4_flex::block::uncompressed_size(data).map_err(WassetError::from_deserialize)?;
+                check_limit(size as u64, limit)?;
+                output.resize(size, 0);
+                let written = lz4_flex::block::decompress_into(block, output).map_err(WassetError::from_deserialize)?;
+                output.truncate(written);
+                Ok(())
+            },
+            #[cfg(feature = "zstd")]
+            Self::Zstd => {
+                if let Ok(Some(size)) = zstd::zstd_safe::get_frame_content_size(data) {
+                    check_limit(size, limit)?;
+                    output.reserve(size as usize);
+                }
+
+                // The declared frame size cannot be trusted, so the stream is cut off at the limit regardless.
+                zstd::stream::read::Decoder::new(data)
+                    .map_err(WassetError::from_deserialize)?
+                    .take(limit.saturating_add(1))
+                    .read_to_end(output)
+                    .map_err(WassetError::from_deserialize)?;
+                check_limit(output.len() as u64, limit)
+            },
+            #[allow(unreachable_patterns)]
+            _ => Err(WassetError::from_deserialize(format!("Compression scheme {self:?} is not enabled")))
+        }
+    }
+}
+
+/// Ensures that the given size does not exceed the limit.
+fn check_limit(size: u64, limit: u64) -> Result<(), WassetError> {
+    if size > limit {
+        Err(WassetError::LimitExceeded { size, limit })
+    }
+    else {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn uncompressed_data_is_checked_against_limit() {
+        let data = [7; 100];
+        assert_eq!(WassetCompression::None.decompress(&data, 100).unwrap(), data);
+        assert!(matches!(WassetCompression::None.decompress(&data, 99), Err(WassetError::LimitExceeded { size: 100, limit: 99 })));
+    }
+
+    #[cfg(feature = "lz4")]
+    #[test]
+    fn lz4_declared_size_is_checked_against_limit() {
+        let compressed = WassetCompression::Lz4.compress(&[7; 1000]).unwrap();
+        assert_eq!(WassetCompression::Lz4.decompress(&compressed, 1000).unwrap(), [7; 1000]);
+        assert!(matches!(WassetCompression::Lz4.decompress(&compressed, 999), Err(WassetError::LimitExceeded { size: 1000, limit: 999 })));
+    }
+
+    #[cfg(feature = "zstd")]
+    #[test]
+    fn zstd_declared_size_is_checked_against_limit() {
+        let compressed = WassetCompression::Zstd.compress(&[7; 1000]).unwrap();
+        assert_eq!(WassetCompression::Zstd.decompress(&compressed, 1000).unwrap(), [7; 1000]);
+        assert!(matches!(WassetCompression::Zstd.decompress(&compressed, 999), Err(WassetError::LimitExceeded { size: 1000, limit: 999 })));
+    }
+
+    #[cfg(feature = "zstd")]
+    #[test]
+    fn zstd_stream_without_declared_size_is_cut_off_at_limit() {
+        use std::io::Write;
+
+        let mut encoder = zstd::stream::write::Encoder::new(Vec::new(), 0).unwrap();
+        encoder.write_all(&[7; 1000]).unwrap();
+        let compressed = encoder.finish().unwrap();
+        assert!(!matches!(zstd::zstd_safe::get_frame_content_size(&compressed), Ok(Some(_))));
+
+        assert_eq!(WassetCompression::Zstd.decompress(&compressed, 1000).unwrap(), [7; 1000]);
+        assert!(matches!(WassetCompression::Zstd.decompress(&compressed, 999), Err(WassetError::LimitExceeded { limit: 999, .. })));
+    }
+}
@@ -0,0 +1,305 @@
+use crate::*;
+
+impl<'a, A: AssetSchema> WassetParser<'a, A> {
+    /// Creates a copy of the module in which the given assets have been removed from their manifests,
+    /// and from any indices over those manifests. The data of the removed assets remains in the
+    /// module until it is reclaimed by [`WassetParser::compact`]. The signatures of any modified
+    /// sets of sections are removed, since they no longer match.
+    pub fn remove_assets(&self, ids: impl IntoIterator<Item = WassetId>) -> Result<Vec<u8>, WassetError> {
+        let ids = ids.into_iter().collect::<FxHashSet<_>>();
+        let mut manifests = FxHashMap::default();
+
+        for (section_id, offsets) in read_asset_sections(self.module, &self.options().section_prefix)? {
+            if let Some(manifest_data) = offsets.manifest {
+                let mut manifest = WassetManifest::from_bytes(manifest_data)?;
+                let count = manifest.assets.len();
+                manifest.assets.retain(|id, _| !ids.contains(id));
+                if manifest.assets.len() != count {
+                    let index = offsets.index.map(|_| build_index(&manifest));
+                    manifests.insert(section_id, (manifest.to_bytes(ManifestFormat::detect(manifest_data))?, index));
+                }
+            }
+        }
+
+        rewrite_custom_sections(self.module, |section| match parse_section_name(section.name(), &self.options().section_prefix) {
+            Some((kind @ (AssetSectionKind::Index | AssetSectionKind::Manifest), id)) => match Uuid::try_parse(id).ok().and_then(|id| manifests.get(&id)) {
+                Some((manifest, index)) => Some(Cow::Borrowed(if kind == AssetSectionKind::Manifest { manifest } else { index.as_ref()? })),
+                None => Some(Cow::Borrowed(section.data()))
+            },
+            Some((AssetSectionKind::Signature, id)) if Uuid::try_parse(id).is_ok_and(|id| manifests.contains_key(&id)) => None,
+            _ => Some(Cow::Borrowed(section.data()))
+        })
+    }
+
+    /// Creates a copy of the module in which the data sections of every asset have been removed, while
+    /// their manifests, indices, and previews are kept. Each manifest is marked as having external data,
+    /// so the result may still be parsed to list and inspect its assets, though loading them fails. This
+    /// allows for publishing a lightweight module for discovery while serving the data from elsewhere,
+    /// such as the sidecar produced by [`WassetParser::split_module`]. Signatures are removed, since they
+    /// no longer match.
+    pub fn strip_data(&self) -> Result<Vec<u8>, WassetError> {
+        let mut manifests = FxHashMap::default();
+        for (section_id, offsets) in read_asset_sections(self.module, &self.options().section_prefix)? {
+            if let Some(manifest_data) = offsets.manifest {
+                let mut manifest = WassetManifest::from_bytes(manifest_data)?;
+                manifest.external_data = true;
+                manifests.insert(section_id, manifest.to_bytes(ManifestFormat::detect(manifest_data))?);
+            }
+        }
+
+        rewrite_custom_sections(self.module, |section| match parse_section_name(section.name(), &self.options().section_prefix) {
+            Some((AssetSectionKind::Manifest, id)) => match Uuid::try_parse(id).ok().and_then(|id| manifests.get(&id)) {
+                Some(manifest) => Some(Cow::Borrowed(manifest)),
+                None => Some(Cow::Borrowed(section.data()))
+            },
+            Some((AssetSectionKind::Data | AssetSectionKind::Signature, _)) => None,
+            _ => Some(Cow::Borrowed(section.data()))
+        })
+    }
+
+    /// Creates a copy of the module in which every asset that matches `filter` has been removed,
+    /// and the remaining data compacted. This allows for producing variants of a shipped module,
+    /// such as one without high-resolution textures, without access to the original asset files.
+    ///
+    /// ```no_run
+    /// # use wasset::*;
+    /// # let module = std::fs::read("plugin.wasm")?;
+    /// # let parser = WassetParser::<Vec<u8>>::parse(&module)?;
+    /// let player_build = parser.strip_assets_matching(|_, entry| entry.is_in_folder("assets/editor"))?;
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn strip_assets_matching(&self, mut filter: impl FnMut(WassetId, &WassetManifestEntry) -> bool) -> Result<Vec<u8>, WassetError> {
+        let removed = self.remove_assets(self.manifest().iter().filter(|(id, entry)| filter(*id, entry)).map(|(id, _)| id).collect::<Vec<_>>())?;
+        WassetParser::<A>::parse_with_options(&removed, self.options().clone())?.compact()
+    }
+
+    /// Creates a copy of the module in which every asset data section holds only the bytes that
+    /// its manifest refers to, separated only by alignment padding, and any indices are rebuilt to
+    /// match. Data sections without a manifest, and section pairs whose manifest is empty, are
+    /// removed entirely. This reclaims the space left behind by [`WassetParser::remove_assets`] or
+    /// by selectively stripping sections. Each payload keeps its offset modulo
+    /// [`MAX_PAYLOAD_ALIGNMENT`], so aligned loads remain valid. The signatures of rewritten sets
+    /// of sections are removed, since they no longer match.
+    pub fn compact(&self) -> Result<Vec<u8>, WassetError> {
+        let mut sections = FxHashMap::default();
+
+        for (section_id, offsets) in read_asset_sections(self.module, &self.options().section_prefix)? {
+            let Some(location) = offsets.data else {
+                continue;
+            };
+
+            let Some(manifest_data) = offsets.manifest else {
+                sections.insert(section_id, None);
+                continue;
+            };
+
+            let data = &self.module[location.offset as usize..(location.offset + location.len) as usize];
+            let previews = offsets.preview.map(|x| &self.module[x.offset as usize..(x.offset + x.len) as usize]).unwrap_or_default();
+            let manifest = WassetManifest::from_bytes(manifest_data)?;
+            if manifest.assets.is_empty() {
+                sections.insert(section_id, None);
+            }
+            else {
+                let (manifest, data, previews) = compact_section(manifest, data, previews)?;
+                let index = offsets.index.map(|_| build_index(&manifest));
+                sections.insert(section_id, Some((manifest.to_bytes(ManifestFormat::detect(manifest_data))?, data, index, previews)));
+            }
+        }
+
+        rewrite_custom_sections(self.module, |section| match parse_section_name(section.name(), &self.options().section_prefix) {
+            Some((kind, id)) => match Uuid::try_parse(id).ok().and_then(|id| sections.get(&id)) {
+                Some(Some((manifest, data, index, previews))) => Some(Cow::Borrowed(match kind {
+                    AssetSectionKind::Data => data,
+                    AssetSectionKind::Index => index.as_ref()?,
+                    AssetSectionKind::Manifest => manifest,
+                    AssetSectionKind::Preview => Some(previews).filter(|x| !x.is_empty())?,
+                    AssetSectionKind::Signature => return None
+                })),
+                Some(None) => None,
+                None => Some(Cow::Borrowed(section.data()))
+            },
+            None => Some(Cow::Borrowed(section.data()))
+        })
+    }
+}
+
+/// Creates a copy of the module without any incomplete sets of asset sections, using the section
+/// prefix from the provided options. A set is incomplete if its manifest or its data section is
+/// missing, as may happen when a build is interrupted, and [`WassetParser::parse`] rejects such
+/// modules with [`WassetError::IncompleteSections`]. Every section of an incomplete set is removed,
+/// along with the assets that it describes, while complete sets are copied unchanged.
+pub fn repair_module(module: &[u8], options: &WassetParseOptions) -> Result<Vec<u8>, WassetError> {
+    let incomplete = read_asset_sections(module, &options.section_prefix)?.into_iter()
+        .filter(|(_, offsets)| offsets.manifest.is_none() || offsets.data.is_none())
+        .map(|(id, _)| id)
+        .collect::<FxHashSet<_>>();
+
+    rewrite_custom_sections(module, |section| match parse_section_name(section.name(), &options.section_prefix) {
+        Some((_, id)) if Uuid::try_parse(id).is_ok_and(|id| incomplete.contains(&id)) => None,
+        _ => Some(Cow::Borrowed(section.data()))
+    })
+}
+
+/// Copies the referenced bytes of a data section and its previews contiguously, updating the ranges
+/// in its manifest, including those of other quality tiers. Each payload is padded to keep its offset
+/// modulo [`MAX_PAYLOAD_ALIGNMENT`]. Entries which refer to the same range continue to share their data.
+fn compact_section(mut manifest: WassetManifest, data: &[u8], previews: &[u8]) -> Result<(WassetManifest, Vec<u8>, Vec<u8>), WassetError> {
+    let mut entries = manifest.assets.iter_mut().collect::<Vec<_>>();
+    entries.sort_by_key(|(_, entry)| (entry.start, entry.end));
+
+    let mut output = Vec::new();
+    let mut preview_output = Vec::new();
+    let mut moved = FxHashMap::default();
+    for (id, entry) in entries {
+        entry.relocate(|range| {
+            let Some(bytes) = data.get(range.start as usize..range.end as usize) else {
+                return Err(WassetError::from_deserialize(format!("Asset {id:?} lies outside of its data section")));
+            };
+
+            Ok(*moved.entry(range.clone()).or_insert_with(|| {
+                output.resize(output.len() + payload_padding(output.len(), range.start, MAX_PAYLOAD_ALIGNMENT), 0);
+                let start = output.len() as u32;
+                output.extend_from_slice(bytes);
+                start
+            }))
+        })?;
+
+        entry.copy_preview(previews, &mut preview_output);
+    }
+
+    Ok((manifest, output, preview_output))
+}
+
+#[cfg(all(test, feature = "testing"))]
+mod tests {
+    use super::*;
+
+    /// Builds a module holding assets of varied sizes, returning their IDs.
+    fn sample_module(options: &WassetEncodeOptions) -> (Vec<WassetId>, Vec<u8>) {
+        let ids = (1..=5).map(|x| WassetId::from_bytes([x; 16])).collect::<Vec<_>>();
+        let module = testing::build_module_with_options(ids.iter().zip(1..).map(|(id, x)| (*id, vec![x as u8; 7 * x])), options).unwrap();
+        (ids, module)
+    }
+
+    #[test]
+    fn compact_reclaims_removed_assets() {
+        let (ids, module) = sample_module(&WassetEncodeOptions::default());
+        let removed = WassetParser::<Vec<u8>>::parse(&module).unwrap().remove_assets([ids[1], ids[3]]).unwrap();
+        let parser = WassetParser::<Vec<u8>>::parse(&removed).unwrap();
+        assert_eq!(parser.load(ids[1]).unwrap(), None);
+        assert!(validate_module(&removed, parser.options()).unwrap().wasted_bytes() > 0);
+
+        let compacted = parser.compact().unwrap();
+        assert!(compacted.len() < removed.len());
+        assert!(validate_module(&compacted, parser.options()).unwrap().is_clean());
+        let compacted_parser = WassetParser::<Vec<u8>>::parse(&compacted).unwrap();
+        for id in [ids[0], ids[2], ids[4]] {
+            assert_eq!(compacted_parser.load(id).unwrap(), parser.load(id).unwrap());
+        }
+    }
+
+    #[test]
+    fn compact_preserves_alignment() {
+        let options = WassetEncodeOptions { alignment: MAX_PAYLOAD_ALIGNMENT, ..Default::default() };
+        let (ids, module) = sample_module(&options);
+        let removed = WassetParser::<Vec<u8>>::parse(&module).unwrap().remove_assets([ids[0]]).unwrap();
+        let parser = WassetParser::<Vec<u8>>::parse(&removed).unwrap();
+        let compacted = parser.compact().unwrap();
+        let compacted_parser = WassetParser::<Vec<u8>>::parse(&compacted).unwrap();
+
+        for id in &ids[1..] {
+            let before = parser.manifest().get(*id).unwrap().range();
+            let after = compacted_parser.manifest().get(*id).unwrap().range();
+            assert_eq!(before.start % MAX_PAYLOAD_ALIGNMENT, after.start % MAX_PAYLOAD_ALIGNMENT);
+            assert_eq!(compacted_parser.load(*id).unwrap(), parser.load(*id).unwrap());
+        }
+    }
+
+    /// Builds a module holding two sets of aligned byte array assets, the first of which has a data section
+    /// larger than 128 bytes, returning their IDs and contents.
+    #[cfg(feature = "bytemuck")]
+    fn pod_module() -> (Vec<(WassetId, testing::PodBytes)>, Vec<u8>) {
+        let options = WassetEncodeOptions { alignment: MAX_PAYLOAD_ALIGNMENT, ..Default::default() };
+        let assets = (1..=6u8).map(|x| (WassetId::from_bytes([x; 16]), testing::PodBytes::from_u32s((0..9 * x as u32).map(|y| y * x as u32)))).collect::<Vec<_>>();
+        let module = WassetModuleBuilder::new()
+            .add_assets(encode_assets(assets[..3].iter().cloned(), &options).unwrap())
+            .add_assets(encode_assets(assets[3..].iter().cloned(), &options).unwrap())
+            .build();
+        (assets, module)
+    }
+
+    /// Checks that every given asset may still be viewed in place within the module.
+    #[cfg(feature = "bytemuck")]
+    fn assert_pod_assets(module: &[u8], assets: &[(WassetId, testing::PodBytes)]) {
+        let parser = WassetParser::<testing::PodBytes>::parse(module).unwrap();
+        for (id, asset) in assets {
+            let values = parser.load_pod::<u32>(*id).unwrap().unwrap();
+            assert_eq!(bytemuck::cast_slice::<u32, u8>(values), asset.0);
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "bytemuck")]
+    fn edits_keep_payloads_aligned() {
+        let (assets, module) = pod_module();
+        let parser = WassetParser::<testing::PodBytes>::parse(&module).unwrap();
+        assert_pod_assets(&module, &assets);
+
+        let removed = parser.remove_assets([assets[1].0, assets[4].0]).unwrap();
+        let kept = [&assets[..1], &assets[2..4], &assets[5..]].concat();
+        assert_pod_assets(&removed, &kept);
+
+        let compacted = WassetParser::<testing::PodBytes>::parse(&removed).unwrap().compact().unwrap();
+        assert_pod_assets(&compacted, &kept);
+    }
+
+    #[test]
+    #[cfg(feature = "bytemuck")]
+    fn compact_keeps_payloads_aligned_when_data_section_shrinks() {
+        let (assets, module) = pod_module();
+        let removed = WassetParser::<testing::PodBytes>::parse(&module).unwrap().remove_assets([assets[1].0, assets[2].0]).unwrap();
+        let compacted = WassetParser::<testing::PodBytes>::parse(&removed).unwrap().compact().unwrap();
+        assert!(compacted.len() < removed.len());
+
+        let kept = [&assets[..1], &assets[3..]].concat();
+        assert_pod_assets(&compacted, &kept);
+    }
+
+    #[test]
+    fn strip_assets_matching_removes_folder() {
+        let assets = testing::encode_text_files([("editor/gizmo.txt", "gizmo"), ("game/level.txt", "level")], &WassetEncodeOptions::default()).unwrap();
+        let module = WassetModuleBuilder::new().add_assets(assets).build();
+        let parser = WassetParser::<String>::parse(&module).unwrap();
+
+        let stripped = parser.strip_assets_matching(|_, entry| entry.is_in_folder("assets/editor")).unwrap();
+        let stripped_parser = WassetParser::<String>::parse(&stripped).unwrap();
+        let names = stripped_parser.manifest().iter().filter_map(|(_, entry)| entry.name()).collect::<Vec<_>>();
+        assert_eq!(names, ["assets/game/level"]);
+        assert!(stripped.len() < module.len());
+    }
+
+    #[test]
+    fn strip_data_keeps_manifests() {
+        let (ids, module) = sample_module(&WassetEncodeOptions::default());
+        let stripped = WassetParser::<Vec<u8>>::parse(&module).unwrap().strip_data().unwrap();
+        let parser = WassetParser::<Vec<u8>>::parse(&stripped).unwrap();
+
+        assert_eq!(parser.manifest().ids().count(), ids.len());
+        assert!(parser.manifest().has_external_data());
+        assert!(parser.load(ids[0]).is_err());
+    }
+
+    #[test]
+    fn repair_removes_incomplete_sections() {
+        let id = WassetId::from_bytes([1; 16]);
+        let module = WassetModuleBuilder::new()
+            .add_assets(encode_assets([(id, 1u32)], &WassetEncodeOptions::default()).unwrap())
+            .build();
+        let parser = WassetParser::<u32>::parse(&module).unwrap();
+        let incomplete = parser.strip_module_with_options(&WassetStripOptions { keep_manifests: true, ..Default::default() }).unwrap();
+        assert!(matches!(WassetParser::<u32>::parse(&incomplete), Err(WassetError::IncompleteSections { .. })));
+
+        let repaired = repair_module(&incomplete, parser.options()).unwrap();
+        assert_eq!(WassetParser::<u32>::parse(&repaired).unwrap().manifest().ids().count(), 0);
+    }
+}
@@ -0,0 +1,171 @@
+use crate::*;
+use std::fmt::Write;
+
+/// A language for which asset ID declarations may be generated.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum BindingLanguage {
+    /// A C header, which declares each ID as a `wasset_id` constant.
+    C,
+    /// A Zig source file, which declares each ID as a `WassetId` constant within nested structs.
+    Zig,
+    /// An AssemblyScript module, which declares each ID as a `StaticArray<u8>` within nested namespaces.
+    AssemblyScript
+}
+
+impl EncodedAssets {
+    /// Generates source code in the given language which declares the ID and serialized size of
+    /// every encoded asset, mirroring the modules and constants generated by the `include_assets` macro. This allows
+    /// WASM plugins written in other languages to reference the same `WassetId`s that the host sees.
+    /// Fails if the size constant of an asset would have the same name as the ID constant of another.
+    pub fn id_bindings(&self, language: BindingLanguage) -> Result<String, WassetError> {
+        let mut output = String::new();
+        let hierarchies = sorted_hierarchies(&self.encoded_assets);
+        for (_, hierarchy) in &hierarchies {
+            hierarchy.check_length_constants(&|x| sanitize_identifier(x).to_uppercase())?;
+        }
+
+        match language {
+            BindingLanguage::C => {
+                output.push_str("// This file is generated by wasset. Do not edit it by hand.\n\n#pragma once\n\n#include <stddef.h>\n#include <stdint.h>\n\n");
+                output.push_str("#ifndef WASSET_ID_DEFINED\n#define WASSET_ID_DEFINED\ntypedef struct wasset_id { uint8_t bytes[16]; } wasset_id;\n#endif\n\n");
+                for (name, hierarchy) in hierarchies {
+                    write_c_hierarchy(&mut output, &sanitize_identifier(name).to_uppercase(), hierarchy);
+                }
+            },
+            BindingLanguage::Zig => {
+                output.push_str("// This file is generated by wasset. Do not edit it by hand.\n\npub const WassetId = [16]u8;\n");
+                for (name, hierarchy) in hierarchies {
+                    write_zig_hierarchy(&mut output, name, hierarchy, 0);
+                }
+            },
+            BindingLanguage::AssemblyScript => {
+                output.push_str("// This file is generated by wasset. Do not edit it by hand.\n");
+                for (name, hierarchy) in hierarchies {
+                    write_assemblyscript_hierarchy(&mut output, name, hierarchy, 0);
+                }
+            }
+        }
+
+        Ok(output)
+    }
+}
+
+/// Writes the constants for a hierarchy as C declarations, prefixing each name with the names of its parents.
+fn write_c_hierarchy(output: &mut String, prefix: &str, hierarchy: &AssetHierarchy) {
+    for asset in sorted_assets(hierarchy) {
+        let name = sanitize_identifier(&asset.name).to_uppercase();
+        let _ = writeln!(output, "static const wasset_id {prefix}_{name} = {{ {{ {} }} }};", byte_list(&asset.id));
+        let _ = writeln!(output, "static const size_t {prefix}_{name}_LEN = {};", asset.size);
+    }
+
+    for (name, sub_hierarchy) in sorted_hierarchies(&hierarchy.sub_hierarchies) {
+        write_c_hierarchy(output, &format!("{prefix}_{}", sanitize_identifier(name).to_uppercase()), sub_hierarchy);
+    }
+}
+
+/// Writes the constants for a hierarchy as a Zig struct.
+fn write_zig_hierarchy(output: &mut String, name: &str, hierarchy: &AssetHierarchy, depth: usize) {
+    let indent = "    ".repeat(depth);
+    let _ = writeln!(output, "\n{indent}pub const {} = struct {{", sanitize_identifier(name));
+    for asset in sorted_assets(hierarchy) {
+        let name = sanitize_identifier(&asset.name).to_uppercase();
+        let _ = writeln!(output, "{indent}    pub const {name}: WassetId = .{{ {} }};", byte_list(&asset.id));
+        let _ = writeln!(output, "{indent}    pub const {name}_LEN: usize = {};", asset.size);
+    }
+
+    for (name, sub_hierarchy) in sorted_hierarchies(&hierarchy.sub_hierarchies) {
+        write_zig_hierarchy(output, name, sub_hierarchy, depth + 1);
+    }
+    let _ = writeln!(output, "{indent}}};");
+}
+
+/// Writes the constants for a hierarchy as an AssemblyScript namespace.
+fn write_assemblyscript_hierarchy(output: &mut String, name: &str, hierarchy: &AssetHierarchy, depth: usize) {
+    let indent = "    ".repeat(depth);
+    let _ = writeln!(output, "\n{indent}export namespace {} {{", sanitize_identifier(name));
+    for asset in sorted_assets(hierarchy) {
+        let name = sanitize_identifier(&asset.name).to_uppercase();
+        let _ = writeln!(output, "{indent}    export const {name}: StaticArray<u8> = [{}];", byte_list(&asset.id));
+        let _ = writeln!(output, "{indent}    export const {name}_LEN: usize = {};", asset.size);
+    }
+
+    for (name, sub_hierarchy) in sorted_hierarchies(&hierarchy.sub_hierarchies) {
+        write_assemblyscript_hierarchy(output, name, sub_hierarchy, depth + 1);
+    }
+    let _ = writeln!(output, "{indent}}}");
+}
+
+/// Gets the assets of a hierarchy, sorted by name so that output is deterministic.
+fn sorted_assets(hierarchy: &AssetHierarchy) -> Vec<&EncodedAsset> {
+    let mut assets = hierarchy.assets.iter().collect::<Vec<_>>();
+    assets.sort_by(|a, b| a.name.cmp(&b.name));
+    assets
+}
+
+/// Gets the provided hierarchies, sorted by name so that output is deterministic.
+fn sorted_hierarchies(hierarchies: &FxHashMap<String, AssetHierarchy>) -> Vec<(&String, &AssetHierarchy)> {
+    let mut result = hierarchies.iter().collect::<Vec<_>>();
+    result.sort_by(|a, b| a.0.cmp(b.0));
+    result
+}
+
+/// Formats the bytes of an ID as a comma-separated list of hexadecimal literals.
+fn byte_list(id: &WassetId) -> String {
+    id.as_bytes().iter().map(|x| format!("0x{x:02x}")).collect::<Vec<_>>().join(", ")
+}
+
+/// Replaces all characters that are not valid within an identifier with underscores.
+fn sanitize_identifier(name: &str) -> String {
+    let mut result = name.chars().map(|x| if x.is_ascii_alphanumeric() { x } else { '_' }).collect::<String>();
+    if result.starts_with(|x: char| x.is_ascii_digit()) {
+        result.insert(0, '_');
+    }
+    result
+}
+
+#[cfg(all(test, feature = "testing"))]
+mod tests {
+    use super::*;
+
+    /// Creates an encoded asset with the given name, ID byte, and size.
+    fn asset(name: &str, x: u8, size: usize) -> EncodedAsset {
+        EncodedAsset { name: name.to_string(), id: WassetId::from_bytes([x; 16]), size, kind: None, timings: EncodeTimings::default() }
+    }
+
+    /// Creates a set of encoded assets with a nested hierarchy.
+    fn encoded_assets(assets: Vec<EncodedAsset>) -> EncodedAssets {
+        let ui = AssetHierarchy { assets: vec![asset("1-button", 2, 7)], sub_hierarchies: FxHashMap::default() };
+        let root = AssetHierarchy { assets, sub_hierarchies: FxHashMap::from_iter([("ui".to_string(), ui)]) };
+        EncodedAssets { encoded_assets: FxHashMap::from_iter([("assets".to_string(), root)]), ..Default::default() }
+    }
+
+    #[test]
+    fn c_bindings_declare_prefixed_constants() {
+        let bindings = encoded_assets(vec![asset("logo", 1, 5)]).id_bindings(BindingLanguage::C).unwrap();
+        let ones = ["0x01"; 16].join(", ");
+        let twos = ["0x02"; 16].join(", ");
+        assert!(bindings.ends_with(&format!("static const wasset_id ASSETS_LOGO = {{ {{ {ones} }} }};\nstatic const size_t ASSETS_LOGO_LEN = 5;\n\
+            static const wasset_id ASSETS_UI__1_BUTTON = {{ {{ {twos} }} }};\nstatic const size_t ASSETS_UI__1_BUTTON_LEN = 7;\n")));
+    }
+
+    #[test]
+    fn nested_bindings_are_scoped_by_folder() {
+        let assets = encoded_assets(vec![asset("logo", 1, 5)]);
+        let zig = assets.id_bindings(BindingLanguage::Zig).unwrap();
+        assert!(zig.contains("\npub const assets = struct {\n    pub const LOGO: WassetId"));
+        assert!(zig.contains("\n    pub const ui = struct {\n        pub const _1_BUTTON: WassetId"));
+        assert!(zig.contains("        pub const _1_BUTTON_LEN: usize = 7;\n    };\n};\n"));
+
+        let assemblyscript = assets.id_bindings(BindingLanguage::AssemblyScript).unwrap();
+        assert!(assemblyscript.contains("\nexport namespace assets {\n    export const LOGO: StaticArray<u8>"));
+        assert!(assemblyscript.contains("\n    export namespace ui {\n        export const _1_BUTTON: StaticArray<u8>"));
+    }
+
+    #[test]
+    fn conflicting_length_constants_are_rejected() {
+        let assets = encoded_assets(vec![asset("logo", 1, 5), asset("logo_len", 3, 1)]);
+        for language in [BindingLanguage::C, BindingLanguage::Zig, BindingLanguage::AssemblyScript] {
+            assert!(assets.id_bindings(language).is_err());
+        }
+    }
+}
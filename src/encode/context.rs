@@ -0,0 +1,132 @@
+use crate::*;
+use std::collections::*;
+use std::path::*;
+use std::sync::*;
+use toml::*;
+
+/// Describes the file from which an asset is being encoded.
+#[derive(Copy, Clone, Debug)]
+pub struct EncodeContext<'a> {
+    /// The extension of the file, without the leading `.`.
+    pub extension: &'a str,
+    /// The path of the file, as located by the [`FileProvider`]. This is absolute when
+    /// encoding from the macros, or from an absolute folder path.
+    pub path: &'a Path,
+    /// The path of the file relative to the root asset folder.
+    pub relative_path: &'a Path,
+    /// The table for the file in the `Wasset.toml` file of its folder.
+    pub metadata: &'a Table,
+    /// The contents of the `Wasset.toml` files of every folder from the root asset folder
+    /// to the folder which holds the file, beginning with the root. Folders without
+    /// a `Wasset.toml` file have an empty table.
+    pub folder_metadata: &'a [&'a Table],
+    /// Provides access to the other files of the asset folder.
+    pub(crate) files: &'a FileAccess<'a>
+}
+
+impl<'a> EncodeContext<'a> {
+    /// Gets the value of a key from the file's metadata, or otherwise from the top level of the
+    /// nearest `Wasset.toml` file which declares it. This allows a setting, such as a compiler
+    /// flag, to be declared once for a folder and overridden for its subfolders or single files.
+    pub fn lookup(&self, key: &str) -> Option<&'a Value> {
+        self.metadata.get(key).or_else(|| self.folder_metadata.iter().rev().find_map(|x| x.get(key)))
+    }
+
+    /// Lists the paths of the other files in the same folder as this one, including those
+    /// which are excluded by the [`AssetFilter`].
+    pub fn siblings(&self) -> Result<Vec<PathBuf>, WassetError> {
+        let Some(folder) = self.path.parent() else {
+            return Ok(Vec::new());
+        };
+
+        let mut result = self.files.provider.read_dir(folder)?;
+        result.retain(|x| x != self.path && self.files.provider.is_file(x));
+        result.sort();
+        Ok(result)
+    }
+
+    /// Reads another file of the asset folder, such as the `.mtl` file of a `.obj` model or the
+    /// `.bin` buffer of a `.gltf` scene. The path is relative to the folder which holds this file,
+    /// and may not leave the root asset folder. If the encoder produces an asset, then every file
+    /// which it read is consumed, and is not encoded as an asset of its own.
+    pub fn read_sibling(&self, path: impl AsRef<Path>) -> Result<Vec<u8>, WassetError> {
+        let path = self.resolve(path.as_ref())?;
+        let data = self.files.provider.read(&path).map_err(|e| e.in_file(&path))?;
+        self.files.consume(path);
+        Ok(data)
+    }
+
+    /// Attaches a metadata value to the asset, such as its author, license, or the settings with
+    /// which it was imported. Metadata is stored in the manifest, so that hosts may read it with
+    /// `WassetParser::metadata` without loading the asset. Values from the `metadata` table of the
+    /// file's `Wasset.toml` entry take precedence over those set by the encoder.
+    pub fn set_metadata(&self, key: impl Into<String>, value: impl Into<String>) {
+        self.files.metadata.lock().unwrap_or_else(PoisonError::into_inner).insert(key.into(), value.into());
+    }
+
+    /// Resolves a path relative to the folder of this file, failing if it lies outside the root asset folder.
+    fn resolve(&self, path: &Path) -> Result<PathBuf, WassetError> {
+        let mut result = self.path.parent().unwrap_or(Path::new("")).to_path_buf();
+        for component in path.components() {
+            match component {
+                Component::Normal(x) => result.push(x),
+                Component::CurDir => {},
+                Component::ParentDir if result != self.files.root => { result.pop(); },
+                _ => return Err(WassetError::from_serialize(format!("Path {} must lie within the asset folder", path.display())))
+            }
+        }
+
+        Ok(result)
+    }
+}
+
+/// Provides encoders with access to the files of an asset folder, and records which files they read
+/// along with the metadata that they attach to the asset.
+pub(crate) struct FileAccess<'a> {
+    /// The provider from which files are read.
+    provider: &'a dyn FileProvider,
+    /// The root asset folder.
+    root: PathBuf,
+    /// The file for which an asset is being encoded.
+    file: PathBuf,
+    /// The other files which have been read.
+    consumed: Mutex<Vec<PathBuf>>,
+    /// The metadata which the encoder attached to the asset.
+    metadata: Mutex<BTreeMap<String, String>>
+}
+
+impl<'a> FileAccess<'a> {
+    /// Creates a new record of the files read while encoding `file`.
+    pub fn new(provider: &'a dyn FileProvider, root: &Path, file: &Path) -> Self {
+        Self {
+            provider,
+            root: root.to_path_buf(),
+            file: file.to_path_buf(),
+            consumed: Mutex::default(),
+            metadata: Mutex::default()
+        }
+    }
+
+    /// Marks another file as consumed by the asset being encoded.
+    pub fn consume(&self, path: PathBuf) {
+        if path != self.file {
+            self.consumed.lock().unwrap_or_else(PoisonError::into_inner).push(path);
+        }
+    }
+
+    /// Gets the paths of all files which were consumed, and the metadata attached to the asset.
+    pub fn finish(self) -> (Vec<PathBuf>, BTreeMap<String, String>) {
+        (self.consumed.into_inner().unwrap_or_else(PoisonError::into_inner), self.metadata.into_inner().unwrap_or_else(PoisonError::into_inner))
+    }
+
+    /// Attaches metadata to the asset, as by [`EncodeContext::set_metadata`].
+    pub fn extend_metadata(&self, metadata: BTreeMap<String, String>) {
+        self.metadata.lock().unwrap_or_else(PoisonError::into_inner).extend(metadata);
+    }
+}
+
+impl std::fmt::Debug for FileAccess<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FileAccess").field("root", &self.root).field("file", &self.file).finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,259 @@
+use crate::*;
+use toml::*;
+
+/// Encodes files as UTF-8 text, failing if a file is not valid UTF-8.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct TextEncoder;
+
+impl AssetEncoder for TextEncoder {
+    type Target = String;
+
+    fn encode(_: &EncodeContext, data: Vec<u8>) -> Result<Option<Self::Target>, WassetError> {
+        String::from_utf8(data).map(Some).map_err(WassetError::from_serialize)
+    }
+
+    fn kind(_: &Self::Target) -> Option<String> {
+        Some("text".to_string())
+    }
+
+    fn preview(asset: &Self::Target) -> Option<Vec<u8>> {
+        Some(text_preview(asset))
+    }
+}
+
+#[cfg(feature = "parse")]
+impl AssetDecoder for TextEncoder {
+    type Source = String;
+
+    fn decode(asset: &Self::Source) -> Result<Vec<u8>, WassetError> {
+        Ok(asset.clone().into_bytes())
+    }
+
+    fn extension(_: &Self::Source) -> Option<String> {
+        Some("txt".to_string())
+    }
+}
+
+/// Encodes files as their raw bytes, without any processing.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct BinaryEncoder;
+
+impl AssetEncoder for BinaryEncoder {
+    type Target = Vec<u8>;
+
+    fn encode(_: &EncodeContext, data: Vec<u8>) -> Result<Option<Self::Target>, WassetError> {
+        Ok(Some(data))
+    }
+
+    fn kind(_: &Self::Target) -> Option<String> {
+        Some("binary".to_string())
+    }
+}
+
+#[cfg(feature = "parse")]
+impl AssetDecoder for BinaryEncoder {
+    type Source = Vec<u8>;
+
+    fn decode(asset: &Self::Source) -> Result<Vec<u8>, WassetError> {
+        Ok(asset.clone())
+    }
+
+    fn extension(_: &Self::Source) -> Option<String> {
+        Some("bin".to_string())
+    }
+}
+
+/// Encodes JSON files as text, failing if a file is not well-formed JSON.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct JsonEncoder;
+
+impl AssetEncoder for JsonEncoder {
+    type Target = String;
+
+    fn encode(context: &EncodeContext, data: Vec<u8>) -> Result<Option<Self::Target>, WassetError> {
+        serde_json::from_slice::<::serde::de::IgnoredAny>(&data).map_err(WassetError::from_serialize)?;
+        TextEncoder::encode(context, data)
+    }
+
+    fn kind(_: &Self::Target) -> Option<String> {
+        Some("json".to_string())
+    }
+
+    fn preview(asset: &Self::Target) -> Option<Vec<u8>> {
+        Some(text_preview(asset))
+    }
+}
+
+#[cfg(feature = "parse")]
+impl AssetDecoder for JsonEncoder {
+    type Source = String;
+
+    fn decode(asset: &Self::Source) -> Result<Vec<u8>, WassetError> {
+        TextEncoder::decode(asset)
+    }
+
+    fn extension(_: &Self::Source) -> Option<String> {
+        Some("json".to_string())
+    }
+}
+
+/// Encodes TOML files as text, failing if a file is not well-formed TOML.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct TomlEncoder;
+
+impl AssetEncoder for TomlEncoder {
+    type Target = String;
+
+    fn encode(context: &EncodeContext, data: Vec<u8>) -> Result<Option<Self::Target>, WassetError> {
+        let text = TextEncoder::encode(context, data)?.unwrap_or_default();
+        text.parse::<Table>().map_err(WassetError::from_serialize)?;
+        Ok(Some(text))
+    }
+
+    fn kind(_: &Self::Target) -> Option<String> {
+        Some("toml".to_string())
+    }
+
+    fn preview(asset: &Self::Target) -> Option<Vec<u8>> {
+        Some(text_preview(asset))
+    }
+}
+
+#[cfg(feature = "parse")]
+impl AssetDecoder for TomlEncoder {
+    type Source = String;
+
+    fn decode(asset: &Self::Source) -> Result<Vec<u8>, WassetError> {
+        TextEncoder::decode(asset)
+    }
+
+    fn extension(_: &Self::Source) -> Option<String> {
+        Some("toml".to_string())
+    }
+}
+
+/// Encodes image files as their raw bytes, along with the format and dimensions
+/// read from the image header. The image data itself is not decoded.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct ImageEncoder;
+
+impl AssetEncoder for ImageEncoder {
+    type Target = ImageAsset;
+
+    fn encode(_: &EncodeContext, data: Vec<u8>) -> Result<Option<Self::Target>, WassetError> {
+        let (format, width, height) = probe_image(&data)
+            .ok_or_else(|| WassetError::from_serialize("Unrecognized or malformed image header"))?;
+        Ok(Some(ImageAsset { format, width, height, data }))
+    }
+
+    fn kind(_: &Self::Target) -> Option<String> {
+        Some("image".to_string())
+    }
+}
+
+#[cfg(feature = "parse")]
+impl AssetDecoder for ImageEncoder {
+    type Source = ImageAsset;
+
+    fn decode(asset: &Self::Source) -> Result<Vec<u8>, WassetError> {
+        Ok(asset.data.clone())
+    }
+
+    fn extension(asset: &Self::Source) -> Option<String> {
+        let extension = match asset.format {
+            ImageFormat::Png => "png",
+            ImageFormat::Jpeg => "jpg",
+            ImageFormat::Gif => "gif",
+            ImageFormat::Bmp => "bmp",
+            ImageFormat::WebP => "webp"
+        };
+
+        Some(extension.to_string())
+    }
+}
+
+/// An image file, along with information from its header.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct ImageAsset {
+    /// The format of the image data.
+    pub format: ImageFormat,
+    /// The width of the image, in pixels.
+    pub width: u32,
+    /// The height of the image, in pixels.
+    pub height: u32,
+    /// The raw contents of the image file.
+    pub data: Vec<u8>
+}
+
+/// Identifies the file format of an image.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ImageFormat {
+    /// A Portable Network Graphics image.
+    #[default]
+    Png,
+    /// A JPEG image.
+    Jpeg,
+    /// A Graphics Interchange Format image.
+    Gif,
+    /// A Windows bitmap image.
+    Bmp,
+    /// A WebP image.
+    WebP
+}
+
+/// Determines the format and dimensions of an image from its header.
+fn probe_image(data: &[u8]) -> Option<(ImageFormat, u32, u32)> {
+    let u16_le = |at: usize| data.get(at..at + 2).map(|x| u16::from_le_bytes([x[0], x[1]]) as u32);
+    let u16_be = |at: usize| data.get(at..at + 2).map(|x| u16::from_be_bytes([x[0], x[1]]) as u32);
+    let u24_le = |at: usize| data.get(at..at + 3).map(|x| u32::from_le_bytes([x[0], x[1], x[2], 0]));
+    let u32_be = |at: usize| data.get(at..at + 4).map(|x| u32::from_be_bytes([x[0], x[1], x[2], x[3]]));
+    let i32_le = |at: usize| data.get(at..at + 4).map(|x| i32::from_le_bytes([x[0], x[1], x[2], x[3]]).unsigned_abs());
+
+    if data.starts_with(b"\x89PNG\r\n\x1a\n") {
+        Some((ImageFormat::Png, u32_be(16)?, u32_be(20)?))
+    }
+    else if data.starts_with(b"GIF87a") || data.starts_with(b"GIF89a") {
+        Some((ImageFormat::Gif, u16_le(6)?, u16_le(8)?))
+    }
+    else if data.starts_with(b"BM") {
+        Some((ImageFormat::Bmp, i32_le(18)?, i32_le(22)?))
+    }
+    else if data.starts_with(b"RIFF") && data.get(8..12) == Some(b"WEBP") {
+        match data.get(12..16)? {
+            b"VP8 " => Some((ImageFormat::WebP, u16_le(26)? & 0x3fff, u16_le(28)? & 0x3fff)),
+            b"VP8L" => {
+                let bits = u32::from_le_bytes(data.get(21..25)?.try_into().ok()?);
+                Some((ImageFormat::WebP, 1 + (bits & 0x3fff), 1 + ((bits >> 14) & 0x3fff)))
+            },
+            b"VP8X" => Some((ImageFormat::WebP, 1 + u24_le(24)?, 1 + u24_le(27)?)),
+            _ => None
+        }
+    }
+    else if data.starts_with(&[0xff, 0xd8]) {
+        let mut position = 2;
+        loop {
+            if *data.get(position)? != 0xff {
+                return None;
+            }
+
+            let marker = *data.get(position + 1)?;
+            let is_start_of_frame = (0xc0..=0xcf).contains(&marker) && !matches!(marker, 0xc4 | 0xc8 | 0xcc);
+            if is_start_of_frame {
+                return Some((ImageFormat::Jpeg, u16_be(position + 7)?, u16_be(position + 5)?));
+            }
+
+            position += 2 + u16_be(position + 2)? as usize;
+        }
+    }
+    else {
+        None
+    }
+}
+
+/// The maximum number of characters kept in the preview of a text asset.
+const TEXT_PREVIEW_LENGTH: usize = 200;
+
+/// Gets the preview of a text asset, which holds its first few characters.
+fn text_preview(text: &str) -> Vec<u8> {
+    text.chars().take(TEXT_PREVIEW_LENGTH).collect::<String>().into_bytes()
+}
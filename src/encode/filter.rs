@@ -0,0 +1,192 @@
+use crate::*;
+use std::path::*;
+use toml::*;
+
+/// Selects which files within an asset folder are encoded, using glob patterns. In a pattern,
+/// `*` matches any characters within a single path component, `?` matches one character, and `**`
+/// matches any number of components. A pattern without a `/`, such as `*.psd` or `.DS_Store`,
+/// matches the name of a file or folder at any depth, while other patterns match paths relative to
+/// the asset folder. A pattern which ends with `/`, such as `*_source/`, only matches folders.
+///
+/// Filters may also be declared for a folder and its subfolders with top-level `include` and
+/// `exclude` arrays in the folder's `Wasset.toml` file, whose patterns are relative to that folder.
+/// These add to the patterns of the options and of any parent folders.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct AssetFilter {
+    /// If this is not empty, only files which match at least one of these patterns are encoded.
+    pub include: Vec<String>,
+    /// Files and folders which match any of these patterns are skipped entirely.
+    pub exclude: Vec<String>
+}
+
+/// The filter patterns which apply to a folder, gathered from the options and every `Wasset.toml`
+/// file between the folder and the asset root.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct FolderFilter {
+    /// The patterns which select files to encode.
+    include: Vec<FilterPattern>,
+    /// The patterns which select files and folders to skip.
+    exclude: Vec<FilterPattern>
+}
+
+impl FolderFilter {
+    /// Creates the filter for an asset root from the given options.
+    pub fn new(root: &Path, filter: &AssetFilter) -> Self {
+        Self {
+            include: filter.include.iter().map(|x| FilterPattern::new(root, x)).collect(),
+            exclude: filter.exclude.iter().map(|x| FilterPattern::new(root, x)).collect()
+        }
+    }
+
+    /// Creates the filter for a folder, adding the patterns from its `Wasset.toml` table.
+    pub fn with_table(&self, folder: &Path, table: &Table) -> Result<Self, WassetError> {
+        let mut result = self.clone();
+        result.include.extend(Self::read_patterns(folder, table, INCLUDE_KEY)?);
+        result.exclude.extend(Self::read_patterns(folder, table, EXCLUDE_KEY)?);
+        Ok(result)
+    }
+
+    /// Determines whether the file or folder at the given path should be skipped.
+    pub fn excludes(&self, path: &Path, is_dir: bool) -> bool {
+        self.exclude.iter().any(|x| x.matches(path, is_dir))
+    }
+
+    /// Determines whether the file at the given path should be encoded.
+    pub fn includes(&self, path: &Path) -> bool {
+        self.include.is_empty() || self.include.iter().any(|x| x.matches(path, false))
+    }
+
+    /// Reads an array of patterns from the given key of a `Wasset.toml` table.
+    fn read_patterns(folder: &Path, table: &Table, key: &str) -> Result<Vec<FilterPattern>, WassetError> {
+        match table.get(key) {
+            Some(Value::Array(values)) => values.iter()
+                .map(|x| x.as_str()
+                    .map(|pattern| FilterPattern::new(folder, pattern))
+                    .ok_or_else(|| WassetError::from_serialize(format!("Unexpected {key} pattern {x:?}; expected string"))))
+                .collect(),
+            None => Ok(Vec::new()),
+            Some(x) => Err(WassetError::from_serialize(format!("Unexpected {key} value {x:?}; expected array of strings")))
+        }
+    }
+}
+
+/// A single glob pattern, along with the folder to which it is relative.
+#[derive(Clone, Debug)]
+struct FilterPattern {
+    /// The folder in which the pattern was declared.
+    base: PathBuf,
+    /// The characters of the pattern, without any trailing `/`.
+    pattern: Vec<char>,
+    /// Whether the pattern only matches folders.
+    dirs_only: bool,
+    /// Whether the pattern matches names at any depth, rather than relative paths.
+    name_only: bool
+}
+
+impl FilterPattern {
+    /// Parses a pattern which was declared in the given folder.
+    fn new(base: &Path, pattern: &str) -> Self {
+        let dirs_only = pattern.ends_with('/');
+        let pattern = pattern.trim_end_matches('/');
+        let name_only = !pattern.contains('/');
+
+        Self {
+            base: base.to_path_buf(),
+            pattern: pattern.trim_start_matches('/').chars().collect(),
+            dirs_only,
+            name_only
+        }
+    }
+
+    /// Determines whether this pattern matches the file or folder at the given path.
+    fn matches(&self, path: &Path, is_dir: bool) -> bool {
+        if self.dirs_only && !is_dir {
+            return false;
+        }
+
+        let Ok(relative) = path.strip_prefix(&self.base) else {
+            return false;
+        };
+
+        let text = if self.name_only {
+            relative.file_name().map(|x| x.to_string_lossy().into_owned()).unwrap_or_default()
+        }
+        else {
+            relative.components().map(|x| x.as_os_str().to_string_lossy()).collect::<Vec<_>>().join("/")
+        };
+
+        glob_matches(&self.pattern, &text.chars().collect::<Vec<_>>())
+    }
+}
+
+/// Determines whether the given text matches a glob pattern.
+fn glob_matches(pattern: &[char], text: &[char]) -> bool {
+    match pattern {
+        [] => text.is_empty(),
+        ['*', '*', '/', rest @ ..] => glob_matches(rest, text)
+            || text.iter().enumerate().any(|(i, x)| *x == '/' && glob_matches(rest, &text[i + 1..])),
+        ['*', '*', rest @ ..] => (0..=text.len()).any(|i| glob_matches(rest, &text[i..])),
+        ['*', rest @ ..] => (0..=text.len())
+            .take_while(|&i| i == 0 || text[i - 1] != '/')
+            .any(|i| glob_matches(rest, &text[i..])),
+        ['?', rest @ ..] => matches!(text, [x, ..] if *x != '/') && glob_matches(rest, &text[1..]),
+        [x, rest @ ..] => text.first() == Some(x) && glob_matches(rest, &text[1..])
+    }
+}
+
+/// The `Wasset.toml` key which adds patterns to [`AssetFilter::include`] for a folder and its subfolders.
+const INCLUDE_KEY: &str = "include";
+
+/// The `Wasset.toml` key which adds patterns to [`AssetFilter::exclude`] for a folder and its subfolders.
+const EXCLUDE_KEY: &str = "exclude";
+
+#[cfg(all(test, feature = "testing"))]
+mod tests {
+    use super::*;
+
+    /// Determines whether the text matches the glob pattern.
+    fn glob(pattern: &str, text: &str) -> bool {
+        glob_matches(&pattern.chars().collect::<Vec<_>>(), &text.chars().collect::<Vec<_>>())
+    }
+
+    /// Encodes the given files with the filter, returning the names of the encoded assets.
+    fn encoded_names(files: &[(&str, &str)], filter: AssetFilter) -> Vec<String> {
+        let options = WassetEncodeOptions { filter, ..Default::default() };
+        let module = WassetModuleBuilder::new().add_assets(testing::encode_text_files(files.iter().copied(), &options).unwrap()).build();
+        let parser = WassetParser::<String>::parse(&module).unwrap();
+        let mut names = parser.manifest().iter().filter_map(|(_, entry)| entry.name().map(str::to_string)).collect::<Vec<_>>();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn globs_match_within_and_across_components() {
+        assert!(glob("*.psd", "art.psd"));
+        assert!(!glob("*.psd", "raw/art.psd"));
+        assert!(glob("?.txt", "a.txt"));
+        assert!(!glob("?.txt", "ab.txt"));
+        assert!(glob("**/*.psd", "art.psd"));
+        assert!(glob("**/*.psd", "raw/deep/art.psd"));
+        assert!(glob("raw/**", "raw/deep/art.psd"));
+        assert!(!glob("raw/*", "raw/deep/art.psd"));
+    }
+
+    #[test]
+    fn patterns_without_slash_match_names_at_any_depth() {
+        let pattern = FilterPattern::new(Path::new("assets"), ".DS_Store");
+        assert!(pattern.matches(Path::new("assets/.DS_Store"), false));
+        assert!(pattern.matches(Path::new("assets/sub/.DS_Store"), false));
+
+        let pattern = FilterPattern::new(Path::new("assets"), "*_source/");
+        assert!(pattern.matches(Path::new("assets/art_source"), true));
+        assert!(!pattern.matches(Path::new("assets/art_source"), false));
+        assert!(!FilterPattern::new(Path::new("assets/sub"), "*.txt").matches(Path::new("assets/a.txt"), false));
+    }
+
+    #[test]
+    fn options_and_folder_tables_filter_files() {
+        let files = [("a.txt", "a"), ("b.psd", "b"), ("raw/c.txt", "c"), ("sub/Wasset.toml", "exclude = [\"*.md\"]"), ("sub/d.md", "d"), ("sub/e.txt", "e")];
+        assert_eq!(encoded_names(&files, AssetFilter { include: Vec::new(), exclude: vec!["*.psd".into(), "raw/".into()] }), ["assets/a", "assets/sub/e"]);
+        assert_eq!(encoded_names(&files, AssetFilter { include: vec!["**/*.txt".into()], exclude: Vec::new() }), ["assets/a", "assets/raw/c", "assets/sub/e"]);
+    }
+}
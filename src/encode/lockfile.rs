@@ -0,0 +1,290 @@
+use crate::*;
+use std::collections::*;
+use std::path::*;
+
+/// Determines whether the hashes of asset files are checked against a
+/// `wasset.hashes` file in the root asset folder.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub enum HashLockMode {
+    /// Asset file hashes are not checked.
+    #[default]
+    Off,
+    /// Encoding fails if any asset file was added, removed, or modified since
+    /// the `wasset.hashes` file was last updated.
+    Verify,
+    /// The `wasset.hashes` file is created or updated to match the current asset files.
+    /// The recorded version of each asset file is incremented whenever its hash changes.
+    Update
+}
+
+/// Determines whether the IDs of asset files are read from and recorded in a `Wasset.lock`
+/// file in the root asset folder. Locked IDs take precedence over the [`IdStrategy`], so they
+/// remain stable across builds and machines even when new IDs are random. Editing the path of an
+/// entry in the lockfile preserves the ID of a file which is moved or renamed.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub enum IdLockMode {
+    /// Asset IDs are not locked.
+    #[default]
+    Off,
+    /// Every asset file must have an ID in the `Wasset.lock` file, and encoding fails if any
+    /// asset file was added or removed since the lockfile was last updated.
+    Verify,
+    /// The `Wasset.lock` file is created or updated to match the current asset files. Files which
+    /// are missing from the lockfile receive new IDs, and entries for removed files are dropped.
+    Update
+}
+
+/// The contents of a `Wasset.lock` file.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+struct IdLockfile {
+    /// A mapping from asset file paths, relative to the root folder, to their IDs.
+    #[serde(default)]
+    assets: BTreeMap<String, WassetId>
+}
+
+/// The contents of a `wasset.hashes` file.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub(crate) struct HashLockfile {
+    /// A mapping from asset file paths, relative to the root folder, to their recorded state.
+    #[serde(default)]
+    assets: BTreeMap<String, LockedAsset>
+}
+
+impl HashLockfile {
+    /// Ensures that an asset file matches the hash recorded for it, so that
+    /// a modified file is rejected before it is passed to the encoder.
+    pub(crate) fn verify_file(&self, name: &str, hash: WassetHash) -> Result<(), WassetError> {
+        match self.assets.get(name) {
+            Some(x) if x.hash == hash.to_string() => Ok(()),
+            Some(_) => Err(WassetError::from_serialize(format!("Asset file {name} does not match its hash in {HASH_LOCKFILE_NAME}"))),
+            None => Err(WassetError::from_serialize(format!("Asset file {name} is missing from {HASH_LOCKFILE_NAME}")))
+        }
+    }
+}
+
+/// The recorded state of a single asset file.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+struct LockedAsset {
+    /// The hexadecimal hash of the file contents.
+    hash: String,
+    /// The revision of the file, which starts at `1` and increases whenever the hash changes.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    version: Option<u64>
+}
+
+impl LockedAsset {
+    /// Gets the revision of the file, treating entries without a recorded version as the first revision.
+    fn version(&self) -> u64 {
+        self.version.unwrap_or(1)
+    }
+}
+
+/// The name of the file which holds asset hashes.
+pub(crate) const HASH_LOCKFILE_NAME: &str = "wasset.hashes";
+
+/// The name of the file which holds locked asset IDs.
+pub(crate) const ID_LOCKFILE_NAME: &str = "Wasset.lock";
+
+/// The comment written at the start of generated ID lockfiles.
+const ID_LOCKFILE_HEADER: &str = "# This file is generated by wasset. It records the ID of every asset file,\n# and should be committed to version control.\n\n";
+
+/// The comment written at the start of generated hash files.
+const HASH_LOCKFILE_HEADER: &str = "# This file is generated by wasset. It records the hash of every asset file,\n# and should be committed to version control.\n\n";
+
+/// Reads the `wasset.hashes` file in the `root` folder if `mode` verifies it, so that each asset
+/// file may be checked before it is encoded. Otherwise, returns an empty lockfile.
+pub(crate) fn read_hash_lockfile(provider: &dyn FileProvider, root: &Path, mode: HashLockMode) -> Result<HashLockfile, WassetError> {
+    if mode != HashLockMode::Verify {
+        return Ok(HashLockfile::default());
+    }
+
+    let path = root.join(HASH_LOCKFILE_NAME);
+    let contents = provider.read_to_string(&path).map_err(|e| WassetError::from_serialize(format!("Failed to read {}: {e}", path.display())))?;
+    toml::from_str::<HashLockfile>(&contents).map_err(|e| WassetError::from_serialize(e).in_file(&path))
+}
+
+/// Checks or updates the `wasset.hashes` file in the `root` folder, according to `mode`,
+/// using the hashes of every asset file that was read during encoding. When verifying,
+/// `locked` holds the lockfile returned by [`read_hash_lockfile`]. Returns the recorded
+/// version of each asset file.
+pub(crate) fn apply_hash_lockfile(provider: &dyn FileProvider, root: &Path, mode: HashLockMode, locked: &HashLockfile, file_hashes: &BTreeMap<String, WassetHash>) -> Result<BTreeMap<String, u64>, WassetError> {
+    let path = root.join(HASH_LOCKFILE_NAME);
+
+    match mode {
+        HashLockMode::Off => Ok(BTreeMap::new()),
+        HashLockMode::Verify => {
+            verify_hashes(locked, file_hashes)?;
+            Ok(locked.assets.iter().map(|(name, asset)| (name.clone(), asset.version())).collect())
+        },
+        HashLockMode::Update => {
+            let previous = provider.read_to_string(&path).ok();
+            let locked = previous.as_deref().and_then(|x| toml::from_str::<HashLockfile>(x).ok()).unwrap_or_default();
+            let current = HashLockfile {
+                assets: file_hashes.iter().map(|(name, hash)| {
+                    let hash = hash.to_string();
+                    let version = match locked.assets.get(name) {
+                        Some(x) if x.hash == hash => x.version(),
+                        Some(x) => x.version() + 1,
+                        None => 1
+                    };
+
+                    (name.clone(), LockedAsset { hash, version: Some(version) })
+                }).collect()
+            };
+
+            let contents = format!("{HASH_LOCKFILE_HEADER}{}", toml::to_string(&current).map_err(WassetError::from_serialize)?);
+            if previous.as_deref() != Some(&contents) {
+                provider.write(&path, contents.as_bytes())?;
+            }
+
+            Ok(current.assets.iter().map(|(name, asset)| (name.clone(), asset.version())).collect())
+        }
+    }
+}
+
+/// Reads the IDs recorded in the `Wasset.lock` file in the `root` folder, according to `mode`,
+/// keyed by the path of each asset file relative to the root.
+pub(crate) fn read_id_lockfile(provider: &dyn FileProvider, root: &Path, mode: IdLockMode) -> Result<BTreeMap<String, WassetId>, WassetError> {
+    let path = root.join(ID_LOCKFILE_NAME);
+    let contents = match mode {
+        IdLockMode::Off => return Ok(BTreeMap::new()),
+        IdLockMode::Verify => provider.read_to_string(&path).map_err(|e| WassetError::from_serialize(format!("Failed to read {}: {e}", path.display())))?,
+        IdLockMode::Update => match provider.read_to_string(&path) {
+            Ok(x) => x,
+            Err(_) => return Ok(BTreeMap::new())
+        }
+    };
+
+    let locked = toml::from_str::<IdLockfile>(&contents).map_err(|e| WassetError::from_serialize(e).in_file(&path))?;
+    let mut seen = BTreeMap::new();
+    for (name, id) in &locked.assets {
+        if let Some(other) = seen.insert(*id, name) {
+            return Err(WassetError::from_serialize(format!("Asset files {other} and {name} share the ID {id:?} in {ID_LOCKFILE_NAME}")));
+        }
+    }
+
+    Ok(locked.assets)
+}
+
+/// Checks or updates the `Wasset.lock` file in the `root` folder, according to `mode`,
+/// using the ID of every asset file that was encoded.
+pub(crate) fn apply_id_lockfile(provider: &dyn FileProvider, root: &Path, mode: IdLockMode, locked: &BTreeMap<String, WassetId>, asset_files: &BTreeMap<String, WassetId>) -> Result<(), WassetError> {
+    match mode {
+        IdLockMode::Off => Ok(()),
+        IdLockMode::Verify => match locked.keys().find(|x| !asset_files.contains_key(*x)) {
+            Some(name) => Err(WassetError::from_serialize(format!("Asset file {name} is listed in {ID_LOCKFILE_NAME} but does not exist"))),
+            None => Ok(())
+        },
+        IdLockMode::Update => {
+            if locked == asset_files && provider.is_file(&root.join(ID_LOCKFILE_NAME)) {
+                return Ok(());
+            }
+
+            let current = IdLockfile { assets: asset_files.clone() };
+            let contents = format!("{ID_LOCKFILE_HEADER}{}", toml::to_string(&current).map_err(WassetError::from_serialize)?);
+            provider.write(&root.join(ID_LOCKFILE_NAME), contents.as_bytes())
+        }
+    }
+}
+
+/// Ensures that the current asset files exactly match those that were locked.
+fn verify_hashes(locked: &HashLockfile, file_hashes: &BTreeMap<String, WassetHash>) -> Result<(), WassetError> {
+    for (name, hash) in file_hashes {
+        locked.verify_file(name, *hash)?;
+    }
+
+    if let Some(name) = locked.assets.keys().find(|x| !file_hashes.contains_key(*x)) {
+        return Err(WassetError::from_serialize(format!("Asset file {name} is listed in {HASH_LOCKFILE_NAME} but does not exist")));
+    }
+
+    Ok(())
+}
+
+#[cfg(all(test, feature = "testing"))]
+mod tests {
+    use super::*;
+
+    /// Writes the given files to an empty temporary asset folder unique to the given test, returning its path.
+    fn temporary_assets(name: &str, files: &[(&str, &str)]) -> PathBuf {
+        let folder = std::env::temp_dir().join(format!("wasset-lockfile-{name}-{}", std::process::id())).join("assets");
+        let _ = std::fs::remove_dir_all(&folder);
+        std::fs::create_dir_all(&folder).unwrap();
+        for (path, contents) in files {
+            std::fs::write(folder.join(path), contents).unwrap();
+        }
+        folder
+    }
+
+    /// Encodes the asset folder, returning the ID and version of each asset by name.
+    fn encode(folder: &Path, options: &WassetEncodeOptions) -> Result<BTreeMap<String, (WassetId, Option<u64>)>, WassetError> {
+        let module = WassetModuleBuilder::new().add_assets(encode_asset_folder_with_options::<testing::TextEncoder>(folder, options)?).build();
+        let parser = WassetParser::<String>::parse(&module)?;
+        Ok(parser.manifest().iter().map(|(id, entry)| (entry.name().unwrap_or_default().to_string(), (id, entry.version()))).collect())
+    }
+
+    #[test]
+    fn hash_lock_rejects_changed_files() {
+        let folder = temporary_assets("hashes", &[("a.txt", "first"), ("b.txt", "second")]);
+        let update = WassetEncodeOptions { hash_lock: HashLockMode::Update, ..Default::default() };
+        let verify = WassetEncodeOptions { hash_lock: HashLockMode::Verify, ..Default::default() };
+        assert!(encode(&folder, &verify).is_err());
+        encode(&folder, &update).unwrap();
+        encode(&folder, &verify).unwrap();
+
+        std::fs::write(folder.join("a.txt"), "modified").unwrap();
+        assert!(encode(&folder, &verify).is_err());
+        std::fs::write(folder.join("a.txt"), "first").unwrap();
+
+        std::fs::write(folder.join("c.txt"), "added").unwrap();
+        assert!(encode(&folder, &verify).is_err());
+        std::fs::remove_file(folder.join("c.txt")).unwrap();
+
+        std::fs::remove_file(folder.join("b.txt")).unwrap();
+        assert!(encode(&folder, &verify).is_err());
+        std::fs::remove_dir_all(folder.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn hash_lock_update_increments_versions() {
+        let folder = temporary_assets("versions", &[("a.txt", "first"), ("b.txt", "second")]);
+        let update = WassetEncodeOptions { hash_lock: HashLockMode::Update, ..Default::default() };
+        let versions = |assets: BTreeMap<String, (WassetId, Option<u64>)>| assets.into_iter().map(|(name, (_, version))| (name, version)).collect::<Vec<_>>();
+        assert_eq!(versions(encode(&folder, &update).unwrap()), [("assets/a".to_string(), Some(1)), ("assets/b".to_string(), Some(1))]);
+
+        std::fs::write(folder.join("a.txt"), "modified").unwrap();
+        assert_eq!(versions(encode(&folder, &update).unwrap()), [("assets/a".to_string(), Some(2)), ("assets/b".to_string(), Some(1))]);
+        assert_eq!(versions(encode(&folder, &update).unwrap()), [("assets/a".to_string(), Some(2)), ("assets/b".to_string(), Some(1))]);
+        std::fs::remove_dir_all(folder.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn id_lock_keeps_random_ids_stable() {
+        let folder = temporary_assets("ids", &[("a.txt", "first"), ("b.txt", "second")]);
+        let update = WassetEncodeOptions { id_lock: IdLockMode::Update, id_strategy: IdStrategy::Random, ..Default::default() };
+        let verify = WassetEncodeOptions { id_lock: IdLockMode::Verify, id_strategy: IdStrategy::Random, ..Default::default() };
+        let first = encode(&folder, &update).unwrap();
+        assert_eq!(encode(&folder, &verify).unwrap(), first);
+
+        std::fs::write(folder.join("c.txt"), "added").unwrap();
+        assert!(encode(&folder, &verify).is_err());
+        let second = encode(&folder, &update).unwrap();
+        assert_eq!(second["assets/a"], first["assets/a"]);
+        assert_eq!(second.len(), 3);
+
+        std::fs::remove_file(folder.join("c.txt")).unwrap();
+        assert!(encode(&folder, &verify).is_err());
+        std::fs::remove_dir_all(folder.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn id_lockfile_with_duplicate_ids_is_rejected() {
+        let id = WassetId::from_bytes([1; 16]);
+        let folder = temporary_assets("duplicates", &[("a.txt", "first"), ("b.txt", "second")]);
+        let lockfile = IdLockfile { assets: BTreeMap::from([("a.txt".to_string(), id), ("b.txt".to_string(), id)]) };
+        std::fs::write(folder.join(ID_LOCKFILE_NAME), toml::to_string(&lockfile).unwrap()).unwrap();
+
+        let verify = WassetEncodeOptions { id_lock: IdLockMode::Verify, ..Default::default() };
+        assert!(encode(&folder, &verify).unwrap_err().to_string().contains("share the ID"));
+        std::fs::remove_dir_all(folder.parent().unwrap()).unwrap();
+    }
+}
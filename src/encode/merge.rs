@@ -0,0 +1,181 @@
+use crate::*;
+use std::path::*;
+
+/// Determines how conflicts are resolved when assets from several folders are merged.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub enum ConflictStrategy {
+    /// Merging fails if two assets share a name within the same hierarchy, or share an ID.
+    #[default]
+    Error,
+    /// The asset from the folder that appears first is kept.
+    KeepFirst,
+    /// The asset from the folder that appears last is kept.
+    KeepLast
+}
+
+/// Serializes the assets from several folders into one set of assets. Each folder is placed into
+/// a top-level hierarchy named by its namespace, and folders which share a namespace are merged.
+/// Conflicting asset names and IDs are resolved according to [`WassetEncodeOptions::conflict_strategy`].
+pub fn encode_asset_folders<A: AssetEncoder>(roots: &[(&str, &Path)], options: &WassetEncodeOptions) -> Result<EncodedAssets, WassetError> {
+    let mut encoded_assets = FxHashMap::<String, AssetHierarchy>::default();
+    let mut folder_data = Vec::new();
+    let mut entries = FxHashMap::default();
+    let mut unaccepted_files = Vec::new();
+
+    // Subfolders are split only once every folder has been merged.
+    let folder_options = WassetEncodeOptions { split_subfolders: false, ..options.clone() };
+    for (index, (namespace, folder)) in roots.iter().enumerate() {
+        let encoded = encode_asset_folder_with_options::<A>(folder, &folder_options)?;
+        let manifest = WassetManifest::from_bytes(&encoded.manifest)?;
+        unaccepted_files.extend(encoded.unaccepted_files);
+        let hierarchy = encoded.encoded_assets.into_values().next().unwrap_or_default();
+
+        let mut conflicts = MergeConflicts::default();
+        merge_hierarchy(encoded_assets.entry(namespace.to_string()).or_default(), hierarchy, namespace, options.conflict_strategy, &mut conflicts)?;
+
+        for id in &conflicts.replaced {
+            entries.remove(id);
+        }
+
+        for (id, mut entry) in manifest.assets {
+            if conflicts.rejected.contains(&id) {
+                continue;
+            }
+
+            if entries.contains_key(&id) {
+                match options.conflict_strategy {
+                    ConflictStrategy::Error => return Err(WassetError::from_serialize(format!("Asset ID {id:?} appears in more than one folder"))),
+                    ConflictStrategy::KeepFirst => continue,
+                    ConflictStrategy::KeepLast => {}
+                }
+            }
+
+            if let Some(name) = &mut entry.name {
+                *name = match name.split_once('/') {
+                    Some((_, rest)) => format!("{namespace}/{rest}"),
+                    None => namespace.to_string()
+                };
+            }
+
+            entries.insert(id, (index, entry));
+        }
+
+        folder_data.push((encoded.data, encoded.preview));
+    }
+
+    // Copy only the assets that were kept, so that no dead bytes remain besides alignment padding.
+    let mut entries = entries.into_iter().collect::<Vec<_>>();
+    entries.sort_by_key(|(_, (index, entry))| (*index, entry.start));
+
+    let mut data = Vec::new();
+    let mut previews = Vec::new();
+    let mut manifest = WassetManifest::default();
+    for (id, (index, mut entry)) in entries {
+        entry.relocate(|range| {
+            let bytes = folder_data[index].0.get(range.start as usize..range.end as usize)
+                .ok_or_else(|| WassetError::from_serialize(format!("Asset {id:?} lies outside of its data section")))?;
+            data.resize(data.len() + payload_padding(data.len(), range.start, options.alignment), 0);
+            let start = data.len() as u32;
+            data.extend_from_slice(bytes);
+            Ok(start)
+        })?;
+
+        entry.copy_preview(&folder_data[index].1, &mut previews);
+        manifest.assets.insert(id, entry);
+    }
+
+    let mut result = EncodedAssets::from_parts(data, previews, manifest, encoded_assets, options)?;
+    result.unaccepted_files = unaccepted_files;
+    Ok(result)
+}
+
+/// Records the assets which lost a name conflict while merging hierarchies.
+#[derive(Default)]
+struct MergeConflicts {
+    /// Previously merged assets which were replaced by a newer asset.
+    replaced: Vec<WassetId>,
+    /// Newly merged assets which were discarded in favor of an existing asset.
+    rejected: FxHashSet<WassetId>
+}
+
+/// Merges the assets of `source` into `target`, resolving name conflicts with the given strategy.
+fn merge_hierarchy(target: &mut AssetHierarchy, source: AssetHierarchy, path: &str, strategy: ConflictStrategy, conflicts: &mut MergeConflicts) -> Result<(), WassetError> {
+    for asset in source.assets {
+        if let Some(existing) = target.assets.iter_mut().find(|x| x.name == asset.name) {
+            match strategy {
+                ConflictStrategy::Error => return Err(WassetError::from_serialize(format!("Asset {path}/{} appears in more than one folder", asset.name))),
+                ConflictStrategy::KeepFirst => { conflicts.rejected.insert(asset.id); },
+                ConflictStrategy::KeepLast => conflicts.replaced.push(std::mem::replace(existing, asset).id)
+            }
+        }
+        else {
+            target.assets.push(asset);
+        }
+    }
+
+    for (name, hierarchy) in source.sub_hierarchies {
+        merge_hierarchy(target.sub_hierarchies.entry(name.clone()).or_default(), hierarchy, &format!("{path}/{name}"), strategy, conflicts)?;
+    }
+
+    Ok(())
+}
+
+#[cfg(all(test, feature = "testing"))]
+mod tests {
+    use super::*;
+
+    /// Writes the given folders of files to an empty temporary folder unique to the given test, returning its path.
+    fn temporary_roots(name: &str, folders: &[(&str, &[(&str, &str)])]) -> PathBuf {
+        let root = std::env::temp_dir().join(format!("wasset-merge-{name}-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        for (folder, files) in folders {
+            std::fs::create_dir_all(root.join(folder)).unwrap();
+            for (path, contents) in *files {
+                std::fs::write(root.join(folder).join(path), contents).unwrap();
+            }
+        }
+        root
+    }
+
+    /// Merges the given namespaced folders of `root`, returning the contents of each asset by name.
+    fn merge(root: &Path, folders: &[(&str, &str)], conflict_strategy: ConflictStrategy) -> Result<Vec<(String, String)>, WassetError> {
+        let roots = folders.iter().map(|(namespace, folder)| (*namespace, root.join(folder))).collect::<Vec<_>>();
+        let roots = roots.iter().map(|(namespace, folder)| (*namespace, folder.as_path())).collect::<Vec<_>>();
+        let options = WassetEncodeOptions { conflict_strategy, ..Default::default() };
+        let module = WassetModuleBuilder::new().add_assets(encode_asset_folders::<testing::TextEncoder>(&roots, &options)?).build();
+        let parser = WassetParser::<String>::parse(&module)?;
+        let mut assets = parser.manifest().iter()
+            .map(|(id, entry)| (entry.name().unwrap_or_default().to_string(), parser.load(id).unwrap().unwrap()))
+            .collect::<Vec<_>>();
+        assets.sort();
+        Ok(assets)
+    }
+
+    #[test]
+    fn folders_are_merged_by_namespace() {
+        let root = temporary_roots("namespaces", &[("base", &[("a.txt", "base a"), ("b.txt", "base b")]), ("extra", &[("c.txt", "extra c")])]);
+        let assets = merge(&root, &[("game", "base"), ("other", "extra")], ConflictStrategy::Error).unwrap();
+        assert_eq!(assets, [
+            ("game/a".to_string(), "base a".to_string()),
+            ("game/b".to_string(), "base b".to_string()),
+            ("other/c".to_string(), "extra c".to_string())
+        ]);
+        std::fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn conflicts_follow_strategy() {
+        let root = temporary_roots("conflicts", &[("base", &[("a.txt", "base a"), ("b.txt", "base b")]), ("patch", &[("a.txt", "patch a")])]);
+        let folders = [("game", "base"), ("game", "patch")];
+        assert!(merge(&root, &folders, ConflictStrategy::Error).is_err());
+        assert_eq!(merge(&root, &folders, ConflictStrategy::KeepFirst).unwrap(), [
+            ("game/a".to_string(), "base a".to_string()),
+            ("game/b".to_string(), "base b".to_string())
+        ]);
+        assert_eq!(merge(&root, &folders, ConflictStrategy::KeepLast).unwrap(), [
+            ("game/a".to_string(), "patch a".to_string()),
+            ("game/b".to_string(), "base b".to_string())
+        ]);
+        std::fs::remove_dir_all(root).unwrap();
+    }
+}
@@ -1,21 +1,352 @@
 use crate::*;
+pub use crate::encode::bindings::*;
+pub use crate::encode::context::*;
+#[cfg(feature = "encoders")]
+pub use crate::encode::encoders::*;
+pub use crate::encode::filter::*;
+pub use crate::encode::lockfile::*;
+pub use crate::encode::merge::*;
+pub use crate::encode::module_builder::*;
+pub use crate::encode::partition::*;
+#[cfg(feature = "encode_macro")]
 pub use crate::encode::proc_macro::*;
-use std::fs::*;
+pub use crate::encode::progress::*;
+pub use crate::encode::provider::*;
+pub use crate::encode::sandbox::*;
+#[cfg(feature = "watch")]
+pub use crate::encode::watch::*;
+use std::collections::*;
 use std::path::*;
+use std::sync::*;
+use std::time::*;
 use toml::*;
 
+/// Generates asset ID declarations for other languages.
+mod bindings;
+
+/// Describes the files from which assets are encoded.
+mod context;
+
+#[cfg(feature = "encoders")]
+/// Provides ready-made encoders for common file types.
+mod encoders;
+
+/// Selects which asset files are encoded with glob patterns.
+mod filter;
+
+/// Checks asset files against recorded hashes and IDs.
+mod lockfile;
+
+/// Combines assets encoded from several folders.
+mod merge;
+
+/// Builds WASM modules which hold only assets.
+mod module_builder;
+
+#[cfg(feature = "encode_macro")]
 /// Defines macros for easily embedding assets.
 mod proc_macro;
 
+/// Splits encoded assets into separate sections by subfolder.
+mod partition;
+
+/// Reports the progress of encoding an asset folder.
+mod progress;
+
+/// Allows for reading asset files from different sources.
+mod provider;
+
+/// Allows for running encoders in a separate process.
+mod sandbox;
+
+#[cfg(feature = "watch")]
+/// Re-encodes asset folders whenever their files change.
+mod watch;
+
 /// Represents a type that can load assets from files on disk.
 pub trait AssetEncoder {
     /// The target asset type that this encoder produces.
     type Target: AssetSchema;
 
     /// Creates a new `Target` asset from file data. The target asset data may be modified
-    /// based upon the file `extension`, or by the `metadata` from a `Wasset.toml` file
-    /// in the same directory.
-    fn encode(extension: &str, metadata: &Table, data: Vec<u8>) -> Result<Option<Self::Target>, WassetError>;
+    /// based upon the file's location and metadata, as described by the `context`. A string
+    /// `name` key in the metadata, which must not contain path separators, replaces the file name
+    /// in the generated constant and the stored manifest, and a `dependencies` array lists
+    /// the names of other assets upon which this asset depends. Dependency names are relative
+    /// to the folder of the `Wasset.toml` file, or to the root asset folder if they begin with `/`.
+    /// An integer `version` key sets the version of the asset, which otherwise comes from
+    /// the `wasset.hashes` file when hash locking is enabled, and a string `kind` key replaces
+    /// the kind reported by [`AssetEncoder::kind`]. A `metadata` table of strings is stored in the
+    /// manifest alongside any metadata set with [`EncodeContext::set_metadata`], and a `tags` array of
+    /// strings allows for finding the asset with `WassetParser::ids_with_tag`.
+    fn encode(context: &EncodeContext, data: Vec<u8>) -> Result<Option<Self::Target>, WassetError>;
+
+    /// Gets the IDs of other assets upon which an encoded asset depends. These are stored
+    /// in the manifest alongside any dependencies declared in a `Wasset.toml` file.
+    fn dependencies(asset: &Self::Target) -> Vec<WassetId> {
+        let _ = asset;
+        Vec::new()
+    }
+
+    /// Produces a small preview of an encoded asset, such as a thumbnail or the beginning of
+    /// a text file. Previews are stored in a separate section, so that asset browsers may show
+    /// them with `WassetParser::preview` without decoding the full assets.
+    fn preview(asset: &Self::Target) -> Option<Vec<u8>> {
+        let _ = asset;
+        None
+    }
+
+    /// Gets the kind of an encoded asset, such as `image` or `text`. The kind is stored in the
+    /// manifest, and the macro groups the generated constants of assets by kind, so that both
+    /// hosts and plugins may find all assets of a kind without decoding them.
+    fn kind(asset: &Self::Target) -> Option<String> {
+        let _ = asset;
+        None
+    }
+}
+
+/// Configures how assets are encoded.
+#[derive(Clone, Debug)]
+pub struct WassetEncodeOptions {
+    /// The alignment, in bytes, of the contents of uncompressed assets which are serialized
+    /// as byte arrays. This must be a power of two no greater than [`MAX_PAYLOAD_ALIGNMENT`].
+    /// Aligned payloads may be viewed in place with `WassetParser::load_pod`, provided that
+    /// the data section itself is aligned, as it is in modules built by [`WassetModuleBuilder`].
+    pub alignment: u32,
+    /// Determines how serialized assets are compressed.
+    pub compression: CompressionMode,
+    /// The format in which assets are serialized. Parsers read the codec of each asset
+    /// from its manifest entry.
+    pub codec: WassetCodec,
+    /// The key with which the payload of every asset is encrypted, if any.
+    /// See [`WassetEncryptionKey`] for details.
+    #[cfg(feature = "encryption")]
+    pub encryption_key: Option<WassetEncryptionKey>,
+    /// Determines how conflicting asset names and IDs are resolved by [`encode_asset_folders`].
+    pub conflict_strategy: ConflictStrategy,
+    /// Determines which files within the asset folder are encoded.
+    pub filter: AssetFilter,
+    /// Determines whether asset files are checked against the hashes recorded
+    /// in a `wasset.hashes` file within the asset folder.
+    pub hash_lock: HashLockMode,
+    /// Determines whether the IDs of asset files are read from and recorded
+    /// in a `Wasset.lock` file within the asset folder.
+    pub id_lock: IdLockMode,
+    /// Determines how the IDs of newly encoded assets are generated.
+    pub id_strategy: IdStrategy,
+    /// The locales of localized asset files. A file such as `dialog.de.txt`, whose name ends in
+    /// one of these locales before its extension, is encoded as the `de` variant of the `dialog.txt`
+    /// asset. If there is no `dialog.txt` file, the variant whose locale comes first in this list
+    /// becomes the asset itself. Variants are loaded with `WassetParser::load_localized`.
+    pub locales: Vec<String>,
+    /// Whether files such as `texture.low.png`, whose name ends in the name of a [`Quality`] before
+    /// its extension, are encoded as tiers of the `texture.png` asset. Each tier is stored as a
+    /// separate range under the ID of that asset, rather than as an asset of its own. If there is no
+    /// `texture.png` file, the highest-quality variant becomes the asset itself. Tiers are loaded
+    /// with `WassetParser::load_variant`.
+    pub quality_variants: bool,
+    /// Determines how each manifest is serialized.
+    pub manifest_format: ManifestFormat,
+    /// A fingerprint of the asset schema, such as a version string, which is recorded in every
+    /// manifest. Parsers which set [`WassetParseOptions::schema`] reject modules whose assets
+    /// were encoded with a different fingerprint, rather than deserializing them incorrectly.
+    pub schema: Option<String>,
+    /// The key with which every set of asset sections is signed, if any.
+    /// See [`EncodedAssets::sign`] for details.
+    #[cfg(feature = "signing")]
+    pub signing_key: Option<ed25519_dalek::SigningKey>,
+    /// Determines whether a perfect-hash index is stored alongside each manifest.
+    pub manifest_layout: ManifestLayout,
+    /// The callback which receives the progress of encoding each asset folder, if any.
+    /// See [`EncodeProgressCallback`] for details.
+    pub progress: Option<EncodeProgressCallback>,
+    /// Determines what happens to files which the encoder does not accept. This may be
+    /// overridden for a folder and its subfolders with a top-level `unknown_files` key in
+    /// the folder's `Wasset.toml` file, whose value is `"skip"`, `"warn"`, or `"error"`.
+    pub unknown_files: UnknownFilePolicy,
+    /// The prefix of the custom section names in which assets are stored. Modules which
+    /// use a different prefix must be read with a matching [`WassetParseOptions::section_prefix`].
+    pub section_prefix: String,
+    /// Whether the assets of each top-level subfolder should be stored in their own pair of
+    /// custom sections, with their own manifest. This allows tooling to strip or ship subfolders
+    /// independently, such as by dropping editor assets from player builds, without re-encoding.
+    pub split_subfolders: bool
+}
+
+impl Default for WassetEncodeOptions {
+    fn default() -> Self {
+        Self {
+            alignment: 1,
+            compression: CompressionMode::default(),
+            codec: WassetCodec::default(),
+            #[cfg(feature = "encryption")]
+            encryption_key: None,
+            conflict_strategy: ConflictStrategy::default(),
+            filter: AssetFilter::default(),
+            hash_lock: HashLockMode::default(),
+            id_lock: IdLockMode::default(),
+            id_strategy: IdStrategy::default(),
+            locales: Vec::new(),
+            quality_variants: false,
+            manifest_format: ManifestFormat::default(),
+            schema: None,
+            #[cfg(feature = "signing")]
+            signing_key: None,
+            manifest_layout: ManifestLayout::default(),
+            progress: None,
+            unknown_files: UnknownFilePolicy::default(),
+            section_prefix: DEFAULT_SECTION_PREFIX.to_string(),
+            split_subfolders: false
+        }
+    }
+}
+
+/// Determines which compression scheme is applied to each serialized asset.
+#[derive(Clone, Debug, PartialEq)]
+pub enum CompressionMode {
+    /// Every asset is compressed with the same scheme.
+    Fixed(WassetCompression),
+    /// Every scheme in `codecs` is tried on each asset, and the smallest result is kept.
+    /// The asset is stored uncompressed unless compression shrinks it by at least the
+    /// `min_savings` fraction.
+    Auto {
+        /// The compression schemes to try.
+        codecs: Vec<WassetCompression>,
+        /// The minimum fraction of the uncompressed size, between `0.0` and `1.0`,
+        /// that compression must save for the compressed result to be used.
+        min_savings: f32
+    }
+}
+
+impl CompressionMode {
+    /// Creates an automatic mode which tries every enabled compression scheme, and keeps
+    /// the compressed result if it saves at least 10% of the uncompressed size.
+    pub fn auto() -> Self {
+        Self::Auto {
+            codecs: WassetCompression::ALL.into_iter().filter(|x| !x.is_none() && x.is_available()).collect(),
+            min_savings: 0.1
+        }
+    }
+
+    /// Compresses the serialized asset according to this mode, returning the scheme
+    /// that was selected and the stored bytes.
+    fn compress(&self, data: Vec<u8>) -> Result<(WassetCompression, Vec<u8>), WassetError> {
+        match self {
+            Self::Fixed(WassetCompression::None) => Ok((WassetCompression::None, data)),
+            Self::Fixed(compression) => Ok((*compression, compression.compress(&data)?)),
+            Self::Auto { codecs, min_savings } => {
+                let threshold = (data.len() as f64 * (1.0 - *min_savings as f64).clamp(0.0, 1.0)) as usize;
+                let mut best = None::<(WassetCompression, Vec<u8>)>;
+                for codec in codecs.iter().filter(|x| !x.is_none()) {
+                    let compressed = codec.compress(&data)?;
+                    if compressed.len() <= threshold && best.as_ref().is_none_or(|(_, x)| compressed.len() < x.len()) {
+                        best = Some((*codec, compressed));
+                    }
+                }
+                Ok(best.unwrap_or((WassetCompression::None, data)))
+            }
+        }
+    }
+}
+
+impl Default for CompressionMode {
+    fn default() -> Self {
+        Self::Fixed(WassetCompression::None)
+    }
+}
+
+/// Determines how the manifest of each set of assets is laid out within a module.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub enum ManifestLayout {
+    /// Only the serialized manifest is stored, which is read into a hash map when parsing.
+    #[default]
+    Map,
+    /// A [`WassetIndex`] over the asset IDs is stored in addition to the manifest. This allows
+    /// shipped, read-only modules to be opened with [`WassetIndexedModule`], which looks up assets
+    /// directly within the module rather than hashing and probing a deserialized map.
+    PerfectHash
+}
+
+impl ManifestLayout {
+    /// Builds the index for the given manifest according to this layout,
+    /// or returns an empty buffer if no index should be stored.
+    pub(crate) fn build_index(&self, manifest: &WassetManifest) -> Vec<u8> {
+        match self {
+            Self::Map => Vec::new(),
+            Self::PerfectHash => build_index(manifest)
+        }
+    }
+}
+
+/// Determines how the IDs of newly encoded assets are generated. Every strategy
+/// produces ordinary [`WassetId`]s, so modules encoded with different strategies
+/// may be read and combined freely.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub enum IdStrategy {
+    /// IDs are entirely random UUIDv4 values.
+    #[default]
+    Random,
+    /// IDs are UUIDv7 values, which begin with a millisecond timestamp. Assets encoded later
+    /// have greater IDs, so they sort by creation time in manifests, logs, and databases.
+    TimeOrdered,
+    /// IDs are UUIDv5 values, derived from a namespace and the path of each asset file relative
+    /// to the asset folder. The same file always receives the same ID across builds and machines,
+    /// so references to it from other modules and caches remain valid. Moving or renaming the
+    /// file changes its ID. Each crate should use its own namespace, since files with the same
+    /// relative path in different crates would otherwise collide.
+    Deterministic {
+        /// The namespace from which IDs are derived.
+        namespace: Uuid
+    }
+}
+
+impl IdStrategy {
+    /// Creates a deterministic strategy whose namespace is derived from the given name,
+    /// such as the name of the crate which embeds the assets.
+    pub fn deterministic(name: &str) -> Self {
+        Self::Deterministic {
+            namespace: Uuid::new_v5(&DETERMINISTIC_ID_NAMESPACE, name.as_bytes())
+        }
+    }
+
+    /// Generates the ID for the asset file at `path`, which is relative to the asset folder
+    /// and uses `/` as its separator. The path only influences deterministic IDs.
+    pub fn generate(&self, path: &str) -> WassetId {
+        WassetId::from(match self {
+            Self::Random => Uuid::new_v4(),
+            Self::TimeOrdered => Uuid::now_v7(),
+            Self::Deterministic { namespace } => Uuid::new_v5(namespace, path.as_bytes())
+        })
+    }
+}
+
+/// The namespace from which the namespaces of [`IdStrategy::deterministic`] are derived.
+const DETERMINISTIC_ID_NAMESPACE: Uuid = Uuid::from_u128(0x5f0c_2a1e_8b4d_4c7a_9e36_d1f7_a0b2_c948);
+
+/// Determines what happens when an [`AssetEncoder`] does not accept a file.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub enum UnknownFilePolicy {
+    /// The file is silently ignored.
+    #[default]
+    Skip,
+    /// The file is ignored, and is listed in [`EncodedAssets::unaccepted_files`] so that the
+    /// caller may warn about it. A `tracing` warning is also emitted if that feature is enabled.
+    Warn,
+    /// Encoding fails.
+    Error
+}
+
+impl std::str::FromStr for UnknownFilePolicy {
+    type Err = WassetError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "skip" => Ok(Self::Skip),
+            "warn" => Ok(Self::Warn),
+            "error" => Ok(Self::Error),
+            _ => Err(WassetError::from_serialize(format!("Unknown file policy {s:?}; expected \"skip\", \"warn\", or \"error\"")))
+        }
+    }
 }
 
 /// Denotes an asset that has been serialized.
@@ -24,7 +355,35 @@ pub struct EncodedAsset {
     /// The name of the asset that should be displayed to the developer.
     pub name: String,
     /// The asset ID.
-    pub id: WassetId
+    pub id: WassetId,
+    /// The size of the serialized asset in bytes, before compression. This is the
+    /// size of the data that a host obtains when loading the raw asset.
+    pub size: usize,
+    /// The kind of the asset, as reported by its encoder or `Wasset.toml` file.
+    pub kind: Option<String>,
+    /// The time spent on each stage of encoding the asset.
+    pub timings: EncodeTimings
+}
+
+/// Measures the time spent on each stage of encoding a single asset. These allow for
+/// finding the files and encoders which make encoding, and thus macro expansion, slow.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct EncodeTimings {
+    /// The time spent reading the asset file.
+    pub read: Duration,
+    /// The time spent in [`AssetEncoder::encode`].
+    pub encode: Duration,
+    /// The time spent serializing and hashing the encoded asset.
+    pub serialize: Duration,
+    /// The time spent compressing the serialized asset.
+    pub compress: Duration
+}
+
+impl EncodeTimings {
+    /// Gets the total time spent encoding the asset.
+    pub fn total(&self) -> Duration {
+        self.read + self.encode + self.serialize + self.compress
+    }
 }
 
 /// Represents a hierarchy of assets that have been serialized.
@@ -36,8 +395,74 @@ pub struct AssetHierarchy {
     pub sub_hierarchies: FxHashMap<String, AssetHierarchy>
 }
 
+impl AssetHierarchy {
+    /// Reconstructs the hierarchies of the named assets in a manifest, keyed by the name of the
+    /// root folder from which they were encoded. Assets which were not encoded from a file are
+    /// omitted. Encoding timings are not recorded in manifests, so they are left empty.
+    pub fn from_manifest(manifest: &WassetManifest) -> FxHashMap<String, AssetHierarchy> {
+        let mut result = FxHashMap::<String, AssetHierarchy>::default();
+        for (id, entry) in manifest.iter() {
+            let Some((root, rest)) = entry.name().and_then(|x| x.split_once('/')) else {
+                continue;
+            };
+
+            let mut components = rest.split('/').collect::<Vec<_>>();
+            let name = components.pop().unwrap_or_default();
+            let level = components.into_iter().fold(result.entry(root.to_string()).or_default(), |level, folder| {
+                level.sub_hierarchies.entry(folder.to_string()).or_default()
+            });
+
+            level.assets.push(EncodedAsset {
+                name: name.to_string(),
+                id,
+                size: entry.uncompressed_size().unwrap_or_default() as usize,
+                kind: entry.kind().map(str::to_string),
+                timings: EncodeTimings::default()
+            });
+        }
+
+        result
+    }
+
+    /// Gets an iterator over the assets on every level of the hierarchy, in no particular order.
+    /// This allows for finding the assets which took longest to encode:
+    ///
+    /// ```
+    /// # let hierarchy = wasset::AssetHierarchy::default();
+    /// let mut assets = hierarchy.all_assets().collect::<Vec<_>>();
+    /// assets.sort_by_key(|x| std::cmp::Reverse(x.timings.total()));
+    /// ```
+    pub fn all_assets(&self) -> impl '_ + Iterator<Item = &EncodedAsset> {
+        let mut pending = vec![self];
+        std::iter::from_fn(move || {
+            let level = pending.pop()?;
+            pending.extend(level.sub_hierarchies.values());
+            Some(level.assets.iter())
+        }).flatten()
+    }
+
+    /// Ensures that the `_LEN` constant generated for each asset does not share a name with the
+    /// constant of another asset on the same level, once both names are converted to identifiers
+    /// by `identifier`. For example, assets named `name` and `name_len` cannot be declared together.
+    pub(crate) fn check_length_constants(&self, identifier: &impl Fn(&str) -> String) -> Result<(), WassetError> {
+        let names = self.assets.iter().map(|x| identifier(&x.name)).collect::<FxHashSet<_>>();
+        for asset in &self.assets {
+            let length_name = identifier(&format!("{}_LEN", asset.name));
+            if names.contains(&length_name) {
+                return Err(WassetError::from_serialize(format!("The length constant {length_name} of asset {:?} has the same name as the constant of another asset", asset.name)));
+            }
+        }
+
+        for hierarchy in self.sub_hierarchies.values() {
+            hierarchy.check_length_constants(identifier)?;
+        }
+
+        Ok(())
+    }
+}
+
 /// Holds an entire set of assets that have been serialized from files on disk.
-#[derive(Debug, Default)]
+#[derive(Clone, Debug, Default)]
 pub struct EncodedAssets {
     /// The data that should be written to the custom section for holding the assets.
     pub data: Vec<u8>,
@@ -45,84 +470,576 @@ pub struct EncodedAssets {
     pub encoded_assets: FxHashMap<String, AssetHierarchy>,
     /// The serialized manifest describing the assets.
     pub manifest: Vec<u8>,
+    /// The data that should be written to the custom section for holding asset previews.
+    pub preview: Vec<u8>,
+    /// The serialized [`WassetIndex`] over the manifest, or an empty buffer if
+    /// the assets were encoded with [`ManifestLayout::Map`].
+    pub index: Vec<u8>,
+    /// The assets of each top-level subfolder, if they were encoded with
+    /// [`WassetEncodeOptions::split_subfolders`]. Otherwise, this is empty.
+    pub partitions: Vec<EncodedPartition>,
+    /// The signature over the other sections, or an empty buffer if the assets are not signed.
+    pub signature: Vec<u8>,
+    /// The files which the encoder did not accept within folders whose [`UnknownFilePolicy`] is `Warn`.
+    pub unaccepted_files: Vec<PathBuf>
 }
 
 /// Loads all assets from the provided folder into an `EncodedAssets` structure.
 pub fn encode_asset_folder<A: AssetEncoder>(folder: &Path) -> Result<EncodedAssets, WassetError> {
+    encode_asset_folder_with_options::<A>(folder, &WassetEncodeOptions::default())
+}
+
+/// Loads all assets from the provided folder into an `EncodedAssets` structure,
+/// using the provided options.
+pub fn encode_asset_folder_with_options<A: AssetEncoder>(folder: &Path, options: &WassetEncodeOptions) -> Result<EncodedAssets, WassetError> {
+    encode_asset_folder_from::<A>(&DiskFileProvider, folder, options)
+}
+
+/// Loads all assets from the provided folder of a [`FileProvider`] into an `EncodedAssets`
+/// structure, using the provided options. This allows for encoding assets from sources
+/// such as archives without unpacking them to disk. With the **parallel** feature, files are
+/// read and encoded on a pool of threads, but are written in the same order as they would
+/// be otherwise, so the result does not depend upon scheduling. Every file is encoded before
+/// any are written, so that files consumed through [`EncodeContext::read_sibling`] are skipped
+/// regardless of whether they come before or after the asset which consumed them. Assets whose
+/// stored bytes would be identical, such as those of copied files, share a single copy of the data.
+#[cfg_attr(feature = "tracing", tracing::instrument(name = "wasset::encode", level = "debug", skip_all, fields(folder = %folder.display())))]
+pub fn encode_asset_folder_from<A: AssetEncoder>(provider: &dyn FileProvider, folder: &Path, options: &WassetEncodeOptions) -> Result<EncodedAssets, WassetError> {
     let mut data = Vec::new();
     let mut hierarchy = AssetHierarchy::default();
     let mut manifest = WassetManifest::default();
+    let mut asset_files = BTreeMap::new();
+    let mut file_hashes = BTreeMap::new();
+    let locked_ids = read_id_lockfile(provider, folder, options.id_lock)?;
+    let locked_hashes = read_hash_lockfile(provider, folder, options.hash_lock)?;
+    let mut named_dependencies = Vec::new();
+    let mut variants = Vec::new();
+    let mut previews = Vec::new();
+    let mut unaccepted_files = Vec::new();
 
     let base = folder.parent().ok_or_else(|| WassetError::from_serialize("Folder must have name."))?;
-    load_assets_in_folder::<A>(base, folder, &mut EncodingOperation {
+    let mut pending = Vec::new();
+    let settings = FolderSettings {
+        unknown_files: options.unknown_files,
+        filter: FolderFilter::new(folder, &options.filter),
+        tables: Vec::new()
+    };
+    find_asset_files(provider, folder, folder, &mut Vec::new(), &mut hierarchy, &settings, &mut pending)?;
+
+    let progress = ProgressTracker::new(options.progress.as_ref(), pending.len());
+    let prepare = |file: &PendingFile| {
+        progress.start(&file.path);
+        let prepared = prepare_asset_file::<A>(provider, base, folder, file, &locked_hashes, options).map_err(|e| e.in_file(&file.path))?;
+        progress.finish(&file.path, prepared.size);
+        Ok::<_, WassetError>(prepared)
+    };
+
+    #[cfg(feature = "parallel")]
+    let prepared = {
+        use rayon::prelude::*;
+        pending.par_iter().map(prepare).collect::<Result<Vec<_>, _>>()?
+    };
+
+    #[cfg(not(feature = "parallel"))]
+    let prepared = pending.iter().map(prepare).collect::<Result<Vec<_>, _>>()?;
+
+    let consumed = prepared.iter().flat_map(|x| x.consumed.iter().cloned()).collect::<HashSet<_>>();
+
+    let mut operation = EncodingOperation {
+        asset_files: &mut asset_files,
+        consumed: &consumed,
         data: &mut data,
+        payloads: &mut WrittenPayloads::default(),
         encoded_assets: &mut hierarchy,
-        manifest: &mut manifest
-    })?;
+        file_hashes: &mut file_hashes,
+        variants: &mut variants,
+        locked_ids: &locked_ids,
+        manifest: &mut manifest,
+        named_dependencies: &mut named_dependencies,
+        options,
+        previews: &mut previews,
+        unaccepted_files: &mut unaccepted_files
+    };
+
+    for (file, prepared) in pending.iter().zip(prepared) {
+        commit_asset_file(file, prepared, &mut operation).map_err(|e| e.in_file(&file.path))?;
+    }
+
+    resolve_variants(&mut manifest, &mut hierarchy, &mut asset_files, &options.locales, variants)?;
+    resolve_named_dependencies(&mut manifest, &name_for_path(folder)?, named_dependencies)?;
+    apply_id_lockfile(provider, folder, options.id_lock, &locked_ids, &asset_files)?;
+
+    for (file, version) in apply_hash_lockfile(provider, folder, options.hash_lock, &locked_hashes, &file_hashes)? {
+        if let Some(entry) = asset_files.get(&file).and_then(|x| manifest.assets.get_mut(x)) {
+            entry.version.get_or_insert(version);
+        }
+    }
 
     let name = name_for_path(folder)?;
     let encoded_assets = FxHashMap::from_iter([(name.into_owned(), hierarchy)]);
+    let mut result = EncodedAssets::from_parts(data, previews, manifest, encoded_assets, options)?;
+    result.unaccepted_files = unaccepted_files;
+    Ok(result)
+}
 
-    Ok(EncodedAssets {
-        data,
-        encoded_assets,
-        manifest: rmp_serde::to_vec_named(&manifest).map_err(WassetError::from_serialize)?
-    })
+/// Serializes the provided assets into an `EncodedAssets` structure, using the provided
+/// options. The result has an empty asset hierarchy, since the assets have no names.
+pub fn encode_assets<T: AssetSchema>(assets: impl IntoIterator<Item = (WassetId, T)>, options: &WassetEncodeOptions) -> Result<EncodedAssets, WassetError> {
+    let mut data = Vec::new();
+    let mut payloads = WrittenPayloads::default();
+    let mut manifest = WassetManifest::default();
+
+    for (id, asset) in assets {
+        write_asset(&mut data, &mut payloads, &mut manifest, id, &asset, options, &mut EncodeTimings::default())?;
+    }
+
+    EncodedAssets::from_parts(data, Vec::new(), manifest, FxHashMap::default(), options)
 }
 
-/// Represents an ongoing operation to encode assets.
+/// Represents an ongoing operation to write encoded assets.
 struct EncodingOperation<'a> {
+    /// The ID of the asset encoded from each file, keyed by the file path relative to the root.
+    pub asset_files: &'a mut BTreeMap<String, WassetId>,
+    /// The files which were read by the encoder to produce other assets, and should not be encoded themselves.
+    pub consumed: &'a HashSet<PathBuf>,
     /// The data section.
     pub data: &'a mut Vec<u8>,
-    /// The current hierarchy level.
+    /// The payloads which have been written to the data section.
+    pub payloads: &'a mut WrittenPayloads,
+    /// The root of the asset hierarchy.
     pub encoded_assets: &'a mut AssetHierarchy,
+    /// The hashes of all asset files that have been read, keyed by their path relative to the root.
+    pub file_hashes: &'a mut BTreeMap<String, WassetHash>,
+    /// The variants of other assets, which are grouped with those assets after all assets are encoded.
+    pub variants: &'a mut Vec<PendingVariant>,
+    /// The IDs recorded in the `Wasset.lock` file, keyed by their path relative to the root.
+    pub locked_ids: &'a BTreeMap<String, WassetId>,
     /// The manifest.
-    pub manifest: &'a mut WassetManifest
+    pub manifest: &'a mut WassetManifest,
+    /// The dependencies declared by name in `Wasset.toml` files, which are resolved after all assets are encoded.
+    pub named_dependencies: &'a mut Vec<NamedDependencies>,
+    /// The options that control encoding.
+    pub options: &'a WassetEncodeOptions,
+    /// The preview section.
+    pub previews: &'a mut Vec<u8>,
+    /// The files which the encoder did not accept, and which should be reported to the caller.
+    pub unaccepted_files: &'a mut Vec<PathBuf>
+}
+
+/// An asset file which has been found within the asset folder, but not yet encoded.
+struct PendingFile {
+    /// The path of the file.
+    pub path: PathBuf,
+    /// The folder which holds the file.
+    pub folder: PathBuf,
+    /// The names of the folders between the root and the file, which locate its level of the asset hierarchy.
+    pub hierarchy: Vec<String>,
+    /// The settings of the folder which holds the file.
+    pub settings: Arc<FolderSettings>
+}
+
+/// The settings of a folder, which combine its `Wasset.toml` file with those of its parent folders.
+#[derive(Clone)]
+struct FolderSettings {
+    /// Determines what happens to files which the encoder does not accept.
+    pub unknown_files: UnknownFilePolicy,
+    /// Selects which files within the folder are encoded.
+    pub filter: FolderFilter,
+    /// The contents of the `Wasset.toml` files of every folder from the root to this one.
+    pub tables: Vec<Arc<Table>>
+}
+
+/// An asset file which has been read and encoded, but not yet assigned an ID or written to the data section.
+struct PreparedFile {
+    /// The path of the file relative to the root.
+    pub file: String,
+    /// The hash of the file contents, if hash locking is enabled.
+    pub hash: Option<WassetHash>,
+    /// The size of the file contents, or zero if the file was not read.
+    pub size: usize,
+    /// The other files which the encoder read to produce the asset.
+    pub consumed: Vec<PathBuf>,
+    /// The encoded asset, or `None` if the encoder did not accept the file.
+    pub asset: Option<PreparedAsset>
 }
 
-/// Loads all assets from a certain folder into the `operation`.
-fn load_assets_in_folder<A: AssetEncoder>(base: &Path, folder: &Path, operation: &mut EncodingOperation) -> Result<(), WassetError> {
-    let master_table = if let Ok(options) = read_to_string(folder.join("Wasset.toml")) {
-        options.parse::<Table>().map_err(WassetError::from_serialize)?
+/// An asset that has been encoded and serialized from a file.
+struct PreparedAsset {
+    /// The name of the asset in its level of the hierarchy.
+    pub entry_name: String,
+    /// The path of the asset, beginning with the root asset folder.
+    pub name: String,
+    /// The serialized asset.
+    pub serialized: SerializedAsset,
+    /// The IDs of the other assets upon which this asset depends, as reported by the encoder.
+    pub dependencies: Vec<WassetId>,
+    /// The names of the other assets upon which this asset depends, as declared in `Wasset.toml`.
+    pub dependency_names: Vec<String>,
+    /// The path of the folder which holds the file, beginning with the root asset folder.
+    pub folder: String,
+    /// The version declared in `Wasset.toml`.
+    pub version: Option<u64>,
+    /// The kind of the asset.
+    pub kind: Option<String>,
+    /// The preview produced by the encoder.
+    pub preview: Option<Vec<u8>>,
+    /// The metadata attached by the encoder and declared in `Wasset.toml`.
+    pub metadata: BTreeMap<String, String>,
+    /// The tags declared in `Wasset.toml`.
+    pub tags: Vec<String>,
+    /// The locale or quality tier of the asset, if the file is a variant of another.
+    pub variant: Option<AssetVariant>,
+    /// The time spent on each stage of encoding the asset.
+    pub timings: EncodeTimings
+}
+
+/// Finds all asset files within a certain folder and its subfolders, adding a level to the `hierarchy`
+/// for each subfolder. The folder's settings are derived from those of its `parent`, and files and folders
+/// which its filter rejects are skipped. Files are listed in the order in which they should be written.
+fn find_asset_files(provider: &dyn FileProvider, root: &Path, folder: &Path, levels: &mut Vec<String>, hierarchy: &mut AssetHierarchy, parent: &FolderSettings, pending: &mut Vec<PendingFile>) -> Result<(), WassetError> {
+    let table_path = folder.join("Wasset.toml");
+    let table = if let Ok(options) = provider.read_to_string(&table_path) {
+        options.parse::<Table>().map_err(|e| WassetError::from_serialize(e).in_file(&table_path))?
     }
     else {
         Table::default()
     };
 
-    for to_read in read_dir(folder).map_err(WassetError::from_serialize)? {
-        let entry = to_read.map_err(WassetError::from_serialize)?;
-        let path = entry.path();
-        if path.is_dir() {
-            let entry_name = name_for_path(&path)?;
-            load_assets_in_folder::<A>(base, &path, &mut EncodingOperation {
-                data: operation.data,
-                encoded_assets: operation.encoded_assets.sub_hierarchies.entry(entry_name.into_owned()).or_default(),
-                manifest: operation.manifest
-            })?;
-        }
-        else if path.is_file() {
-            let asset_path = path.strip_prefix(base).ok().map(|x| x.with_extension(""));
-            if let Some(local_path) = asset_path {
-                let default_table = Table::default();
-                let file_name = name_for_path(&path)?;
-                let metadata = match master_table.get(&*file_name) {
-                    Some(Value::Table(x)) => x,
-                    None => &default_table,
-                    Some(x) => return Err(WassetError::from_serialize(format!("Unexpected metadata value {x:?} for asset {file_name}; expected table")))
-                };
-
-                if let Some(asset) = A::encode(&path.extension().unwrap_or_default().to_string_lossy(), metadata, read(&path).map_err(WassetError::from_serialize)?)? {
-                    let entry_name = name_for_path(&local_path)?;
-                    let id = WassetId::from(Uuid::new_v4());
-
-                    let start = operation.data.len() as u32;
-                    rmp_serde::encode::write_named(operation.data, &asset).map_err(WassetError::from_serialize)?;
-                    let end = operation.data.len() as u32;
-                    operation.manifest.asset_ranges.insert(id, start..end);
-                    operation.encoded_assets.assets.push(EncodedAsset {
-                        name: entry_name.into_owned(),
-                        id
-                    })
+    let unknown_files = match table.get(UNKNOWN_FILES_KEY) {
+        Some(Value::String(x)) => x.parse().map_err(|e: WassetError| e.in_file(&table_path))?,
+        None => parent.unknown_files,
+        Some(x) => return Err(WassetError::from_serialize(format!("Unexpected {UNKNOWN_FILES_KEY} value {x:?}; expected string")).in_file(&table_path))
+    };
+
+    let filter = parent.filter.with_table(folder, &table).map_err(|e| e.in_file(&table_path))?;
+    let mut tables = parent.tables.clone();
+    tables.push(Arc::new(table));
+    let settings = Arc::new(FolderSettings { unknown_files, filter, tables });
+
+    for path in provider.read_dir(folder).map_err(|e| e.in_file(folder))? {
+        let is_dir = provider.is_dir(&path);
+        if settings.filter.excludes(&path, is_dir) {
+            continue;
+        }
+
+        if is_dir {
+            let entry_name = name_for_path(&path)?.into_owned();
+            levels.push(entry_name.clone());
+            find_asset_files(provider, root, &path, levels, hierarchy.sub_hierarchies.entry(entry_name).or_default(), &settings, pending)?;
+            levels.pop();
+        }
+        else if provider.is_file(&path) && settings.filter.includes(&path) && path != root.join(HASH_LOCKFILE_NAME) && path != root.join(ID_LOCKFILE_NAME) {
+            pending.push(PendingFile {
+                path,
+                folder: folder.to_path_buf(),
+                hierarchy: levels.clone(),
+                settings: settings.clone()
+            });
+        }
+    }
+
+    Ok(())
+}
+
+/// Reads and encodes a single asset file, using the metadata for it in the folder's `Wasset.toml` table.
+/// When verifying hashes, the file is checked against `locked_hashes` before it is encoded. This does
+/// not modify any shared state, so that files may be prepared concurrently.
+fn prepare_asset_file<A: AssetEncoder>(provider: &dyn FileProvider, base: &Path, root: &Path, pending: &PendingFile, locked_hashes: &HashLockfile, options: &WassetEncodeOptions) -> Result<PreparedFile, WassetError> {
+    let path = &pending.path;
+    let file = root_relative_name(root, path);
+    let Some(local_path) = path.strip_prefix(base).ok().map(|x| x.with_extension("")) else {
+        return Ok(PreparedFile { file, hash: None, size: 0, consumed: Vec::new(), asset: None });
+    };
+
+    let default_table = Table::default();
+    let file_name = name_for_path(path)?;
+    let folder_metadata = pending.settings.tables.iter().map(|x| &**x).collect::<Vec<_>>();
+    let metadata = match folder_metadata.last().and_then(|x| x.get(&*file_name)) {
+        Some(Value::Table(x)) => x,
+        None => &default_table,
+        Some(x) => return Err(WassetError::from_serialize(format!("Unexpected metadata value {x:?} for asset {file_name}; expected table")))
+    };
+
+    let mut timings = EncodeTimings::default();
+    let read_start = Instant::now();
+    let file_data = provider.read(path)?;
+    timings.read = read_start.elapsed();
+    let hash = (options.hash_lock != HashLockMode::Off).then(|| WassetHash::from_bytes(*blake3::hash(&file_data).as_bytes()));
+    let size = file_data.len();
+    if let (HashLockMode::Verify, Some(hash)) = (options.hash_lock, hash) {
+        locked_hashes.verify_file(&file, hash)?;
+    }
+
+    let variant = local_path.extension()
+        .map(|x| x.to_string_lossy())
+        .and_then(|x| if options.locales.iter().any(|locale| *locale == x) {
+            Some(VariantKey::Locale(x.into_owned()))
+        }
+        else {
+            x.parse().ok().filter(|_| options.quality_variants).map(VariantKey::Quality)
+        })
+        .map(|key| AssetVariant {
+            key,
+            file: root_relative_name(root, &path.with_extension("").with_extension(path.extension().unwrap_or_default()))
+        });
+    let local_path = if variant.is_some() { local_path.with_extension("") } else { local_path };
+
+    let local_path = match metadata.get("name") {
+        Some(Value::String(x)) if x.is_empty() || x == "." || x == ".." || x.contains(['/', '\\']) => {
+            return Err(WassetError::from_serialize(format!("Name {x:?} for asset {file_name} must be a single path component")))
+        },
+        Some(Value::String(x)) => local_path.with_file_name(x),
+        None => local_path,
+        Some(x) => return Err(WassetError::from_serialize(format!("Unexpected name value {x:?} for asset {file_name}; expected string")))
+    };
+
+    let version = match metadata.get("version") {
+        Some(Value::Integer(x)) => Some(u64::try_from(*x).map_err(|_| WassetError::from_serialize(format!("Version {x} for asset {file_name} must not be negative")))?),
+        None => None,
+        Some(x) => return Err(WassetError::from_serialize(format!("Unexpected version value {x:?} for asset {file_name}; expected integer")))
+    };
+
+    let dependency_names = match metadata.get("dependencies") {
+        Some(Value::Array(x)) => x.iter()
+            .map(|x| x.as_str().map(str::to_string).ok_or_else(|| WassetError::from_serialize(format!("Unexpected dependency value {x:?} for asset {file_name}; expected string"))))
+            .collect::<Result<Vec<_>, _>>()?,
+        None => Vec::new(),
+        Some(x) => return Err(WassetError::from_serialize(format!("Unexpected dependencies value {x:?} for asset {file_name}; expected array")))
+    };
+
+    let kind = match metadata.get("kind") {
+        Some(Value::String(x)) => Some(x.clone()),
+        None => None,
+        Some(x) => return Err(WassetError::from_serialize(format!("Unexpected kind value {x:?} for asset {file_name}; expected string")))
+    };
+
+    let tags = match metadata.get("tags") {
+        Some(Value::Array(x)) => x.iter()
+            .map(|x| x.as_str().map(str::to_string).ok_or_else(|| WassetError::from_serialize(format!("Unexpected tag value {x:?} for asset {file_name}; expected string"))))
+            .collect::<Result<Vec<_>, _>>()?,
+        None => Vec::new(),
+        Some(x) => return Err(WassetError::from_serialize(format!("Unexpected tags value {x:?} for asset {file_name}; expected array")))
+    };
+
+    let declared_metadata = match metadata.get("metadata") {
+        Some(Value::Table(x)) => x.iter()
+            .map(|(key, value)| match value {
+                Value::String(x) => Ok((key.clone(), x.clone())),
+                Value::Integer(_) | Value::Float(_) | Value::Boolean(_) | Value::Datetime(_) => Ok((key.clone(), value.to_string())),
+                x => Err(WassetError::from_serialize(format!("Unexpected metadata value {x:?} for key {key} of asset {file_name}; expected string")))
+            })
+            .collect::<Result<Vec<_>, _>>()?,
+        None => Vec::new(),
+        Some(x) => return Err(WassetError::from_serialize(format!("Unexpected metadata value {x:?} for asset {file_name}; expected table")))
+    };
+
+    let encode_start = Instant::now();
+    let files = FileAccess::new(provider, root, path);
+    let context = EncodeContext {
+        extension: &path.extension().unwrap_or_default().to_string_lossy(),
+        path,
+        relative_path: path.strip_prefix(root).unwrap_or(path),
+        metadata,
+        folder_metadata: &folder_metadata,
+        files: &files
+    };
+    let asset = A::encode(&context, file_data)?;
+    timings.encode = encode_start.elapsed();
+
+    let Some(asset) = asset else {
+        return Ok(PreparedFile { file, hash, size, consumed: Vec::new(), asset: None });
+    };
+
+    let serialized = SerializedAsset::new(&asset, options, &mut timings)?;
+    let (consumed, mut asset_metadata) = files.finish();
+    asset_metadata.extend(declared_metadata);
+    Ok(PreparedFile {
+        file,
+        hash,
+        size,
+        consumed,
+        asset: Some(PreparedAsset {
+            entry_name: name_for_path(&local_path)?.into_owned(),
+            name: root_relative_name(base, &local_path),
+            serialized,
+            dependencies: A::dependencies(&asset),
+            dependency_names,
+            folder: root_relative_name(base, &pending.folder),
+            version,
+            kind: kind.or_else(|| A::kind(&asset)),
+            preview: A::preview(&asset),
+            metadata: asset_metadata,
+            tags,
+            variant,
+            timings
+        })
+    })
+}
+
+/// Assigns an ID to a prepared asset file, and writes it to the data section, manifest, and hierarchy.
+fn commit_asset_file(pending: &PendingFile, prepared: PreparedFile, operation: &mut EncodingOperation) -> Result<(), WassetError> {
+    let PreparedFile { file, hash, asset, .. } = prepared;
+    if let Some(hash) = hash {
+        operation.file_hashes.insert(file.clone(), hash);
+    }
+
+    if operation.consumed.contains(&pending.path) {
+        return Ok(());
+    }
+
+    let Some(asset) = asset else {
+        let file_name = name_for_path(&pending.path)?;
+        if file_name != "Wasset.toml" {
+            match pending.settings.unknown_files {
+                UnknownFilePolicy::Skip => {},
+                UnknownFilePolicy::Warn => {
+                    #[cfg(feature = "tracing")]
+                    tracing::warn!(path = %pending.path.display(), "asset file was not accepted by the encoder");
+
+                    operation.unaccepted_files.push(pending.path.clone());
+                },
+                UnknownFilePolicy::Error => return Err(WassetError::from_serialize(format!("Asset file {file_name} was not accepted by the encoder")))
+            }
+        }
+
+        return Ok(());
+    };
+
+    let id = match operation.locked_ids.get(&file) {
+        Some(id) => *id,
+        None if operation.options.id_lock == IdLockMode::Verify => return Err(WassetError::from_serialize(format!("Asset file {file} is missing from {ID_LOCKFILE_NAME}"))),
+        None => operation.options.id_strategy.generate(&file)
+    };
+
+    let entry = asset.serialized.append(operation.data, operation.payloads, operation.manifest, id, operation.options)?;
+    entry.name = Some(match &asset.variant {
+        Some(variant) => format!("{}.{}", asset.name, variant.key.name()),
+        None => asset.name.clone()
+    });
+    entry.dependencies = asset.dependencies;
+    entry.version = asset.version;
+    entry.kind = asset.kind;
+    entry.metadata = asset.metadata;
+    entry.tags = asset.tags;
+    if let Some(preview) = asset.preview {
+        let start = operation.previews.len() as u32;
+        operation.previews.extend_from_slice(&preview);
+        entry.preview = Some(start..operation.previews.len() as u32);
+    }
+
+    let kind = entry.kind.clone();
+    operation.asset_files.insert(file, id);
+    let size = entry.uncompressed_size().unwrap_or_default() as usize;
+
+    if !asset.dependency_names.is_empty() {
+        operation.named_dependencies.push(NamedDependencies {
+            id,
+            folder: asset.folder,
+            names: asset.dependency_names
+        });
+    }
+
+    let encoded = EncodedAsset {
+        name: asset.entry_name,
+        id,
+        size,
+        kind,
+        timings: asset.timings
+    };
+
+    if let Some(variant) = asset.variant {
+        operation.variants.push(PendingVariant {
+            variant,
+            name: asset.name,
+            hierarchy: pending.hierarchy.clone(),
+            asset: encoded
+        });
+    }
+    else {
+        asset_level(operation.encoded_assets, &pending.hierarchy).assets.push(encoded);
+    }
+
+    Ok(())
+}
+
+/// Gets the level of the asset hierarchy which is reached by descending through the given folders.
+fn asset_level<'a>(hierarchy: &'a mut AssetHierarchy, folders: &[String]) -> &'a mut AssetHierarchy {
+    folders.iter().fold(hierarchy, |level, folder| level.sub_hierarchies.entry(folder.clone()).or_default())
+}
+
+/// Identifies an asset file as a variant of another asset.
+struct AssetVariant {
+    /// The locale or quality tier of the variant.
+    key: VariantKey,
+    /// The path of the asset file of which this is a variant, relative to the root. This file need not exist.
+    file: String
+}
+
+/// Distinguishes a variant from the other variants of the same asset.
+enum VariantKey {
+    /// The variant is localized for the given locale.
+    Locale(String),
+    /// The variant is stored at the given quality tier.
+    Quality(Quality)
+}
+
+impl VariantKey {
+    /// Gets the name which follows the name of the asset in the variant's file name.
+    fn name(&self) -> &str {
+        match self {
+            Self::Locale(x) => x,
+            Self::Quality(x) => x.name()
+        }
+    }
+}
+
+/// A variant of an asset which has been written, but not yet grouped with the asset.
+struct PendingVariant {
+    /// The key and asset file of the variant.
+    variant: AssetVariant,
+    /// The path of the asset of which this is a variant, beginning with the root asset folder.
+    name: String,
+    /// The names of the folders between the root and the file.
+    hierarchy: Vec<String>,
+    /// The encoded variant.
+    asset: EncodedAsset
+}
+
+/// Records every variant in the manifest entry of the asset of which it is a variant. Where that asset's
+/// file was not encoded, a variant takes its place: the one whose locale comes first in `locales`, or
+/// otherwise the one with the highest quality. Quality variants are moved into the entry of the asset
+/// as tiers, so they are removed from the manifest and from `asset_files`.
+fn resolve_variants(manifest: &mut WassetManifest, hierarchy: &mut AssetHierarchy, asset_files: &mut BTreeMap<String, WassetId>, locales: &[String], variants: Vec<PendingVariant>) -> Result<(), WassetError> {
+    let mut groups = BTreeMap::<String, Vec<PendingVariant>>::new();
+    for variant in variants {
+        groups.entry(variant.variant.file.clone()).or_default().push(variant);
+    }
+
+    for (file, mut variants) in groups {
+        let id = match asset_files.get(&file) {
+            Some(id) => *id,
+            None => {
+                variants.sort_by_key(|x| match &x.variant.key {
+                    VariantKey::Locale(locale) => (0, locales.iter().position(|x| x == locale).unwrap_or_default()),
+                    VariantKey::Quality(quality) => (1, Quality::ALL.len() - *quality as usize)
+                });
+                let default = &variants[0];
+                manifest_entry(manifest, default.asset.id)?.name = Some(default.name.clone());
+                asset_level(hierarchy, &default.hierarchy).assets.push(default.asset.clone());
+                default.asset.id
+            }
+        };
+
+        for variant in variants.into_iter().filter(|x| x.asset.id != id || matches!(x.variant.key, VariantKey::Locale(_))) {
+            match variant.variant.key {
+                VariantKey::Locale(locale) => {
+                    manifest_entry(manifest, id)?.locales.insert(locale, variant.asset.id);
+                },
+                VariantKey::Quality(quality) => {
+                    let tier = manifest_entry(manifest, variant.asset.id)?.to_tier();
+                    manifest.assets.remove(&variant.asset.id);
+                    asset_files.retain(|_, x| *x != variant.asset.id);
+                    manifest_entry(manifest, id)?.qualities.insert(quality, tier);
                 }
             }
         }
@@ -131,7 +1048,179 @@ fn load_assets_in_folder<A: AssetEncoder>(base: &Path, folder: &Path, operation:
     Ok(())
 }
 
+/// Gets the manifest entry of an asset which has been written, failing if it is missing.
+fn manifest_entry(manifest: &mut WassetManifest, id: WassetId) -> Result<&mut WassetManifestEntry, WassetError> {
+    manifest.assets.get_mut(&id).ok_or_else(|| WassetError::from_serialize(format!("Asset {id:?} was missing from the manifest")))
+}
+
+/// The `Wasset.toml` key which sets the [`UnknownFilePolicy`] for a folder and its subfolders.
+const UNKNOWN_FILES_KEY: &str = "unknown_files";
+
+/// Dependencies declared by name in a `Wasset.toml` file.
+struct NamedDependencies {
+    /// The asset which declared the dependencies.
+    id: WassetId,
+    /// The name of the folder in which the dependencies were declared.
+    folder: String,
+    /// The names of the dependencies.
+    names: Vec<String>
+}
+
+/// Resolves dependencies that were declared by name into asset IDs, adding them to the manifest.
+fn resolve_named_dependencies(manifest: &mut WassetManifest, root_name: &str, named_dependencies: Vec<NamedDependencies>) -> Result<(), WassetError> {
+    let ids_by_name = manifest.assets.iter()
+        .filter_map(|(id, entry)| entry.name.clone().map(|name| (name, *id)))
+        .collect::<FxHashMap<_, _>>();
+
+    for dependencies in named_dependencies {
+        let entry = manifest_entry(manifest, dependencies.id)?;
+        for name in &dependencies.names {
+            let full_name = match name.strip_prefix('/') {
+                Some(x) => format!("{root_name}/{x}"),
+                None => format!("{}/{name}", dependencies.folder)
+            };
+
+            let id = *ids_by_name.get(&full_name)
+                .ok_or_else(|| WassetError::from_serialize(format!("Dependency {name} of asset {} does not exist", entry.name.as_deref().unwrap_or_default())))?;
+            if !entry.dependencies.contains(&id) {
+                entry.dependencies.push(id);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Serializes and compresses an asset, appending it to the data section and recording it in the manifest.
+/// Returns the new manifest entry, so that the caller may add further information. The time spent
+/// serializing and compressing is recorded in `timings`.
+pub(crate) fn write_asset<'a>(data: &mut Vec<u8>, payloads: &mut WrittenPayloads, manifest: &'a mut WassetManifest, id: WassetId, asset: &impl Serialize, options: &WassetEncodeOptions, timings: &mut EncodeTimings) -> Result<&'a mut WassetManifestEntry, WassetError> {
+    SerializedAsset::new(asset, options, timings)?.append(data, payloads, manifest, id, options)
+}
+
+/// The payloads which have been written to a data section, keyed by the hash of the serialized
+/// asset and the way in which it was stored. Assets whose stored bytes would be identical share
+/// the range of the first such payload, rather than being written again.
+pub(crate) type WrittenPayloads = FxHashMap<(WassetHash, WassetCodec, WassetCompression, bool), Range<u32>>;
+
+/// An asset that has been serialized and compressed, but not yet written to a data section.
+struct SerializedAsset {
+    /// The bytes to store in the data section.
+    stored: Vec<u8>,
+    /// The hash of the serialized asset.
+    hash: WassetHash,
+    /// The format in which the asset was serialized.
+    codec: WassetCodec,
+    /// The compression scheme applied to the serialized asset.
+    compression: WassetCompression,
+    /// Whether the compressed asset was encrypted.
+    encrypted: bool,
+    /// The size of the serialized asset before compression.
+    uncompressed_size: u32
+}
+
+impl SerializedAsset {
+    /// Serializes, compresses, and optionally encrypts an asset, recording the time spent in `timings`.
+    fn new(asset: &impl Serialize, options: &WassetEncodeOptions, timings: &mut EncodeTimings) -> Result<Self, WassetError> {
+        let serialize_start = Instant::now();
+        let serialized = options.codec.serialize(asset)?;
+        let hash = WassetHash::from_bytes(*blake3::hash(&serialized).as_bytes());
+        let uncompressed_size = serialized.len() as u32;
+        timings.serialize = serialize_start.elapsed();
+
+        let compress_start = Instant::now();
+        let (compression, stored) = options.compression.compress(serialized)?;
+
+        #[cfg(feature = "encryption")]
+        let (stored, encrypted) = match &options.encryption_key {
+            Some(key) => (key.encrypt(&stored)?, true),
+            None => (stored, false)
+        };
+        #[cfg(not(feature = "encryption"))]
+        let encrypted = false;
+        timings.compress = compress_start.elapsed();
+
+        Ok(Self { stored, hash, codec: options.codec, compression, encrypted, uncompressed_size })
+    }
+
+    /// Appends the asset to the data section and records it in the manifest, returning the new
+    /// manifest entry. If an identical payload was already written, the entry refers to it instead.
+    fn append<'a>(self, data: &mut Vec<u8>, payloads: &mut WrittenPayloads, manifest: &'a mut WassetManifest, id: WassetId, options: &WassetEncodeOptions) -> Result<&'a mut WassetManifestEntry, WassetError> {
+        let Self { stored, hash, codec, compression, encrypted, uncompressed_size } = self;
+        if !options.alignment.is_power_of_two() || options.alignment > MAX_PAYLOAD_ALIGNMENT {
+            return Err(WassetError::from_serialize(format!("Asset alignment {} is not a power of two up to {MAX_PAYLOAD_ALIGNMENT}", options.alignment)));
+        }
+
+        let Range { start, end } = payloads.entry((hash, codec, compression, encrypted)).or_insert_with(|| {
+            if compression.is_none() && !encrypted {
+                let alignment = options.alignment as usize;
+                let header_len = if codec.is_message_pack() { byte_array_header_len(&stored) } else { 0 };
+                let offset = data.len() + header_len;
+                data.resize(data.len() + (alignment - offset % alignment) % alignment, 0);
+            }
+
+            let start = data.len() as u32;
+            data.extend_from_slice(&stored);
+
+            #[cfg(feature = "tracing")]
+            tracing::trace!(?id, bytes = stored.len(), ?compression, "wrote asset");
+
+            start..data.len() as u32
+        }).clone();
+
+        let entry = manifest.assets.entry(id).insert_entry(WassetManifestEntry { start, end, hash: Some(hash), compression, encrypted, codec, uncompressed_size: Some(uncompressed_size), name: None, dependencies: Vec::new(), version: None, kind: None, preview: None, metadata: BTreeMap::new(), tags: Vec::new(), locales: BTreeMap::new(), qualities: BTreeMap::new(), external: false });
+        Ok(entry.into_mut())
+    }
+}
+
+/// Gets the length of the MessagePack header which precedes the contents of a serialized
+/// byte array, or zero if the data does not hold a byte array.
+fn byte_array_header_len(serialized: &[u8]) -> usize {
+    match serialized.first() {
+        Some(0xc4) => 2,
+        Some(0xc5) => 3,
+        Some(0xc6) => 5,
+        _ => 0
+    }
+}
+
+/// Gets the path of a file relative to the root asset folder, using `/` as a separator.
+fn root_relative_name(root: &Path, path: &Path) -> String {
+    path.strip_prefix(root).unwrap_or(path).components().map(|x| x.as_os_str().to_string_lossy()).collect::<Vec<_>>().join("/")
+}
+
 /// Gets the name at the end of the file path as a string.
-fn name_for_path(path: &Path) -> Result<Cow<str>, WassetError> {
+fn name_for_path(path: &Path) -> Result<Cow<'_, str>, WassetError> {
     Ok(path.file_name().ok_or_else(|| WassetError::from_serialize("Failed to get file system name"))?.to_string_lossy())
+}
+
+#[cfg(all(test, feature = "testing"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn length_constant_collision_is_detected() {
+        let identifier = |x: &str| x.to_uppercase();
+        let colliding = testing::encode_text_files([("name.txt", "a"), ("name_len.txt", "b")], &WassetEncodeOptions::default()).unwrap();
+        assert!(colliding.encoded_assets["assets"].check_length_constants(&identifier).is_err());
+
+        let nested = testing::encode_text_files([("name.txt", "a"), ("sub/name_len.txt", "b")], &WassetEncodeOptions::default()).unwrap();
+        assert!(nested.encoded_assets["assets"].check_length_constants(&identifier).is_ok());
+    }
+
+    #[test]
+    fn locale_variants_are_recorded() {
+        let options = WassetEncodeOptions { locales: vec![String::from("de")], ..Default::default() };
+        let assets = testing::encode_text_files([("greeting.txt", "hello"), ("greeting.de.txt", "hallo"), ("farewell.de.txt", "tschüss")], &options).unwrap();
+        let manifest = WassetManifest::from_bytes(&assets.manifest).unwrap();
+        let id_of = |name| manifest.iter().find(|(_, entry)| entry.name() == Some(name)).map(|(id, _)| id).unwrap();
+
+        let greeting = id_of("assets/greeting");
+        assert_eq!(manifest.localized_id(greeting, "de"), Some(id_of("assets/greeting.de")));
+        assert_eq!(manifest.localized_id(greeting, "fr"), Some(greeting));
+
+        // Without a base file, the variant itself takes the name of the asset.
+        let farewell = id_of("assets/farewell");
+        assert_eq!(manifest.localized_id(farewell, "de"), Some(farewell));
+    }
 }
\ No newline at end of file
@@ -0,0 +1,202 @@
+use crate::*;
+use wasm_encoder::*;
+
+/// Builds a minimal valid WASM module which contains nothing but asset custom sections.
+/// This allows for distributing pure content packs through the same channels as plugins,
+/// and reading them with the usual [`WassetParser`].
+///
+/// ```no_run
+/// # use wasset::*;
+/// # use std::path::Path;
+/// # struct MyEncoder;
+/// # impl AssetEncoder for MyEncoder {
+/// #     type Target = Vec<u8>;
+/// #     fn encode(_: &EncodeContext, data: Vec<u8>) -> Result<Option<Vec<u8>>, WassetError> {
+/// #         Ok(Some(data))
+/// #     }
+/// # }
+/// let assets = encode_asset_folder::<MyEncoder>(Path::new("assets"))?;
+/// let module = WassetModuleBuilder::new().add_assets(assets).build();
+/// std::fs::write("content.wasm", module)?;
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+#[derive(Clone, Debug)]
+pub struct WassetModuleBuilder {
+    /// The sets of assets to include, each of which is stored in its own pair of sections.
+    assets: Vec<EncodedAssets>,
+    /// The prefix used to name the asset sections.
+    section_prefix: String
+}
+
+impl WassetModuleBuilder {
+    /// Creates a new builder for a module without any assets.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a set of encoded assets to the module.
+    pub fn add_assets(&mut self, assets: EncodedAssets) -> &mut Self {
+        self.assets.push(assets);
+        self
+    }
+
+    /// Sets the prefix used to name the asset sections. This must match the
+    /// [`WassetParseOptions::section_prefix`] of any parser that reads the module.
+    pub fn section_prefix(&mut self, prefix: impl Into<String>) -> &mut Self {
+        self.section_prefix = prefix.into();
+        self
+    }
+
+    /// Produces the WASM bytecode for the module.
+    pub fn build(&self) -> Vec<u8> {
+        let mut module = Module::new().finish();
+        for assets in &self.assets {
+            append_asset_sections(&mut module, assets, &self.section_prefix);
+        }
+        module
+    }
+
+    /// Produces a standalone asset pack which holds the assets without any WASM wrapper.
+    /// Packs suit bundles of content which have no code of their own, and are read with
+    /// the usual [`WassetParser`].
+    ///
+    /// A pack begins with [`WASSET_PACK_MAGIC`] and the little-endian [`WASSET_PACK_VERSION`]. Each
+    /// section follows as the little-endian length of its name, the name, and the little-endian
+    /// length of its contents, followed by the contents, which begin at a multiple of [`MAX_PAYLOAD_ALIGNMENT`].
+    /// The sections are named and laid out exactly as the custom sections of a module.
+    pub fn build_pack(&self) -> Vec<u8> {
+        let mut pack = new_pack();
+        for assets in &self.assets {
+            for (name, data) in assets.to_custom_sections_with_prefix(Uuid::new_v4(), &self.section_prefix) {
+                append_pack_section(&mut pack, &name, &data);
+            }
+        }
+        pack
+    }
+}
+
+impl Default for WassetModuleBuilder {
+    fn default() -> Self {
+        Self {
+            assets: Vec::new(),
+            section_prefix: DEFAULT_SECTION_PREFIX.to_string()
+        }
+    }
+}
+
+/// Embeds encoded assets into an already-compiled WASM module, using the default section prefix.
+/// This allows for processing assets separately from compilation, without the `include_assets`
+/// macro. The assets are appended to the module in a new set of custom sections, so any assets
+/// which the module already contains remain readable alongside them.
+///
+/// ```no_run
+/// # use wasset::*;
+/// # use std::path::Path;
+/// # struct MyEncoder;
+/// # impl AssetEncoder for MyEncoder {
+/// #     type Target = Vec<u8>;
+/// #     fn encode(_: &EncodeContext, data: Vec<u8>) -> Result<Option<Vec<u8>>, WassetError> {
+/// #         Ok(Some(data))
+/// #     }
+/// # }
+/// let assets = encode_asset_folder::<MyEncoder>(Path::new("assets"))?;
+/// let module = std::fs::read("target/wasm32-unknown-unknown/release/plugin.wasm")?;
+/// std::fs::write("plugin.wasm", inject_assets(&module, &assets))?;
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+pub fn inject_assets(module: &[u8], assets: &EncodedAssets) -> Vec<u8> {
+    inject_assets_with_prefix(module, assets, DEFAULT_SECTION_PREFIX)
+}
+
+/// Embeds encoded assets into an already-compiled WASM module, naming the new sections
+/// with the given prefix. See [`inject_assets`] for details.
+pub fn inject_assets_with_prefix(module: &[u8], assets: &EncodedAssets, prefix: &str) -> Vec<u8> {
+    let mut output = module.to_vec();
+    append_asset_sections(&mut output, assets, prefix);
+    output
+}
+
+impl EncodedAssets {
+    /// Gets the names and contents of the custom sections which hold these assets, using the
+    /// default section prefix. The `section_id` distinguishes this set of sections from any others
+    /// in the same module, and should be unique. The data section is listed before the manifest.
+    pub fn to_custom_sections(&self, section_id: Uuid) -> Vec<(String, Vec<u8>)> {
+        self.to_custom_sections_with_prefix(section_id, DEFAULT_SECTION_PREFIX)
+    }
+
+    /// Gets the names and contents of the custom sections which hold these assets,
+    /// naming them with the given prefix. Each set of sections lists its index, previews,
+    /// and signature, if any, after its manifest. The sections of any partitions follow those of the other
+    /// assets, and are given new random IDs.
+    pub fn to_custom_sections_with_prefix(&self, section_id: Uuid, prefix: &str) -> Vec<(String, Vec<u8>)> {
+        let mut sections = Vec::new();
+        for (set_id, set) in std::iter::once(section_id).chain(std::iter::repeat_with(Uuid::new_v4)).zip(self.section_sets()) {
+            sections.push((format!("{prefix}{DATA_SECTION_SUFFIX}{set_id}"), set.data.to_vec()));
+            sections.push((format!("{prefix}{MANIFEST_SECTION_SUFFIX}{set_id}"), set.manifest.to_vec()));
+
+            if !set.index.is_empty() {
+                sections.push((format!("{prefix}{INDEX_SECTION_SUFFIX}{set_id}"), set.index.to_vec()));
+            }
+
+            if !set.preview.is_empty() {
+                sections.push((format!("{prefix}{PREVIEW_SECTION_SUFFIX}{set_id}"), set.preview.to_vec()));
+            }
+
+            if !set.signature.is_empty() {
+                sections.push((format!("{prefix}{SIGNATURE_SECTION_SUFFIX}{set_id}"), set.signature.to_vec()));
+            }
+        }
+
+        sections
+    }
+
+    /// Serializes these assets into a standalone asset pack without any WASM wrapper, using
+    /// the default section prefix. See [`WassetModuleBuilder::build_pack`] for details.
+    pub fn to_pack(&self) -> Vec<u8> {
+        let mut pack = new_pack();
+        for (name, data) in self.to_custom_sections(Uuid::new_v4()) {
+            append_pack_section(&mut pack, &name, &data);
+        }
+        pack
+    }
+
+    /// Adds the custom sections which hold these assets to a module that is being built
+    /// with `wasm_encoder`, using the default section prefix.
+    pub fn append_to_module(&self, module: &mut Module, section_id: Uuid) {
+        self.append_to_module_with_prefix(module, section_id, DEFAULT_SECTION_PREFIX);
+    }
+
+    /// Adds the custom sections which hold these assets to a module that is being built
+    /// with `wasm_encoder`, naming them with the given prefix.
+    pub fn append_to_module_with_prefix(&self, module: &mut Module, section_id: Uuid, prefix: &str) {
+        for (name, data) in self.to_custom_sections_with_prefix(section_id, prefix) {
+            let section = CustomSection {
+                name: name.into(),
+                data: data.into()
+            };
+
+            if let Some(padding) = alignment_padding(module.as_slice().len(), &section) {
+                module.section(&padding);
+            }
+
+            module.section(&section);
+        }
+    }
+}
+
+/// Appends custom sections holding the encoded assets to the end of a module,
+/// naming them with the given prefix.
+pub(crate) fn append_asset_sections(module: &mut Vec<u8>, assets: &EncodedAssets, prefix: &str) {
+    for (name, data) in assets.to_custom_sections_with_prefix(Uuid::new_v4(), prefix) {
+        let section = CustomSection {
+            name: name.into(),
+            data: data.into()
+        };
+
+        if let Some(padding) = alignment_padding(module.len(), &section) {
+            padding.append_to(module);
+        }
+
+        section.append_to(module);
+    }
+}
@@ -0,0 +1,158 @@
+use crate::*;
+use std::collections::*;
+
+/// Holds the assets from one top-level subfolder, which are stored in their own pair of
+/// custom sections when encoding with [`WassetEncodeOptions::split_subfolders`].
+#[derive(Clone, Debug, Default)]
+pub struct EncodedPartition {
+    /// The name of the subfolder from which the assets were encoded.
+    pub name: String,
+    /// The data that should be written to the custom section for holding the assets.
+    pub data: Vec<u8>,
+    /// The serialized manifest describing the assets.
+    pub manifest: Vec<u8>,
+    /// The data that should be written to the custom section for holding asset previews.
+    pub preview: Vec<u8>,
+    /// The serialized [`WassetIndex`] over the manifest, or an empty buffer if
+    /// the assets were encoded with [`ManifestLayout::Map`].
+    pub index: Vec<u8>,
+    /// The signature over the other sections, or an empty buffer if the assets are not signed.
+    pub signature: Vec<u8>
+}
+
+impl EncodedAssets {
+    /// Serializes a manifest and its data as by [`Self::split`], signing the result
+    /// if the options provide a signing key.
+    pub(crate) fn from_parts(data: Vec<u8>, preview: Vec<u8>, manifest: WassetManifest, encoded_assets: FxHashMap<String, AssetHierarchy>, options: &WassetEncodeOptions) -> Result<Self, WassetError> {
+        #[allow(unused_mut)]
+        let mut result = Self::split(data, preview, manifest, encoded_assets, options)?;
+
+        #[cfg(feature = "signing")]
+        if let Some(key) = &options.signing_key {
+            result.sign(key);
+        }
+
+        Ok(result)
+    }
+
+    /// Serializes a manifest and its data, moving the assets of each top-level
+    /// subfolder into their own partition if the options request it.
+    fn split(data: Vec<u8>, preview: Vec<u8>, mut manifest: WassetManifest, encoded_assets: FxHashMap<String, AssetHierarchy>, options: &WassetEncodeOptions) -> Result<Self, WassetError> {
+        manifest.schema = options.schema.clone();
+        if !options.split_subfolders {
+            return Ok(Self {
+                data,
+                encoded_assets,
+                manifest: manifest.to_bytes(options.manifest_format)?,
+                preview,
+                index: options.manifest_layout.build_index(&manifest),
+                partitions: Vec::new(),
+                signature: Vec::new(),
+                unaccepted_files: Vec::new()
+            });
+        }
+
+        let mut groups = BTreeMap::<Option<String>, Vec<_>>::new();
+        for (id, entry) in manifest.assets {
+            groups.entry(entry.name.as_deref().and_then(partition_of)).or_default().push((id, entry));
+        }
+
+        let mut root = EncodedPartition::default();
+        let mut partitions = Vec::new();
+        for (name, entries) in groups {
+            let (partition_data, partition_preview, mut partition_manifest) = copy_entries(&data, &preview, entries, options.alignment)?;
+            partition_manifest.partition = name.clone();
+            partition_manifest.schema = manifest.schema.clone();
+
+            let partition = EncodedPartition {
+                name: name.clone().unwrap_or_default(),
+                data: partition_data,
+                manifest: partition_manifest.to_bytes(options.manifest_format)?,
+                preview: partition_preview,
+                index: options.manifest_layout.build_index(&partition_manifest),
+                signature: Vec::new()
+            };
+
+            match name {
+                Some(_) => partitions.push(partition),
+                None => root = partition
+            }
+        }
+
+        if root.manifest.is_empty() {
+            let empty = WassetManifest { schema: manifest.schema, ..Default::default() };
+            root.manifest = empty.to_bytes(options.manifest_format)?;
+            root.index = options.manifest_layout.build_index(&empty);
+        }
+
+        Ok(Self {
+            data: root.data,
+            encoded_assets,
+            manifest: root.manifest,
+            preview: root.preview,
+            index: root.index,
+            partitions,
+            signature: Vec::new(),
+            unaccepted_files: Vec::new()
+        })
+    }
+
+    /// Gets the data, manifest, index, preview, and signature contents of every set of sections
+    /// that holds these assets, starting with the assets outside of any partition.
+    pub(crate) fn section_sets(&self) -> impl '_ + Iterator<Item = EncodedSectionSet<'_>> {
+        std::iter::once(EncodedSectionSet { data: &self.data, manifest: &self.manifest, index: &self.index, preview: &self.preview, signature: &self.signature })
+            .chain(self.partitions.iter().map(|x| EncodedSectionSet { data: &x.data, manifest: &x.manifest, index: &x.index, preview: &x.preview, signature: &x.signature }))
+    }
+}
+
+/// Refers to the contents of one set of asset sections.
+#[derive(Copy, Clone, Debug)]
+pub(crate) struct EncodedSectionSet<'a> {
+    /// The contents of the data section.
+    pub data: &'a [u8],
+    /// The contents of the manifest section.
+    pub manifest: &'a [u8],
+    /// The contents of the index section, which is omitted if empty.
+    pub index: &'a [u8],
+    /// The contents of the preview section, which is omitted if empty.
+    pub preview: &'a [u8],
+    /// The contents of the signature section, which is omitted if empty.
+    pub signature: &'a [u8]
+}
+
+/// Gets the name of the top-level subfolder which holds the asset with the given
+/// root-relative name, or `None` if the asset lies directly within the root folder.
+fn partition_of(name: &str) -> Option<String> {
+    let mut components = name.split('/').skip(1);
+    let folder = components.next()?;
+    components.next().map(|_| folder.to_string())
+}
+
+/// Copies the stored bytes and previews of the given entries into new buffers, keeping the
+/// position of each payload modulo `alignment` so that aligned payloads remain aligned. Entries
+/// which share a range continue to share it.
+fn copy_entries(data: &[u8], preview: &[u8], mut entries: Vec<(WassetId, WassetManifestEntry)>, alignment: u32) -> Result<(Vec<u8>, Vec<u8>, WassetManifest), WassetError> {
+    entries.sort_by_key(|(_, entry)| entry.start);
+
+    let mut output = Vec::new();
+    let mut previews = Vec::new();
+    let mut manifest = WassetManifest::default();
+    let mut moved = FxHashMap::default();
+    for (id, mut entry) in entries {
+        entry.copy_preview(preview, &mut previews);
+        entry.relocate(|range| {
+            let bytes = data.get(range.start as usize..range.end as usize)
+                .ok_or_else(|| WassetError::from_serialize(format!("Asset {id:?} lies outside of its data section")))?;
+            Ok(*moved.entry(range.clone()).or_insert_with(|| {
+                output.resize(output.len() + payload_padding(output.len(), range.start, alignment), 0);
+                let start = output.len() as u32;
+                output.extend_from_slice(bytes);
+                start
+            }))
+        })?;
+
+        manifest.assets.insert(id, entry);
+    }
+
+    Ok((output, previews, manifest))
+}
@@ -5,17 +5,291 @@ use crate::*;
 use litrs::*;
 use proc_macro::*;
 use quote::quote;
+use std::collections::*;
 use std::fs::*;
 use std::path::*;
 
 /// Provides a macro implementation which accepts a directory path and outputs
 /// code which embeds all assets in the directory. This should be called with a concrete
 /// asset type from a user-defined macro.
+///
+/// Several directory paths may be given, in which case their assets are stored in a single set
+/// of sections. Each directory produces a module named after it, and directories with the same
+/// name are merged according to [`WassetEncodeOptions::conflict_strategy`], as by [`encode_asset_folders`].
+///
+/// The paths may be followed by named arguments which control the generated items:
+/// - `module = name` names the generated module, rather than naming it after the folder.
+///   The assets of every directory are merged into this module.
+/// - `vis = pub(crate)` sets the visibility of the generated module, which is `pub` by default.
+/// - `case = screaming | snake | preserve` sets the casing of the asset constants, which are
+///   `SCREAMING_CASE` by default. Non-screaming constants do not trigger naming lints.
+/// - `include = [...]` and `exclude = [...]` add glob patterns to [`WassetEncodeOptions::filter`],
+///   selecting which files are encoded.
+/// - `typed = true` generates a wrapper type around [`WassetId`] for each asset kind reported by
+///   [`AssetEncoder::kind`], such as `ImageId` for `image` assets, in the top-level module. The
+///   constants of assets with a kind then have the wrapper type, so that an image cannot be passed
+///   where a sound is expected. Each wrapper converts into a [`WassetId`] with `From`.
+/// - `schema = "..."` sets [`WassetEncodeOptions::schema`], the fingerprint of the asset schema
+///   which hosts may require when parsing the module.
+///
+/// ```text
+/// include_assets!("assets", module = my_assets, vis = pub(crate), case = snake);
+/// include_assets!("assets", exclude = ["*.psd", ".DS_Store", "*_source/"]);
+/// include_assets!("assets", typed = true, schema = "my_schema@3");
+/// include_assets!("assets", "shared_assets", "target/generated", module = assets);
+/// ```
 pub fn include_assets<A: AssetEncoder>(x: TokenStream, wasset_id_path: &proc_macro2::TokenStream) -> TokenStream {
-    let input = x.into_iter().map(Into::into).collect::<Vec<TokenTree>>();
-    assert!(input.len() == 1, "Wrong number of arguments.");
-    let x = StringLit::try_from(&input[0])
-        .expect("Could not parse argument as path string.")
+    include_assets_with_options::<A>(x, wasset_id_path, &WassetEncodeOptions::default())
+}
+
+/// Provides a macro implementation which accepts a directory path and outputs
+/// code which embeds all assets in the directory, using the provided encoding options.
+/// This should be called with a concrete asset type from a user-defined macro.
+pub fn include_assets_with_options<A: AssetEncoder>(x: TokenStream, wasset_id_path: &proc_macro2::TokenStream, options: &WassetEncodeOptions) -> TokenStream {
+    let args = match MacroArguments::parse(x) {
+        Ok(x) => x,
+        Err(error) => return error
+    };
+
+    let mut options = options.clone();
+    options.filter.include.extend(args.filter.include.iter().cloned());
+    options.filter.exclude.extend(args.filter.exclude.iter().cloned());
+    if args.schema.is_some() {
+        options.schema.clone_from(&args.schema);
+    }
+
+    encode_macro_folders::<A>(&args, &options)
+        .and_then(|assets| write_assets(&assets, &args, wasset_id_path, &options.section_prefix))
+        .unwrap_or_else(|e| compile_error(format!("Failed to encode assets: {e}"), args.span))
+}
+
+/// Encodes the assets of every directory passed to a macro. Directories are merged
+/// into the hierarchy named by the `module` argument, or otherwise by their folder names.
+fn encode_macro_folders<A: AssetEncoder>(args: &MacroArguments, options: &WassetEncodeOptions) -> Result<EncodedAssets, WassetError> {
+    if let ([path], None) = (args.paths.as_slice(), &args.module) {
+        return encode_asset_folder_with_options::<A>(path, options);
+    }
+
+    let mut names = Vec::with_capacity(args.paths.len());
+    for path in &args.paths {
+        names.push(match &args.module {
+            Some(module) => module.to_string(),
+            None => path.file_name().ok_or_else(|| WassetError::from_serialize("Folder must have name.").in_file(path))?.to_string_lossy().into_owned()
+        });
+    }
+
+    let roots = names.iter().map(String::as_str).zip(args.paths.iter().map(PathBuf::as_path)).collect::<Vec<_>>();
+    encode_asset_folders::<A>(&roots, options)
+}
+
+/// Provides a macro implementation which accepts the path of a compiled WASM module, and outputs
+/// the same asset ID constants as the `include_assets` invocations which embedded assets into that
+/// module, without embedding any asset data. This allows one crate to refer to the assets of
+/// another, such as a shared content plugin, which the host loads separately. The module must
+/// be rebuilt before the referencing crate whenever its assets change, since their IDs change too.
+/// The same named arguments are accepted as by [`include_assets`].
+///
+/// ```text
+/// #[proc_macro]
+/// pub fn reference_assets(path: TokenStream) -> TokenStream {
+///     wasset::reference_assets(path, &quote::quote! { ::wasset::WassetId })
+/// }
+///
+/// // In the plugin:
+/// reference_assets!("../shared/target/wasm32-unknown-unknown/release/shared.wasm");
+/// ```
+#[cfg(feature = "parse")]
+pub fn reference_assets(x: TokenStream, wasset_id_path: &proc_macro2::TokenStream) -> TokenStream {
+    reference_assets_with_options(x, wasset_id_path, &WassetParseOptions::default())
+}
+
+/// Provides a macro implementation which accepts the path of a compiled WASM module, and outputs
+/// the asset ID constants for the assets within it, reading them with the provided options.
+/// See [`reference_assets`] for details.
+#[cfg(feature = "parse")]
+pub fn reference_assets_with_options(x: TokenStream, wasset_id_path: &proc_macro2::TokenStream, options: &WassetParseOptions) -> TokenStream {
+    let args = match MacroArguments::parse(x) {
+        Ok(x) => x,
+        Err(error) => return error
+    };
+
+    let [path] = args.paths.as_slice() else {
+        return compile_error("Expected a single module path", args.span);
+    };
+
+    let module = match read(path) {
+        Ok(x) => x,
+        Err(e) => return compile_error(format!("Failed to read {}: {e}", path.display()), args.span)
+    };
+
+    WassetParser::<()>::parse_with_options(&module, options.clone())
+        .and_then(|parser| tokens_for_roots(&AssetHierarchy::from_manifest(parser.manifest()), &args, wasset_id_path))
+        .map(TokenStream::from)
+        .unwrap_or_else(|e| compile_error(format!("Failed to read assets from {}: {e}", path.display()), args.span))
+}
+
+/// The arguments with which an asset macro was invoked.
+struct MacroArguments {
+    /// The path arguments, resolved to absolute paths.
+    paths: Vec<PathBuf>,
+    /// The span of the first path argument.
+    span: Span,
+    /// The name of the generated module, if it should not be named after the folder.
+    module: Option<proc_macro2::Ident>,
+    /// The visibility of the generated module.
+    vis: proc_macro2::TokenStream,
+    /// The casing of the generated asset constants.
+    case: ConstantCase,
+    /// The patterns which select the files to encode, in addition to those of the options.
+    filter: AssetFilter,
+    /// Whether a wrapper type is generated for each asset kind.
+    typed: bool,
+    /// The schema fingerprint to record, overriding that of the options.
+    schema: Option<String>
+}
+
+impl MacroArguments {
+    /// Parses one or more path literals, followed by optional comma-separated `key = value` arguments.
+    /// Returns a compile error if the arguments are invalid.
+    fn parse(x: TokenStream) -> Result<Self, TokenStream> {
+        let mut input = x.into_iter().peekable();
+        let (path, span) = path_argument(input.next())?;
+        let mut result = Self {
+            paths: vec![path],
+            span,
+            module: None,
+            vis: quote! { pub },
+            case: ConstantCase::Screaming,
+            filter: AssetFilter::default(),
+            typed: false,
+            schema: None
+        };
+
+        let mut named = false;
+        while let Some(separator) = input.next() {
+            if !matches!(&separator, TokenTree::Punct(x) if x.as_char() == ',') {
+                return Err(compile_error("Expected a comma between arguments", separator.span()));
+            }
+
+            let Some(key) = input.next() else {
+                break;
+            };
+
+            if matches!(key, TokenTree::Literal(_)) && !named {
+                result.paths.push(path_argument(Some(key))?.0);
+                continue;
+            }
+
+            named = true;
+            let TokenTree::Ident(key) = key else {
+                return Err(compile_error("Expected the name of an argument", key.span()));
+            };
+
+            match input.next() {
+                Some(TokenTree::Punct(x)) if x.as_char() == '=' => {},
+                other => return Err(compile_error(format!("Expected `=` after {key}"), other.map(|x| x.span()).unwrap_or(key.span())))
+            }
+
+            let mut value = Vec::new();
+            while let Some(token) = input.next_if(|x| !matches!(x, TokenTree::Punct(x) if x.as_char() == ',')) {
+                value.push(token);
+            }
+
+            match (key.to_string().as_str(), value.as_slice()) {
+                ("module", [TokenTree::Ident(name)]) => result.module = Some(proc_macro2::Ident::new(&name.to_string(), name.span().into())),
+                ("vis", _) => result.vis = proc_macro2::TokenStream::from(TokenStream::from_iter(value)),
+                ("case", [TokenTree::Ident(case)]) => result.case = ConstantCase::from_name(&case.to_string())
+                    .ok_or_else(|| compile_error(format!("Unknown case {case}; expected screaming, snake, or preserve"), case.span()))?,
+                ("include", [TokenTree::Group(group)]) if group.delimiter() == Delimiter::Bracket => result.filter.include.extend(string_list(group)?),
+                ("exclude", [TokenTree::Group(group)]) if group.delimiter() == Delimiter::Bracket => result.filter.exclude.extend(string_list(group)?),
+                ("typed", [TokenTree::Ident(x)]) if x.to_string() == "true" || x.to_string() == "false" => result.typed = x.to_string() == "true",
+                ("module" | "case", _) => return Err(compile_error(format!("Expected a single identifier for {key}"), key.span())),
+                ("include" | "exclude", _) => return Err(compile_error(format!("Expected an array of pattern strings for {key}"), key.span())),
+                ("schema", [x @ TokenTree::Literal(_)]) => result.schema = Some(StringLit::try_from(x)
+                    .map_err(|e| compile_error(format!("Expected a schema string: {e}"), x.span()))?
+                    .into_value()
+                    .into_owned()),
+                ("typed", _) => return Err(compile_error("Expected true or false for typed", key.span())),
+                ("schema", _) => return Err(compile_error("Expected a string literal for schema", key.span())),
+                _ => return Err(compile_error(format!("Unknown argument {key}; expected module, vis, case, include, exclude, typed, or schema"), key.span()))
+            }
+        }
+
+        Ok(result)
+    }
+}
+
+/// Determines the casing of generated asset constants.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum ConstantCase {
+    /// Names are converted to uppercase, such as `SOME_TEXT`.
+    Screaming,
+    /// Names are converted to lowercase, with underscores between words, such as `some_text`.
+    Snake,
+    /// Names are used exactly as they appear in the asset folder.
+    Preserve
+}
+
+impl ConstantCase {
+    /// Gets the casing with the given argument name, if any.
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "screaming" => Some(Self::Screaming),
+            "snake" => Some(Self::Snake),
+            "preserve" => Some(Self::Preserve),
+            _ => None
+        }
+    }
+
+    /// Applies this casing to the given name.
+    fn apply(&self, name: &str) -> String {
+        match self {
+            Self::Screaming => name.to_uppercase(),
+            Self::Snake => {
+                let mut result = String::with_capacity(name.len());
+                let mut previous = None::<char>;
+                for x in name.chars() {
+                    if x.is_uppercase() && previous.is_some_and(|p| p.is_lowercase() || p.is_numeric()) {
+                        result.push('_');
+                    }
+                    result.extend(x.to_lowercase());
+                    previous = Some(x);
+                }
+                result
+            },
+            Self::Preserve => name.to_string()
+        }
+    }
+}
+
+/// Creates tokens which fail compilation with the given message, pointing at `span`.
+fn compile_error(message: impl std::fmt::Display, span: Span) -> TokenStream {
+    let message = proc_macro2::Literal::string(&message.to_string());
+    let span = proc_macro2::Span::from(span);
+    quote::quote_spanned!(span => ::core::compile_error!(#message);).into()
+}
+
+/// Reads the comma-separated string literals within a bracketed group, such as `["*.psd", "*.tmp"]`.
+/// Returns a compile error if any element is not a string literal.
+fn string_list(group: &Group) -> Result<Vec<String>, TokenStream> {
+    group.stream().into_iter()
+        .filter(|x| !matches!(x, TokenTree::Punct(x) if x.as_char() == ','))
+        .map(|x| StringLit::try_from(&x)
+            .map(|x| x.into_value().into_owned())
+            .map_err(|e| compile_error(format!("Expected a pattern string: {e}"), x.span())))
+        .collect()
+}
+
+/// Reads the path literal with which a macro was invoked, resolving it to an absolute path.
+/// Returns the span of the literal alongside the path, or a compile error if the argument is invalid.
+fn path_argument(argument: Option<TokenTree>) -> Result<(PathBuf, Span), TokenStream> {
+    let Some(argument) = argument else {
+        return Err(compile_error("Expected a path string literal", Span::call_site()));
+    };
+
+    let span = argument.span();
+    let x = StringLit::try_from(&argument)
+        .map_err(|e| compile_error(format!("Could not parse argument as path string: {e}"), span))?
         .into_value();
 
     #[allow(unused)]
@@ -27,39 +301,227 @@ pub fn include_assets<A: AssetEncoder>(x: TokenStream, wasset_id_path: &proc_mac
         parent_dir_path = Some(path);
     }
 
-    let resolved_path = resolve_path(&x, parent_dir_path).expect("Could not resolve path.");
-    
+    let resolved_path = resolve_path(&x, parent_dir_path)
+        .map_err(|e| compile_error(format!("Could not resolve path {x}: {e}"), span))?;
+
     #[cfg(unstable)]
     tracked_path::path(resolved_path.display().to_string());
 
-    let assets = encode_asset_folder::<A>(&resolved_path).expect("Failed to encode assets");
-    write_assets(&assets, wasset_id_path)
+    Ok((resolved_path, span))
+}
+
+/// Writes the set of encoded assets as code, naming their sections with the given prefix.
+fn write_assets(assets: &EncodedAssets, args: &MacroArguments, wasset_id_path: &proc_macro2::TokenStream, prefix: &str) -> Result<TokenStream, WassetError> {
+    let mut data = proc_macro2::TokenStream::new();
+    for set in assets.section_sets() {
+        data.extend(tokens_for_sections(set, prefix));
+    }
+
+    data.extend(tokens_for_roots(&assets.encoded_assets, args, wasset_id_path)?);
+    Ok(data.into())
+}
+
+/// Gets tokens which encode the hierarchies of each root asset folder as modules, named
+/// and cased according to the macro arguments.
+fn tokens_for_roots(roots: &FxHashMap<String, AssetHierarchy>, args: &MacroArguments, wasset_id_path: &proc_macro2::TokenStream) -> Result<proc_macro2::TokenStream, WassetError> {
+    if args.module.is_some() && roots.len() > 1 {
+        return Err(WassetError::from_serialize("A module name may only be given for assets from a single folder"));
+    }
+
+    let vis = &args.vis;
+    let lints = (args.case != ConstantCase::Screaming).then(|| quote! { #[allow(non_upper_case_globals)] });
+
+    let mut data = proc_macro2::TokenStream::new();
+    for (name, hierarchy) in roots {
+        let module_name = match &args.module {
+            Some(x) => x.clone(),
+            None => ident(name)?
+        };
+
+        hierarchy.check_length_constants(&|x| args.case.apply(x))?;
+        let inner_module = tokens_for_module_contents(hierarchy, tokens_for_root(hierarchy, args, wasset_id_path)?, args, 0, wasset_id_path)?;
+        data.extend(quote! {
+            #lints
+            #vis mod #module_name {
+                #inner_module
+            }
+        });
+    }
+
+    Ok(data)
+}
+
+/// Gets the extra tokens for the top-level module of a hierarchy, which are an `ALL_ASSETS`
+/// slice of the IDs of every asset within it, a module of the assets of each kind, and the
+/// wrapper type of each kind if they were requested.
+fn tokens_for_root(hierarchy: &AssetHierarchy, args: &MacroArguments, wasset_id_path: &proc_macro2::TokenStream) -> Result<proc_macro2::TokenStream, WassetError> {
+    let ids = tokens_for_ids(hierarchy, wasset_id_path);
+    let mut data = quote! {
+        pub const ALL_ASSETS: &[#wasset_id_path] = &[#ids];
+    };
+
+    data.extend(tokens_for_kinds(hierarchy, wasset_id_path));
+    if args.typed {
+        data.extend(tokens_for_kind_types(hierarchy, wasset_id_path)?);
+    }
+
+    Ok(data)
+}
+
+/// Gets comma-separated expressions for the IDs of every asset within the hierarchy, sorted by ID.
+fn tokens_for_ids(hierarchy: &AssetHierarchy, wasset_id_path: &proc_macro2::TokenStream) -> proc_macro2::TokenStream {
+    let mut ids = hierarchy.all_assets().map(|x| x.id).collect::<Vec<_>>();
+    ids.sort();
+    let ids = ids.iter().map(|id| proc_macro2::Literal::byte_string(&id.as_bytes()[..]));
+    quote! { #(#wasset_id_path::from_bytes(* #ids)),* }
+}
+
+/// Gets tokens for a `kinds` module, which holds an array of the IDs of every asset
+/// of each kind within the hierarchy. Returns nothing if no asset has a kind.
+fn tokens_for_kinds(hierarchy: &AssetHierarchy, wasset_id_path: &proc_macro2::TokenStream) -> proc_macro2::TokenStream {
+    let kinds = asset_kinds(hierarchy);
+    if kinds.is_empty() {
+        return proc_macro2::TokenStream::new();
+    }
+
+    let constants = kinds.into_iter().map(|(kind, mut ids)| {
+        ids.sort();
+        let mut kind_name = kind.chars().map(|x| if x.is_ascii_alphanumeric() { x.to_ascii_uppercase() } else { '_' }).collect::<String>();
+        if !kind_name.starts_with(|x: char| x.is_ascii_alphabetic()) {
+            kind_name.insert(0, '_');
+        }
+
+        let kind_name = proc_macro2::Ident::new(&kind_name, proc_macro2::Span::call_site());
+        let len = proc_macro2::Literal::usize_unsuffixed(ids.len());
+        let ids = ids.iter().map(|id| proc_macro2::Literal::byte_string(&id.as_bytes()[..]));
+        quote! {
+            pub const #kind_name: [#wasset_id_path; #len] = [#(#wasset_id_path::from_bytes(* #ids)),*];
+        }
+    });
+
+    quote! {
+        pub mod kinds {
+            #(#constants)*
+        }
+    }
+}
+
+/// Gets tokens for a wrapper type around asset IDs for each asset kind within the hierarchy.
+/// Fails if the name of a kind cannot form a type name.
+fn tokens_for_kind_types(hierarchy: &AssetHierarchy, wasset_id_path: &proc_macro2::TokenStream) -> Result<proc_macro2::TokenStream, WassetError> {
+    let mut data = proc_macro2::TokenStream::new();
+    for kind in asset_kinds(hierarchy).into_keys() {
+        let type_name = ident(&kind_type_name(kind))?;
+        let doc = format!("The ID of an asset of kind `{kind}`.");
+        data.extend(quote! {
+            #[doc = #doc]
+            #[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+            #[repr(transparent)]
+            pub struct #type_name(pub #wasset_id_path);
+
+            impl ::core::convert::From<#type_name> for #wasset_id_path {
+                fn from(value: #type_name) -> Self {
+                    value.0
+                }
+            }
+        });
+    }
+
+    Ok(data)
+}
+
+/// Gets the IDs of every asset within the hierarchy which has a kind, grouped by kind.
+fn asset_kinds(hierarchy: &AssetHierarchy) -> BTreeMap<&str, Vec<WassetId>> {
+    let mut kinds = BTreeMap::<&str, Vec<WassetId>>::new();
+    let mut pending = vec![hierarchy];
+    while let Some(level) = pending.pop() {
+        pending.extend(level.sub_hierarchies.values());
+        for asset in &level.assets {
+            if let Some(kind) = &asset.kind {
+                kinds.entry(kind).or_default().push(asset.id);
+            }
+        }
+    }
+
+    kinds
+}
+
+/// Gets the name of the wrapper type for IDs of the given kind, such as `ImageId` for `image`.
+fn kind_type_name(kind: &str) -> String {
+    let mut result = kind.split(|x: char| !x.is_alphanumeric())
+        .flat_map(|word| {
+            let mut chars = word.chars();
+            chars.next().map(|x| x.to_ascii_uppercase()).into_iter().chain(chars)
+        })
+        .collect::<String>();
+
+    if result.starts_with(|x: char| x.is_numeric()) {
+        result.insert(0, '_');
+    }
+
+    result.push_str("Id");
+    result
 }
 
-/// Writes the set of encoded assets as code.
-fn write_assets(assets: &EncodedAssets, wasset_id_path: &proc_macro2::TokenStream) -> TokenStream {
+/// Gets tokens which place one set of asset sections into the module, naming them with the given prefix.
+fn tokens_for_sections(set: EncodedSectionSet, prefix: &str) -> proc_macro2::TokenStream {
+    let EncodedSectionSet { data: contents, manifest, index, preview, signature } = set;
     let id = Uuid::new_v4();
-    let manifest_name = proc_macro2::Literal::string(&format!("__wasset_manifest:{id}"));
-    let contents_name = proc_macro2::Literal::string(&format!("__wasset_data:{id}"));
+    let manifest_name = proc_macro2::Literal::string(&format!("{prefix}{MANIFEST_SECTION_SUFFIX}{id}"));
+    let contents_name = proc_macro2::Literal::string(&format!("{prefix}{DATA_SECTION_SUFFIX}{id}"));
 
-    let manifest_literal_len = proc_macro2::Literal::usize_unsuffixed(assets.manifest.len());
-    let manifest_literal = proc_macro2::Literal::byte_string(&assets.manifest);
-    let contents_literal_len = proc_macro2::Literal::usize_unsuffixed(assets.data.len());
-    let contents_literal = proc_macro2::Literal::byte_string(&assets.data);
+    let manifest_literal_len = proc_macro2::Literal::usize_unsuffixed(manifest.len());
+    let manifest_literal = proc_macro2::Literal::byte_string(manifest);
+    let contents_literal_len = proc_macro2::Literal::usize_unsuffixed(contents.len());
+    let contents_literal = proc_macro2::Literal::byte_string(contents);
 
     let mut data = quote! {
         const _: () = {
             #[link_section = #manifest_name]
             static ASSET_MANIFEST: [u8; #manifest_literal_len] = *#manifest_literal;
-    
+
             #[link_section = #contents_name]
             static ASSET_DATA: [u8; #contents_literal_len] = *#contents_literal;
         };
     };
 
-    data.extend(assets.encoded_assets.iter().map(|(name, hierarchy)| tokens_for_hierarchy(name, hierarchy, wasset_id_path)));
+    if !index.is_empty() {
+        let index_name = proc_macro2::Literal::string(&format!("{prefix}{INDEX_SECTION_SUFFIX}{id}"));
+        let index_literal_len = proc_macro2::Literal::usize_unsuffixed(index.len());
+        let index_literal = proc_macro2::Literal::byte_string(index);
+        data.extend(quote! {
+            const _: () = {
+                #[link_section = #index_name]
+                static ASSET_INDEX: [u8; #index_literal_len] = *#index_literal;
+            };
+        });
+    }
+
+    if !preview.is_empty() {
+        let preview_name = proc_macro2::Literal::string(&format!("{prefix}{PREVIEW_SECTION_SUFFIX}{id}"));
+        let preview_literal_len = proc_macro2::Literal::usize_unsuffixed(preview.len());
+        let preview_literal = proc_macro2::Literal::byte_string(preview);
+        data.extend(quote! {
+            const _: () = {
+                #[link_section = #preview_name]
+                static ASSET_PREVIEW: [u8; #preview_literal_len] = *#preview_literal;
+            };
+        });
+    }
 
-    data.into()
+    if !signature.is_empty() {
+        let signature_name = proc_macro2::Literal::string(&format!("{prefix}{SIGNATURE_SECTION_SUFFIX}{id}"));
+        let signature_literal_len = proc_macro2::Literal::usize_unsuffixed(signature.len());
+        let signature_literal = proc_macro2::Literal::byte_string(signature);
+        data.extend(quote! {
+            const _: () = {
+                #[link_section = #signature_name]
+                static ASSET_SIGNATURE: [u8; #signature_literal_len] = *#signature_literal;
+            };
+        });
+    }
+
+    data
 }
 
 /// Canonicalizes the path provided by the user.
@@ -73,24 +535,60 @@ fn resolve_path(path: &str, parent_dir_path: Option<PathBuf>) -> std::io::Result
     canonicalize(&path)
 }
 
-/// Gets tokens which encode the given asset hierarchy.
-fn tokens_for_hierarchy(name: &str, hierarchy: &AssetHierarchy, wasset_id_path: &proc_macro2::TokenStream) -> proc_macro2::TokenStream {
-    let mut inner_module = proc_macro2::TokenStream::new();
-    inner_module.extend(hierarchy.sub_hierarchies.iter().map(|(n, h)| tokens_for_hierarchy(n, h, wasset_id_path)));
-    inner_module.extend(hierarchy.assets.iter().map(|entry| {
-        let entry_name = proc_macro2::Ident::new(&entry.name.to_uppercase(), proc_macro2::Span::call_site());
+/// Gets tokens for the contents of the module of the given asset hierarchy, followed by the `extra`
+/// tokens. Each module holds an `ALL` slice of the IDs of every asset within it and its submodules.
+/// The module lies `depth` levels below the top-level module, which holds any kind wrapper types.
+/// Fails if the name of a folder or asset is not a valid identifier.
+fn tokens_for_module_contents(hierarchy: &AssetHierarchy, extra: proc_macro2::TokenStream, args: &MacroArguments, depth: usize, wasset_id_path: &proc_macro2::TokenStream) -> Result<proc_macro2::TokenStream, WassetError> {
+    let ids = tokens_for_ids(hierarchy, wasset_id_path);
+    let mut inner_module = quote! {
+        pub const ALL: &[#wasset_id_path] = &[#ids];
+    };
+
+    inner_module.extend(extra);
+    for (n, h) in &hierarchy.sub_hierarchies {
+        let module_name = ident(n)?;
+        let contents = tokens_for_module_contents(h, proc_macro2::TokenStream::new(), args, depth + 1, wasset_id_path)?;
+        inner_module.extend(quote! {
+            pub mod #module_name {
+                #contents
+            }
+        });
+    }
+
+    for entry in &hierarchy.assets {
+        let entry_name = ident(&args.case.apply(&entry.name))?;
+        let len_name = ident(&args.case.apply(&format!("{}_LEN", entry.name)))?;
         let byte_data = proc_macro2::Literal::byte_string(&entry.id.as_bytes()[..]);
+        let len = proc_macro2::Literal::usize_unsuffixed(entry.size);
+        let id = quote! { #wasset_id_path::from_bytes(* #byte_data) };
 
-        quote! {
-            pub const #entry_name: #wasset_id_path = #wasset_id_path::from_bytes(* #byte_data);
-        }
-    }));
+        let constant = match entry.kind.as_deref().filter(|_| args.typed) {
+            Some(kind) => {
+                let supers = (0..depth).map(|_| quote! { super:: });
+                let type_name = ident(&kind_type_name(kind))?;
+                let type_path = quote! { #(#supers)* #type_name };
+                quote! { pub const #entry_name: #type_path = #type_path(#id); }
+            },
+            None => quote! { pub const #entry_name: #wasset_id_path = #id; }
+        };
 
-    let module_name = proc_macro2::Ident::new(name, proc_macro2::Span::call_site());
+        inner_module.extend(quote! {
+            #constant
+            pub const #len_name: usize = #len;
+        });
+    }
 
-    quote! {
-        pub mod #module_name {
-            #inner_module
-        }
+    Ok(inner_module)
+}
+
+/// Creates an identifier with the given name, failing if the name is not a valid identifier.
+fn ident(name: &str) -> Result<proc_macro2::Ident, WassetError> {
+    let mut chars = name.chars();
+    if chars.next().is_some_and(|x| x == '_' || x.is_alphabetic()) && chars.all(|x| x == '_' || x.is_alphanumeric()) {
+        Ok(proc_macro2::Ident::new(name, proc_macro2::Span::call_site()))
+    }
+    else {
+        Err(WassetError::from_serialize(format!("{name:?} is not a valid identifier, so no constant or module can be generated for it")))
     }
 }
\ No newline at end of file
@@ -0,0 +1,108 @@
+use crate::*;
+use std::path::*;
+use std::sync::*;
+
+/// Describes how far the encoding of an asset folder has progressed.
+#[derive(Copy, Clone, Debug)]
+pub struct EncodeProgress<'a> {
+    /// The number of asset files which were found within the folder.
+    pub files_discovered: usize,
+    /// The number of asset files which have been read and encoded.
+    pub files_encoded: usize,
+    /// The total size, in bytes, of the asset files which have been read and encoded.
+    pub bytes_encoded: u64,
+    /// The file which began or finished encoding, if any.
+    pub current_file: Option<&'a Path>
+}
+
+/// Receives the progress of encoding an asset folder, so that tools may display a progress bar.
+/// The callback is invoked once after the asset files of the folder are found, and again before
+/// and after each file is encoded. With the **parallel** feature, it may be invoked from several
+/// threads at once, and files are reported in the order in which they finish.
+///
+/// ```
+/// # use wasset::*;
+/// # struct ProgressBar;
+/// # impl ProgressBar {
+/// #     fn set_length(&self, _: u64) {}
+/// #     fn set_position(&self, _: u64) {}
+/// # }
+/// # let bar = ProgressBar;
+/// # let mut options = WassetEncodeOptions::default();
+/// options.progress = Some(EncodeProgressCallback::new(move |progress| {
+///     bar.set_length(progress.files_discovered as u64);
+///     bar.set_position(progress.files_encoded as u64);
+/// }));
+/// ```
+#[derive(Clone)]
+pub struct EncodeProgressCallback(Arc<dyn Send + Sync + Fn(&EncodeProgress)>);
+
+impl EncodeProgressCallback {
+    /// Creates a callback which invokes the given function.
+    pub fn new(callback: impl 'static + Send + Sync + Fn(&EncodeProgress)) -> Self {
+        Self(Arc::new(callback))
+    }
+
+    /// Invokes the callback with the given progress.
+    pub fn report(&self, progress: &EncodeProgress) {
+        (self.0)(progress);
+    }
+}
+
+impl std::fmt::Debug for EncodeProgressCallback {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("EncodeProgressCallback(..)")
+    }
+}
+
+/// Counts the asset files of a folder which have been encoded, and reports the
+/// count to the [`WassetEncodeOptions::progress`] callback, if any.
+pub(crate) struct ProgressTracker<'a> {
+    /// The callback which receives the progress.
+    callback: Option<&'a EncodeProgressCallback>,
+    /// The number of asset files which were found within the folder.
+    files_discovered: usize,
+    /// The number of asset files which have been encoded.
+    files_encoded: atomic::AtomicUsize,
+    /// The total size of the asset files which have been encoded.
+    bytes_encoded: atomic::AtomicU64
+}
+
+impl<'a> ProgressTracker<'a> {
+    /// Creates a tracker for the given number of asset files, reporting that they were found.
+    pub fn new(callback: Option<&'a EncodeProgressCallback>, files_discovered: usize) -> Self {
+        let result = Self {
+            callback,
+            files_discovered,
+            files_encoded: atomic::AtomicUsize::new(0),
+            bytes_encoded: atomic::AtomicU64::new(0)
+        };
+
+        result.report(None);
+        result
+    }
+
+    /// Reports that a file has begun encoding.
+    pub fn start(&self, path: &Path) {
+        self.report(Some(path));
+    }
+
+    /// Reports that a file of the given size has finished encoding.
+    pub fn finish(&self, path: &Path, size: usize) {
+        self.files_encoded.fetch_add(1, atomic::Ordering::Relaxed);
+        self.bytes_encoded.fetch_add(size as u64, atomic::Ordering::Relaxed);
+        self.report(Some(path));
+    }
+
+    /// Invokes the callback with the current progress.
+    fn report(&self, current_file: Option<&Path>) {
+        if let Some(callback) = self.callback {
+            callback.report(&EncodeProgress {
+                files_discovered: self.files_discovered,
+                files_encoded: self.files_encoded.load(atomic::Ordering::Relaxed),
+                bytes_encoded: self.bytes_encoded.load(atomic::Ordering::Relaxed),
+                current_file
+            });
+        }
+    }
+}
@@ -0,0 +1,216 @@
+use crate::*;
+use std::collections::*;
+use std::path::*;
+
+/// Provides access to a tree of asset files. This allows assets to be encoded from
+/// sources other than the file system, such as archives held in memory. Providers must
+/// be shareable between threads, since the **parallel** feature reads files concurrently.
+pub trait FileProvider: Sync {
+    /// Gets the paths of all entries within the given directory.
+    fn read_dir(&self, path: &Path) -> Result<Vec<PathBuf>, WassetError>;
+
+    /// Determines whether the given path refers to a directory.
+    fn is_dir(&self, path: &Path) -> bool;
+
+    /// Determines whether the given path refers to a file.
+    fn is_file(&self, path: &Path) -> bool;
+
+    /// Reads the entire contents of a file.
+    fn read(&self, path: &Path) -> Result<Vec<u8>, WassetError>;
+
+    /// Reads the entire contents of a file as a string.
+    fn read_to_string(&self, path: &Path) -> Result<String, WassetError> {
+        String::from_utf8(self.read(path)?).map_err(WassetError::from_serialize)
+    }
+
+    /// Writes the entire contents of a file. By default, providers are read-only.
+    fn write(&self, path: &Path, data: &[u8]) -> Result<(), WassetError> {
+        let _ = data;
+        Err(WassetError::from_serialize(format!("Cannot write {}: file provider is read-only", path.display())))
+    }
+}
+
+/// Provides asset files from the local file system.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct DiskFileProvider;
+
+impl FileProvider for DiskFileProvider {
+    fn read_dir(&self, path: &Path) -> Result<Vec<PathBuf>, WassetError> {
+        std::fs::read_dir(path)
+            .map_err(WassetError::from_serialize)?
+            .map(|entry| entry.map(|x| x.path()).map_err(WassetError::from_serialize))
+            .collect()
+    }
+
+    fn is_dir(&self, path: &Path) -> bool {
+        path.is_dir()
+    }
+
+    fn is_file(&self, path: &Path) -> bool {
+        path.is_file()
+    }
+
+    fn read(&self, path: &Path) -> Result<Vec<u8>, WassetError> {
+        std::fs::read(path).map_err(WassetError::from_serialize)
+    }
+
+    fn write(&self, path: &Path, data: &[u8]) -> Result<(), WassetError> {
+        std::fs::write(path, data).map_err(WassetError::from_serialize)
+    }
+}
+
+/// Provides asset files which are held in memory, such as the contents of an archive.
+#[derive(Clone, Debug, Default)]
+pub struct MemoryFileProvider {
+    /// The contents of each file, keyed by path.
+    files: BTreeMap<PathBuf, Vec<u8>>,
+    /// The set of all directories which contain files.
+    directories: BTreeSet<PathBuf>
+}
+
+impl MemoryFileProvider {
+    /// Creates a new, empty provider.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a file to the provider, replacing any file that already existed at the same path.
+    pub fn insert(&mut self, path: impl AsRef<Path>, data: Vec<u8>) {
+        let path = path.as_ref().components().filter(|x| *x != Component::CurDir).collect::<PathBuf>();
+        self.directories.extend(path.ancestors().skip(1).filter(|x| !x.as_os_str().is_empty()).map(Path::to_path_buf));
+        self.files.insert(path, data);
+    }
+
+    /// Loads all files from a `.tar` archive. Fails if any file path is absolute or contains
+    /// `..`, since it could otherwise escape the asset folder.
+    #[cfg(feature = "tar")]
+    pub fn from_tar(reader: impl std::io::Read) -> Result<Self, WassetError> {
+        use std::io::Read;
+
+        let mut result = Self::new();
+        let mut archive = tar::Archive::new(reader);
+        for entry in archive.entries().map_err(WassetError::from_serialize)? {
+            let mut entry = entry.map_err(WassetError::from_serialize)?;
+            if entry.header().entry_type().is_file() {
+                let path = entry.path().map_err(WassetError::from_serialize)?.into_owned();
+                if !path.components().all(|x| matches!(x, Component::Normal(_) | Component::CurDir)) {
+                    return Err(WassetError::from_serialize(format!("Invalid path {} in archive", path.display())));
+                }
+
+                // The size in the header is not trusted for preallocation, since a malformed archive may claim any size.
+                let mut data = Vec::new();
+                entry.read_to_end(&mut data).map_err(WassetError::from_serialize)?;
+                result.insert(path, data);
+            }
+        }
+
+        Ok(result)
+    }
+
+    /// Loads all files from a `.zip` archive.
+    #[cfg(feature = "zip")]
+    pub fn from_zip(reader: impl std::io::Read + std::io::Seek) -> Result<Self, WassetError> {
+        use std::io::Read;
+
+        let mut result = Self::new();
+        let mut archive = zip::ZipArchive::new(reader).map_err(WassetError::from_serialize)?;
+        for index in 0..archive.len() {
+            let mut file = archive.by_index(index).map_err(WassetError::from_serialize)?;
+            if file.is_file() {
+                let path = file.enclosed_name().ok_or_else(|| WassetError::from_serialize(format!("Invalid path {} in archive", file.name())))?;
+                let mut data = Vec::new();
+                file.read_to_end(&mut data).map_err(WassetError::from_serialize)?;
+                result.insert(path, data);
+            }
+        }
+
+        Ok(result)
+    }
+}
+
+impl FileProvider for MemoryFileProvider {
+    fn read_dir(&self, path: &Path) -> Result<Vec<PathBuf>, WassetError> {
+        if !self.is_dir(path) {
+            return Err(WassetError::from_serialize(format!("Directory {} does not exist", path.display())));
+        }
+
+        let is_child = |x: &&PathBuf| x.parent() == Some(path);
+        Ok(self.directories.iter().filter(is_child)
+            .chain(self.files.keys().filter(is_child))
+            .cloned()
+            .collect())
+    }
+
+    fn is_dir(&self, path: &Path) -> bool {
+        self.directories.contains(path)
+    }
+
+    fn is_file(&self, path: &Path) -> bool {
+        self.files.contains_key(path)
+    }
+
+    fn read(&self, path: &Path) -> Result<Vec<u8>, WassetError> {
+        self.files.get(path).cloned().ok_or_else(|| WassetError::from_serialize(format!("File {} does not exist", path.display())))
+    }
+}
+
+#[cfg(all(test, feature = "testing"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn memory_provider_lists_nested_files() {
+        let mut provider = MemoryFileProvider::new();
+        provider.insert("./assets/a.txt", b"a".to_vec());
+        provider.insert("assets/sub/b.txt", b"b".to_vec());
+
+        assert!(provider.is_dir(Path::new("assets/sub")));
+        assert!(provider.is_file(Path::new("assets/a.txt")));
+        assert_eq!(provider.read_dir(Path::new("assets")).unwrap(), [PathBuf::from("assets/sub"), PathBuf::from("assets/a.txt")]);
+        assert_eq!(provider.read(Path::new("assets/sub/b.txt")).unwrap(), b"b");
+        assert!(provider.read_dir(Path::new("missing")).is_err());
+    }
+
+    /// Builds a `.tar` archive holding one file. The path is written into the header directly,
+    /// since the archive builder refuses to write unsafe paths.
+    #[cfg(feature = "tar")]
+    fn tar_with_file(path: &str, contents: &[u8]) -> Vec<u8> {
+        let mut header = tar::Header::new_old();
+        header.as_old_mut().name[..path.len()].copy_from_slice(path.as_bytes());
+        header.set_entry_type(tar::EntryType::Regular);
+        header.set_size(contents.len() as u64);
+        header.set_mode(0o644);
+        header.set_cksum();
+
+        let mut builder = tar::Builder::new(Vec::new());
+        builder.append(&header, contents).unwrap();
+        builder.into_inner().unwrap()
+    }
+
+    #[cfg(feature = "tar")]
+    #[test]
+    fn tar_files_are_loaded() {
+        let provider = MemoryFileProvider::from_tar(tar_with_file("assets/a.txt", b"hello").as_slice()).unwrap();
+        assert_eq!(provider.read(Path::new("assets/a.txt")).unwrap(), b"hello");
+    }
+
+    #[cfg(feature = "tar")]
+    #[test]
+    fn tar_paths_outside_folder_are_rejected() {
+        for path in ["../evil.txt", "assets/../../evil.txt", "/etc/evil.txt"] {
+            assert!(MemoryFileProvider::from_tar(tar_with_file(path, b"evil").as_slice()).is_err(), "{path} was accepted");
+        }
+    }
+
+    #[cfg(feature = "tar")]
+    #[test]
+    fn tar_with_oversized_header_is_rejected() {
+        let mut archive = tar_with_file("assets/a.txt", b"hello");
+        let mut header = tar::Header::from_byte_slice(&archive[..512]).clone();
+        header.set_size(0o77777777777);
+        header.set_cksum();
+        archive[..512].copy_from_slice(header.as_bytes());
+
+        assert!(MemoryFileProvider::from_tar(archive.as_slice()).is_err());
+    }
+}
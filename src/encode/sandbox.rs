@@ -0,0 +1,256 @@
+use crate::*;
+use std::collections::*;
+use std::io::{Read, Write, stdin, stdout};
+use std::marker::*;
+use std::path::*;
+use std::process::*;
+use std::sync::mpsc::{channel, RecvTimeoutError};
+use std::thread::{spawn, JoinHandle};
+use std::time::*;
+use toml::*;
+
+/// Describes an external process that hosts an [`AssetEncoder`] using [`serve_encoder`].
+pub trait EncoderProcess {
+    /// The target asset type that the process produces.
+    type Target: AssetSchema;
+
+    /// Creates the command which launches the encoder process. The command may be wrapped
+    /// with tools like `prlimit` or `nice` to restrict the resources available to the encoder.
+    fn command() -> Command;
+
+    /// The maximum amount of time that the process may spend encoding a single asset
+    /// before it is killed, or `None` if the process should never time out.
+    fn timeout() -> Option<Duration> {
+        Some(Duration::from_secs(60))
+    }
+}
+
+/// An [`AssetEncoder`] which runs the encoder described by `P` in a separate process
+/// for every asset. A crash, panic, or hang in the encoder results in a [`WassetError`]
+/// rather than bringing down the compiler during macro expansion. Files requested with
+/// [`EncodeContext::read_sibling`] are read from disk by the process itself.
+pub struct SandboxedEncoder<P: EncoderProcess>(PhantomData<fn(P)>);
+
+impl<P: EncoderProcess> AssetEncoder for SandboxedEncoder<P> {
+    type Target = P::Target;
+
+    fn encode(context: &EncodeContext, data: Vec<u8>) -> Result<Option<Self::Target>, WassetError> {
+        let header = EncodeRequestHeader {
+            extension: context.extension.to_owned(),
+            path: context.path.to_path_buf(),
+            relative_path: context.relative_path.to_path_buf(),
+            metadata: context.metadata.clone(),
+            folder_metadata: context.folder_metadata.iter().map(|x| (*x).clone()).collect()
+        };
+
+        let mut request = Vec::new();
+        write_frame(&mut request, &header)?;
+        request.extend_from_slice(&data);
+
+        let output = run_process(P::command(), request, P::timeout())?;
+        if !output.status.success() {
+            return Err(WassetError::from_serialize(format!("Encoder process exited with {}: {}", output.status, String::from_utf8_lossy(&output.stderr).trim())));
+        }
+
+        match output.stdout.split_first() {
+            Some((&EncodeResponse::NONE, _)) => Ok(None),
+            Some((&EncodeResponse::SOME, response)) => {
+                let ((consumed, metadata), asset) = read_frame::<(Vec<PathBuf>, BTreeMap<String, String>)>(response)?;
+                for path in consumed {
+                    context.files.consume(path);
+                }
+                context.files.extend_metadata(metadata);
+
+                Ok(Some(rmp_serde::from_slice(asset).map_err(WassetError::from_serialize)?))
+            },
+            Some((&EncodeResponse::ERROR, message)) => Err(WassetError::from_serialize(String::from_utf8_lossy(message).into_owned())),
+            _ => Err(WassetError::from_serialize("Encoder process returned a malformed response"))
+        }
+    }
+}
+
+/// Serves a single encode request from a [`SandboxedEncoder`] over standard input and output
+/// using encoder `A`. This should be called from the `main` function of the encoder process.
+pub fn serve_encoder<A: AssetEncoder>() -> Result<(), WassetError> {
+    let mut request = Vec::new();
+    stdin().lock().read_to_end(&mut request).map_err(WassetError::from_deserialize)?;
+    let (header, data) = read_frame::<EncodeRequestHeader>(&request)?;
+
+    let folder_metadata = header.folder_metadata.iter().collect::<Vec<_>>();
+    let root = header.path.ancestors().nth(header.relative_path.components().count()).unwrap_or(Path::new(""));
+    let files = FileAccess::new(&DiskFileProvider, root, &header.path);
+    let context = EncodeContext {
+        extension: &header.extension,
+        path: &header.path,
+        relative_path: &header.relative_path,
+        metadata: &header.metadata,
+        folder_metadata: &folder_metadata,
+        files: &files
+    };
+
+    let mut response = Vec::new();
+    match A::encode(&context, data.to_vec()) {
+        Ok(Some(asset)) => {
+            response.push(EncodeResponse::SOME);
+            write_frame(&mut response, &files.finish())?;
+            rmp_serde::encode::write_named(&mut response, &asset).map_err(WassetError::from_serialize)?;
+        },
+        Ok(None) => response.push(EncodeResponse::NONE),
+        Err(error) => {
+            response.push(EncodeResponse::ERROR);
+            response.extend_from_slice(error.to_string().as_bytes());
+        }
+    }
+
+    let mut out = stdout().lock();
+    out.write_all(&response).and_then(|_| out.flush()).map_err(WassetError::from_serialize)
+}
+
+/// Describes the asset which should be encoded by the process. The file data
+/// immediately follows the header.
+#[derive(Serialize, Deserialize)]
+struct EncodeRequestHeader {
+    /// The extension of the asset file.
+    extension: String,
+    /// The path of the asset file.
+    path: PathBuf,
+    /// The path of the asset file relative to the root asset folder.
+    relative_path: PathBuf,
+    /// The metadata for the asset file.
+    metadata: Table,
+    /// The contents of the `Wasset.toml` files of the folders which hold the asset file.
+    folder_metadata: Vec<Table>
+}
+
+/// Holds the tags which prefix an encoder process response.
+struct EncodeResponse;
+
+impl EncodeResponse {
+    /// The encoder did not produce an asset.
+    const NONE: u8 = 0;
+    /// The encoder produced an asset, which follows the tag, the paths of the files it consumed, and its metadata.
+    const SOME: u8 = 1;
+    /// The encoder failed, and an error message follows the tag.
+    const ERROR: u8 = 2;
+}
+
+/// Writes a length-prefixed, serialized value to the buffer.
+fn write_frame(buffer: &mut Vec<u8>, value: &impl Serialize) -> Result<(), WassetError> {
+    let frame = rmp_serde::to_vec_named(value).map_err(WassetError::from_serialize)?;
+    buffer.extend_from_slice(&(frame.len() as u32).to_le_bytes());
+    buffer.extend_from_slice(&frame);
+    Ok(())
+}
+
+/// Reads a length-prefixed, serialized value from the buffer, returning the value and the remaining bytes.
+fn read_frame<T: for<'de> Deserialize<'de>>(buffer: &[u8]) -> Result<(T, &[u8]), WassetError> {
+    let (len, rest) = buffer.split_first_chunk::<4>().ok_or_else(|| WassetError::from_deserialize("Encoder message was truncated"))?;
+    let len = u32::from_le_bytes(*len) as usize;
+    if rest.len() < len {
+        return Err(WassetError::from_deserialize("Encoder message was truncated"));
+    }
+
+    let value = rmp_serde::from_slice(&rest[..len]).map_err(WassetError::from_deserialize)?;
+    Ok((value, &rest[len..]))
+}
+
+/// Runs the command to completion, feeding it the provided input and killing it if it
+/// does not finish within the timeout.
+fn run_process(mut command: Command, input: Vec<u8>, timeout: Option<Duration>) -> Result<Output, WassetError> {
+    let mut child = command
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(WassetError::from_serialize)?;
+
+    let missing_pipe = || WassetError::from_serialize("Failed to connect to encoder process");
+    let mut child_stdin = child.stdin.take().ok_or_else(missing_pipe)?;
+    let mut child_stdout = child.stdout.take().ok_or_else(missing_pipe)?;
+    let mut child_stderr = child.stderr.take().ok_or_else(missing_pipe)?;
+
+    // Pipes are serviced on separate threads so that a large request or response cannot deadlock.
+    // The output reader signals once the process closes its output, which it does upon exiting.
+    let (finished, output_closed) = channel();
+    let writer = spawn(move || child_stdin.write_all(&input));
+    let reader = spawn(move || {
+        let mut buffer = Vec::new();
+        let result = child_stdout.read_to_end(&mut buffer).map(|_| buffer);
+        let _ = finished.send(());
+        result
+    });
+    let error_reader = spawn(move || {
+        let mut buffer = Vec::new();
+        child_stderr.read_to_end(&mut buffer).map(|_| buffer)
+    });
+
+    if let Some(timeout) = timeout {
+        if output_closed.recv_timeout(timeout) == Err(RecvTimeoutError::Timeout) {
+            let _ = child.kill();
+            let _ = child.wait();
+            return Err(WassetError::from_serialize("Encoder process timed out"));
+        }
+    }
+
+    let status = child.wait().map_err(WassetError::from_serialize)?;
+
+    // A process that exits early may close its input before reading the request, so write errors are ignored.
+    let _ = writer.join();
+    let stdout = join_reader(reader)?;
+    let stderr = join_reader(error_reader)?;
+
+    Ok(Output { status, stdout, stderr })
+}
+
+/// Waits for a thread which reads one of the encoder process pipes, returning the bytes it read.
+fn join_reader(reader: JoinHandle<std::io::Result<Vec<u8>>>) -> Result<Vec<u8>, WassetError> {
+    reader.join()
+        .map_err(|_| WassetError::from_serialize("Failed to read encoder process output"))?
+        .map_err(WassetError::from_serialize)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn frames_round_trip() {
+        let mut buffer = Vec::new();
+        write_frame(&mut buffer, &String::from("first")).unwrap();
+        write_frame(&mut buffer, &7u32).unwrap();
+
+        let (first, rest) = read_frame::<String>(&buffer).unwrap();
+        let (second, rest) = read_frame::<u32>(rest).unwrap();
+        assert_eq!((first.as_str(), second), ("first", 7));
+        assert!(rest.is_empty());
+    }
+
+    #[test]
+    fn truncated_frames_are_rejected() {
+        let mut buffer = Vec::new();
+        write_frame(&mut buffer, &String::from("truncated")).unwrap();
+
+        for len in [0, 3, 4, buffer.len() - 1] {
+            assert!(read_frame::<String>(&buffer[..len]).is_err(), "frame of {len} bytes was accepted");
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn process_output_is_collected() {
+        let output = run_process(Command::new("cat"), b"echoed".to_vec(), Some(Duration::from_secs(30))).unwrap();
+        assert!(output.status.success());
+        assert_eq!(output.stdout, b"echoed");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn slow_process_is_killed() {
+        let mut command = Command::new("sleep");
+        command.arg("30");
+
+        let start = Instant::now();
+        assert!(run_process(command, Vec::new(), Some(Duration::from_millis(100))).is_err());
+        assert!(start.elapsed() < Duration::from_secs(10));
+    }
+}
@@ -0,0 +1,228 @@
+use crate::*;
+use notify::{recommended_watcher, Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use std::marker::*;
+use std::path::*;
+use std::sync::mpsc::{channel, Receiver, RecvError};
+use std::time::*;
+
+/// Watches an asset folder and re-encodes it whenever its files change. Combined with an API
+/// which replaces the assets of a running plugin, this allows for reloading assets during
+/// development without recompiling the plugin.
+///
+/// Changes are reported by ID, so assets should be encoded with a deterministic
+/// [`IdStrategy`] or an [`IdLockMode`] that records their IDs. Otherwise, every asset
+/// receives a new ID whenever the folder is encoded.
+///
+/// ```no_run
+/// # use wasset::*;
+/// # use std::path::Path;
+/// # struct MyEncoder;
+/// # impl AssetEncoder for MyEncoder {
+/// #     type Target = Vec<u8>;
+/// #     fn encode(_: &EncodeContext, data: Vec<u8>) -> Result<Option<Vec<u8>>, WassetError> {
+/// #         Ok(Some(data))
+/// #     }
+/// # }
+/// # struct Engine;
+/// # impl Engine {
+/// #     fn reload(&self, _: &EncodedAssets, _: &[WassetId]) {}
+/// # }
+/// # let engine = Engine;
+/// # let options = WassetEncodeOptions::default();
+/// let mut watcher = WassetWatcher::<MyEncoder>::new(Path::new("assets"), options)?;
+/// while let Some(event) = watcher.wait()? {
+///     engine.reload(&event.assets, &event.changed);
+/// }
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+pub struct WassetWatcher<A: AssetEncoder> {
+    /// The folder which is being watched.
+    folder: PathBuf,
+    /// The options with which the folder is encoded.
+    options: WassetEncodeOptions,
+    /// The most recent successful encoding of the folder.
+    assets: EncodedAssets,
+    /// The content hash of every asset in the most recent encoding.
+    hashes: FxHashMap<WassetId, Option<WassetHash>>,
+    /// Receives file system events from the watcher.
+    events: Receiver<notify::Result<Event>>,
+    /// The underlying file system watcher, which stops watching when dropped.
+    _watcher: RecommendedWatcher,
+    /// A marker type for `A`.
+    marker: PhantomData<fn(A)>
+}
+
+impl<A: AssetEncoder> WassetWatcher<A> {
+    /// Encodes the given folder and begins watching it and its subfolders for changes.
+    pub fn new(folder: &Path, options: WassetEncodeOptions) -> Result<Self, WassetError> {
+        let (sender, events) = channel();
+        let mut watcher = recommended_watcher(sender).map_err(WassetError::from_serialize)?;
+        watcher.watch(folder, RecursiveMode::Recursive).map_err(|e| WassetError::from_serialize(e).in_file(folder))?;
+
+        let assets = encode_asset_folder_with_options::<A>(folder, &options)?;
+        let hashes = asset_hashes(&assets)?;
+
+        Ok(Self {
+            folder: folder.to_path_buf(),
+            options,
+            assets,
+            hashes,
+            events,
+            _watcher: watcher,
+            marker: PhantomData
+        })
+    }
+
+    /// Gets the most recent successful encoding of the folder.
+    pub fn assets(&self) -> &EncodedAssets {
+        &self.assets
+    }
+
+    /// Blocks until the files of the folder change in a way that alters its assets, and then
+    /// re-encodes it. Returns `None` if the folder can no longer be watched. If encoding fails,
+    /// such as because a file was only partially written, the error is returned and the previous
+    /// assets are kept until the next change.
+    pub fn wait(&mut self) -> Result<Option<WassetWatchEvent>, WassetError> {
+        loop {
+            match self.events.recv() {
+                Ok(event) => if let Some(event) = self.handle(event)? {
+                    return Ok(Some(event));
+                },
+                Err(RecvError) => return Ok(None)
+            }
+        }
+    }
+
+    /// Re-encodes the folder if its files have changed since the last call, without blocking.
+    /// Returns `None` if the assets have not changed.
+    pub fn poll(&mut self) -> Result<Option<WassetWatchEvent>, WassetError> {
+        while let Ok(event) = self.events.try_recv() {
+            if let Some(event) = self.handle(event)? {
+                return Ok(Some(event));
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Handles a file system event, along with any which follow it in quick succession,
+    /// re-encoding the folder if they modified it.
+    fn handle(&mut self, event: notify::Result<Event>) -> Result<Option<WassetWatchEvent>, WassetError> {
+        let mut modified = is_modification(event)?;
+        while let Ok(event) = self.events.recv_timeout(DEBOUNCE_INTERVAL) {
+            modified |= is_modification(event)?;
+        }
+
+        if !modified {
+            return Ok(None);
+        }
+
+        let assets = encode_asset_folder_with_options::<A>(&self.folder, &self.options)?;
+        let hashes = asset_hashes(&assets)?;
+
+        let mut changed = Vec::new();
+        let mut added = Vec::new();
+        for (id, hash) in &hashes {
+            match self.hashes.get(id) {
+                Some(old) if old == hash => {},
+                Some(_) => changed.push(*id),
+                None => added.push(*id)
+            }
+        }
+
+        let mut removed = self.hashes.keys().filter(|x| !hashes.contains_key(*x)).copied().collect::<Vec<_>>();
+        if changed.is_empty() && added.is_empty() && removed.is_empty() {
+            return Ok(None);
+        }
+
+        changed.sort();
+        added.sort();
+        removed.sort();
+
+        #[cfg(feature = "tracing")]
+        tracing::debug!(folder = %self.folder.display(), changed = changed.len(), added = added.len(), removed = removed.len(), "re-encoded watched folder");
+
+        self.assets = assets;
+        self.hashes = hashes;
+        Ok(Some(WassetWatchEvent { assets: self.assets.clone(), changed, added, removed }))
+    }
+}
+
+/// Describes how the assets of a watched folder changed when it was re-encoded.
+#[derive(Clone, Debug, Default)]
+pub struct WassetWatchEvent {
+    /// The new encoding of the folder.
+    pub assets: EncodedAssets,
+    /// The IDs of the assets whose contents changed.
+    pub changed: Vec<WassetId>,
+    /// The IDs of the assets which did not exist before.
+    pub added: Vec<WassetId>,
+    /// The IDs of the assets which no longer exist.
+    pub removed: Vec<WassetId>
+}
+
+/// How long the watcher waits for further file system events before re-encoding, so that
+/// an editor which writes several files at once causes only one re-encode.
+const DEBOUNCE_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Gets the content hash of every asset in a set of encoded assets.
+fn asset_hashes(assets: &EncodedAssets) -> Result<FxHashMap<WassetId, Option<WassetHash>>, WassetError> {
+    let mut result = FxHashMap::default();
+    for set in assets.section_sets() {
+        let manifest = WassetManifest::from_bytes(set.manifest)?;
+        result.extend(manifest.iter().map(|(id, entry)| (id, entry.hash())));
+    }
+
+    Ok(result)
+}
+
+/// Determines whether a file system event may have modified the contents of the folder.
+fn is_modification(event: notify::Result<Event>) -> Result<bool, WassetError> {
+    let event = event.map_err(WassetError::from_serialize)?;
+    Ok(!matches!(event.kind, EventKind::Access(_)))
+}
+
+#[cfg(all(test, feature = "testing"))]
+mod tests {
+    use super::*;
+
+    /// Gets the ID of every asset in a set of encoded assets by name.
+    fn ids_by_name(assets: &EncodedAssets) -> FxHashMap<String, WassetId> {
+        assets.encoded_assets.values().flat_map(|x| &x.assets).map(|x| (x.name.clone(), x.id)).collect()
+    }
+
+    #[test]
+    fn changes_are_reported_by_id() {
+        let folder = std::env::temp_dir().join(format!("wasset-watch-{}", std::process::id())).join("assets");
+        let _ = std::fs::remove_dir_all(&folder);
+        std::fs::create_dir_all(&folder).unwrap();
+        for (path, contents) in [("a.txt", "first"), ("b.txt", "second"), ("c.txt", "third")] {
+            std::fs::write(folder.join(path), contents).unwrap();
+        }
+
+        let options = WassetEncodeOptions { id_strategy: IdStrategy::Deterministic { namespace: Uuid::from_bytes([1; 16]) }, ..Default::default() };
+        let mut watcher = WassetWatcher::<testing::TextEncoder>::new(&folder, options).unwrap();
+        let before = ids_by_name(watcher.assets());
+        assert!(watcher.poll().unwrap().is_none());
+
+        std::fs::write(folder.join("a.txt"), "modified").unwrap();
+        std::fs::remove_file(folder.join("b.txt")).unwrap();
+        std::fs::write(folder.join("d.txt"), "added").unwrap();
+
+        let deadline = Instant::now() + Duration::from_secs(10);
+        let event = loop {
+            if let Some(event) = watcher.poll().unwrap() {
+                break event;
+            }
+            assert!(Instant::now() < deadline, "no change was reported");
+            std::thread::sleep(Duration::from_millis(10));
+        };
+
+        let after = ids_by_name(&event.assets);
+        assert_eq!(event.changed, [before["a"]]);
+        assert_eq!(event.removed, [before["b"]]);
+        assert_eq!(event.added, [after["d"]]);
+        assert_eq!(before["c"], after["c"]);
+        std::fs::remove_dir_all(folder.parent().unwrap()).unwrap();
+    }
+}
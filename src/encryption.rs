@@ -0,0 +1,102 @@
+#[cfg(any(feature = "encode", feature = "parse"))]
+use crate::*;
+#[cfg(any(feature = "encode", feature = "parse"))]
+use chacha20poly1305::{Key, KeyInit, XChaCha20Poly1305, XNonce};
+#[cfg(any(feature = "encode", feature = "parse"))]
+use chacha20poly1305::aead::Aead;
+
+/// A symmetric key with which asset payloads are encrypted. When a key is supplied through
+/// `WassetEncodeOptions::encryption_key`, the stored bytes of every asset are sealed with
+/// XChaCha20-Poly1305 after compression, so they cannot be extracted from the module without
+/// the key. The same key must be supplied through `WassetParseOptions::encryption_key` to
+/// load the assets again, and tampered payloads fail to load.
+///
+/// Only asset payloads are encrypted. Manifests, including the names, kinds, and hashes of
+/// assets, along with previews, remain readable. Nonces are derived from the key and payload,
+/// so encoding the same assets twice produces identical modules.
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct WassetEncryptionKey([u8; 32]);
+
+impl WassetEncryptionKey {
+    /// Creates a key from its raw bytes.
+    pub const fn from_bytes(bytes: [u8; 32]) -> Self {
+        Self(bytes)
+    }
+
+    /// Gets the raw bytes of the key.
+    pub const fn as_bytes(&self) -> &[u8; 32] {
+        &self.0
+    }
+
+    /// Encrypts the given payload, returning the nonce followed by the ciphertext.
+    #[cfg(feature = "encode")]
+    pub(crate) fn encrypt(&self, payload: &[u8]) -> Result<Vec<u8>, WassetError> {
+        let nonce = blake3::keyed_hash(&self.0, payload);
+        let nonce = XNonce::from_slice(&nonce.as_bytes()[..NONCE_SIZE]);
+        let ciphertext = self.cipher().encrypt(nonce, payload)
+            .map_err(|_| WassetError::from_serialize("Failed to encrypt asset"))?;
+
+        let mut result = Vec::with_capacity(NONCE_SIZE + ciphertext.len());
+        result.extend_from_slice(nonce);
+        result.extend_from_slice(&ciphertext);
+        Ok(result)
+    }
+
+    /// Decrypts a payload which was produced by [`Self::encrypt`].
+    #[cfg(feature = "parse")]
+    pub(crate) fn decrypt(&self, stored: &[u8]) -> Result<Vec<u8>, WassetError> {
+        if stored.len() < NONCE_SIZE {
+            return Err(WassetError::from_deserialize("Encrypted asset is missing its nonce"));
+        }
+
+        let (nonce, ciphertext) = stored.split_at(NONCE_SIZE);
+        self.cipher().decrypt(XNonce::from_slice(nonce), ciphertext)
+            .map_err(|_| WassetError::from_deserialize("Failed to decrypt asset; the key is incorrect or the data was modified"))
+    }
+
+    /// Creates the cipher for this key.
+    #[cfg(any(feature = "encode", feature = "parse"))]
+    fn cipher(&self) -> XChaCha20Poly1305 {
+        XChaCha20Poly1305::new(Key::from_slice(&self.0))
+    }
+}
+
+impl std::fmt::Debug for WassetEncryptionKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("WassetEncryptionKey(..)")
+    }
+}
+
+/// The number of nonce bytes which precede the ciphertext of an encrypted asset.
+#[cfg(any(feature = "encode", feature = "parse"))]
+const NONCE_SIZE: usize = 24;
+
+#[cfg(all(test, feature = "testing"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encrypted_asset_requires_key() {
+        let id = WassetId::from_bytes([1; 16]);
+        let key = WassetEncryptionKey::from_bytes([7; 32]);
+        let options = WassetEncodeOptions { encryption_key: Some(key.clone()), ..Default::default() };
+        let module = testing::build_module_with_options([(id, String::from("a secret message"))], &options).unwrap();
+        assert!(!module.windows(16).any(|x| x == b"a secret message"));
+
+        let load = |encryption_key| WassetParser::<String>::parse_with_options(&module, WassetParseOptions { encryption_key, ..Default::default() })
+            .and_then(|parser| parser.load(id));
+        assert_eq!(load(Some(key)).unwrap().as_deref(), Some("a secret message"));
+        assert!(load(None).is_err());
+        assert!(load(Some(WassetEncryptionKey::from_bytes([8; 32]))).is_err());
+    }
+
+    #[test]
+    fn encryption_is_deterministic() {
+        let key = WassetEncryptionKey::from_bytes([7; 32]);
+        let encrypted = key.encrypt(b"payload").unwrap();
+        assert_eq!(encrypted, key.encrypt(b"payload").unwrap());
+        assert_ne!(encrypted, key.encrypt(b"other payload").unwrap());
+        assert_eq!(key.decrypt(&encrypted).unwrap(), b"payload");
+        assert!(key.decrypt(&encrypted[..NONCE_SIZE - 1]).is_err());
+    }
+}
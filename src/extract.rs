@@ -0,0 +1,73 @@
+use crate::*;
+use std::path::*;
+
+/// Converts assets back into the contents of files, as the inverse of an [`AssetEncoder`].
+/// This allows for pulling the assets out of a shipped module for inspection.
+pub trait AssetDecoder {
+    /// The asset type that this decoder reads.
+    type Source: AssetSchema;
+
+    /// Produces the contents of a file from an asset.
+    fn decode(asset: &Self::Source) -> Result<Vec<u8>, WassetError>;
+
+    /// Gets the extension of the file to which an asset is written, without the leading `.`.
+    /// Manifests record the names of assets without their extensions, so this determines
+    /// the extension of every extracted file.
+    fn extension(asset: &Self::Source) -> Option<String> {
+        let _ = asset;
+        None
+    }
+}
+
+impl<'a, A: AssetSchema> WassetParser<'a, A> {
+    /// Decodes every asset and writes it to a file within `folder`, at the path from which it
+    /// was encoded. Assets without a name, or whose name would leave the folder, are named after
+    /// their ID, as are assets whose path was already written. Returns the paths of the written files,
+    /// in ID order.
+    pub fn extract_all<D: AssetDecoder<Source = A>>(&self, folder: &Path) -> Result<Vec<PathBuf>, WassetError> {
+        let mut ids = self.ids().collect::<Vec<_>>();
+        ids.sort();
+
+        let mut result = Vec::with_capacity(ids.len());
+        let mut written = FxHashSet::default();
+        for id in ids {
+            let Some(asset) = self.load(id)? else {
+                continue;
+            };
+
+            let extension = D::extension(&asset);
+            let path_for = |name: PathBuf| {
+                let mut path = folder.join(name).into_os_string();
+                if let Some(extension) = &extension {
+                    path.push(".");
+                    path.push(extension);
+                }
+                PathBuf::from(path)
+            };
+
+            let mut path = self.manifest().get(id).and_then(WassetManifestEntry::name).and_then(extracted_name)
+                .map(&path_for)
+                .unwrap_or_else(|| path_for(PathBuf::from(Uuid::from(id).to_string())));
+            if !written.insert(path.clone()) {
+                path = path_for(PathBuf::from(Uuid::from(id).to_string()));
+                written.insert(path.clone());
+            }
+
+            let data = D::decode(&asset).map_err(|e| e.in_file(&path))?;
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent).map_err(|e| WassetError::from_serialize(e).in_file(parent))?;
+            }
+
+            std::fs::write(&path, data).map_err(|e| WassetError::from_serialize(e).in_file(&path))?;
+            result.push(path);
+        }
+
+        Ok(result)
+    }
+}
+
+/// Converts the name of an asset into a relative path, if the path lies within its folder.
+fn extracted_name(name: &str) -> Option<PathBuf> {
+    let path = PathBuf::from(name);
+    (!name.is_empty() && path.components().all(|x| matches!(x, Component::Normal(_)))).then_some(path)
+}
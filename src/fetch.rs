@@ -0,0 +1,169 @@
+use crate::*;
+
+impl<'a, A: AssetSchema> WassetParser<'a, A> {
+    /// Determines the byte ranges of the module that hold the given assets and their quality
+    /// tiers, merging adjacent ranges. Launchers which host modules remotely may download exactly
+    /// these slices with HTTP range requests, rather than the whole module. IDs that do not refer
+    /// to an asset, or that refer to an asset whose data was stripped, are ignored.
+    pub fn fetch_plan(&self, ids: impl IntoIterator<Item = WassetId>) -> Vec<ByteRange> {
+        self.fetch_plan_with_options(ids, &WassetFetchOptions::default())
+    }
+
+    /// Determines the byte ranges of the module that hold the given assets, according to the
+    /// provided options. The resulting ranges are sorted and do not overlap.
+    pub fn fetch_plan_with_options(&self, ids: impl IntoIterator<Item = WassetId>, options: &WassetFetchOptions) -> Vec<ByteRange> {
+        let mut pending = ids.into_iter().collect::<Vec<_>>();
+        let mut visited = FxHashSet::default();
+        let mut ranges = Vec::new();
+
+        while let Some(id) = pending.pop() {
+            let Some(entry) = self.manifest().get(id).filter(|_| visited.insert(id)) else {
+                continue;
+            };
+
+            if !entry.is_external() {
+                ranges.extend(entry.payloads().map(|x| ByteRange { start: x.start as u64, end: x.end as u64 }));
+            }
+
+            if options.include_dependencies {
+                pending.extend_from_slice(entry.dependencies());
+            }
+        }
+
+        ranges.sort_by_key(|x| x.start);
+
+        let mut result = Vec::<ByteRange>::with_capacity(ranges.len());
+        for range in ranges {
+            match result.last_mut() {
+                Some(last) if range.start <= last.end.saturating_add(options.max_gap) => last.end = last.end.max(range.end),
+                _ => result.push(range)
+            }
+        }
+
+        result
+    }
+}
+
+/// Configures how [`WassetParser::fetch_plan_with_options`] selects and merges byte ranges.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct WassetFetchOptions {
+    /// Whether the transitive dependencies of the requested assets should be fetched as well.
+    pub include_dependencies: bool,
+    /// The largest number of unneeded bytes that may lie between two ranges for them to be
+    /// merged. Larger values trade wasted bandwidth for fewer requests.
+    pub max_gap: u64
+}
+
+/// A contiguous range of bytes within a module.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ByteRange {
+    /// The offset of the first byte in the range.
+    pub start: u64,
+    /// The offset one past the last byte in the range.
+    pub end: u64
+}
+
+impl ByteRange {
+    /// Gets the number of bytes in the range.
+    pub fn len(&self) -> u64 {
+        self.end - self.start
+    }
+
+    /// Whether the range contains no bytes.
+    pub fn is_empty(&self) -> bool {
+        self.start == self.end
+    }
+
+    /// Formats a list of ranges as the value of an HTTP `Range` header, or returns
+    /// `None` if there are no non-empty ranges to request.
+    pub fn to_header_value(ranges: &[ByteRange]) -> Option<String> {
+        let ranges = ranges.iter().filter(|x| !x.is_empty()).map(|x| format!("{}-{}", x.start, x.end - 1)).collect::<Vec<_>>();
+        (!ranges.is_empty()).then(|| format!("bytes={}", ranges.join(",")))
+    }
+}
+
+impl From<ByteRange> for Range<u64> {
+    fn from(value: ByteRange) -> Self {
+        value.start..value.end
+    }
+}
+
+#[cfg(all(test, feature = "testing"))]
+mod tests {
+    use super::*;
+
+    /// Builds a module holding three text assets, where `a` depends upon `b`, returning the module
+    /// and the IDs of `a`, `b`, and `c`.
+    fn sample_module() -> (Vec<u8>, [WassetId; 3]) {
+        let files = [("Wasset.toml", "[\"a.txt\"]\ndependencies = [\"b\"]"), ("a.txt", "first"), ("b.txt", "second"), ("c.txt", "third")];
+        let assets = testing::encode_text_files(files, &WassetEncodeOptions::default()).unwrap();
+        let module = WassetModuleBuilder::new().add_assets(assets).build();
+        let manifest = WassetParser::<String>::parse(&module).unwrap().manifest().clone();
+        let ids = ["assets/a", "assets/b", "assets/c"].map(|name| manifest.iter().find(|(_, entry)| entry.name() == Some(name)).unwrap().0);
+        (module, ids)
+    }
+
+    /// Gets the byte range in which the given asset is stored.
+    fn range_of(parser: &WassetParser<String>, id: WassetId) -> ByteRange {
+        let range = parser.manifest().get(id).unwrap().range();
+        ByteRange { start: range.start as u64, end: range.end as u64 }
+    }
+
+    #[test]
+    fn fetch_plan_merges_adjacent_ranges() {
+        let (module, [a, b, c]) = sample_module();
+        let parser = WassetParser::<String>::parse(&module).unwrap();
+        let mut ranges = [a, b, c].map(|id| range_of(&parser, id));
+        ranges.sort();
+        assert_eq!(ranges[0].end, ranges[1].start);
+
+        let plan = parser.fetch_plan([a, b, c, WassetId::from_bytes([0; 16])]);
+        assert_eq!(plan, [ByteRange { start: ranges[0].start, end: ranges[2].end }]);
+    }
+
+    #[test]
+    fn fetch_plan_merges_ranges_within_max_gap() {
+        let (module, [a, b, c]) = sample_module();
+        let parser = WassetParser::<String>::parse(&module).unwrap();
+        let mut ranges = [a, b, c].map(|id| range_of(&parser, id));
+        ranges.sort();
+        let [first, middle, last] = ranges;
+        let outer = [a, b, c].into_iter().filter(|id| range_of(&parser, *id) != middle).collect::<Vec<_>>();
+
+        assert_eq!(parser.fetch_plan(outer.iter().copied()), [first, last]);
+        let options = WassetFetchOptions { max_gap: middle.len() - 1, ..Default::default() };
+        assert_eq!(parser.fetch_plan_with_options(outer.iter().copied(), &options), [first, last]);
+        let options = WassetFetchOptions { max_gap: middle.len(), ..Default::default() };
+        assert_eq!(parser.fetch_plan_with_options(outer.iter().copied(), &options), [ByteRange { start: first.start, end: last.end }]);
+    }
+
+    #[test]
+    fn fetch_plan_expands_dependencies() {
+        let (module, [a, b, _]) = sample_module();
+        let parser = WassetParser::<String>::parse(&module).unwrap();
+
+        assert_eq!(parser.fetch_plan([a]), [range_of(&parser, a)]);
+        let options = WassetFetchOptions { include_dependencies: true, ..Default::default() };
+        let plan = parser.fetch_plan_with_options([a], &options);
+        assert_eq!(plan.iter().map(ByteRange::len).sum::<u64>(), range_of(&parser, a).len() + range_of(&parser, b).len());
+        assert!(plan.iter().any(|x| x.start <= range_of(&parser, b).start && range_of(&parser, b).end <= x.end));
+    }
+
+    #[test]
+    fn fetch_plan_skips_external_assets() {
+        let (module, [a, b, _]) = sample_module();
+        let stripped = WassetParser::<String>::parse(&module).unwrap().strip_data().unwrap();
+        let parser = WassetParser::<String>::parse(&stripped).unwrap();
+
+        let options = WassetFetchOptions { include_dependencies: true, ..Default::default() };
+        assert!(parser.fetch_plan_with_options([a, b], &options).is_empty());
+    }
+
+    #[test]
+    fn ranges_are_formatted_as_header_value() {
+        let ranges = [ByteRange { start: 0, end: 10 }, ByteRange { start: 5, end: 5 }, ByteRange { start: 20, end: 21 }];
+        assert_eq!(ByteRange::to_header_value(&ranges).as_deref(), Some("bytes=0-9,20-20"));
+        assert_eq!(ByteRange::to_header_value(&ranges[1..2]), None);
+        assert_eq!(ByteRange::to_header_value(&[]), None);
+    }
+}
@@ -0,0 +1,108 @@
+use crate::*;
+
+/// Determines the order in which the given assets and all of their transitive dependencies should
+/// be loaded, such that every asset comes after the assets upon which it depends. The dependencies
+/// of each asset are obtained from `dependencies`, which returns `None` for assets that do not exist.
+/// Requested assets which do not exist are omitted.
+pub(crate) fn dependency_order<'a>(ids: impl IntoIterator<Item = WassetId>, dependencies: impl Fn(WassetId) -> Option<&'a [WassetId]>) -> Result<Vec<WassetId>, WassetError> {
+    let mut traversal = DependencyTraversal {
+        dependencies,
+        order: Vec::new(),
+        path: Vec::new(),
+        states: FxHashMap::default()
+    };
+
+    for id in ids {
+        traversal.visit(id)?;
+    }
+
+    Ok(traversal.order)
+}
+
+/// Tracks the progress of a depth-first dependency traversal for a single asset.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum VisitState {
+    /// The asset's dependencies are currently being visited.
+    InProgress,
+    /// The asset and all of its dependencies have been ordered.
+    Done
+}
+
+/// Holds the state of a depth-first traversal over asset dependencies.
+struct DependencyTraversal<F> {
+    /// Gets the dependencies of an asset, or `None` if the asset does not exist.
+    dependencies: F,
+    /// The assets which have been ordered so far.
+    order: Vec<WassetId>,
+    /// The chain of assets which are currently being visited.
+    path: Vec<WassetId>,
+    /// The progress of each asset which has been reached.
+    states: FxHashMap<WassetId, VisitState>
+}
+
+impl<'a, F: Fn(WassetId) -> Option<&'a [WassetId]>> DependencyTraversal<F> {
+    /// Appends the dependencies of an asset to the order, followed by the asset itself.
+    /// Returns `false` if the asset does not exist.
+    fn visit(&mut self, id: WassetId) -> Result<bool, WassetError> {
+        match self.states.get(&id) {
+            Some(VisitState::Done) => return Ok(true),
+            Some(VisitState::InProgress) => {
+                let start = self.path.iter().position(|x| *x == id).unwrap_or_default();
+                return Err(WassetError::DependencyCycle(self.path[start..].to_vec()));
+            },
+            None => {}
+        }
+
+        let Some(dependencies) = (self.dependencies)(id) else {
+            return Ok(false);
+        };
+
+        self.states.insert(id, VisitState::InProgress);
+        self.path.push(id);
+
+        for &dependency in dependencies {
+            if !self.visit(dependency)? {
+                return Err(WassetError::MissingDependency { asset: id, dependency });
+            }
+        }
+
+        self.path.pop();
+        self.states.insert(id, VisitState::Done);
+        self.order.push(id);
+        Ok(true)
+    }
+}
+
+#[cfg(all(test, feature = "testing"))]
+mod tests {
+    use super::*;
+
+    /// Gets the ID of the asset with the given number.
+    fn id(x: u8) -> WassetId {
+        WassetId::from_bytes([x; 16])
+    }
+
+    #[test]
+    fn dependencies_come_before_dependents() {
+        let graph = FxHashMap::from_iter([(id(1), vec![id(2), id(3)]), (id(2), vec![id(3)]), (id(3), Vec::new()), (id(4), vec![id(3)])]);
+        let order = dependency_order([id(1), id(4), id(5)], |x| graph.get(&x).map(Vec::as_slice)).unwrap();
+        assert_eq!(order, [id(3), id(2), id(1), id(4)]);
+    }
+
+    #[test]
+    fn missing_dependencies_and_cycles_are_rejected() {
+        let graph = FxHashMap::from_iter([(id(1), vec![id(2)]), (id(2), vec![id(5)])]);
+        assert!(matches!(dependency_order([id(1)], |x| graph.get(&x).map(Vec::as_slice)), Err(WassetError::MissingDependency { asset, dependency }) if asset == id(2) && dependency == id(5)));
+
+        let graph = FxHashMap::from_iter([(id(1), vec![id(2)]), (id(2), vec![id(3)]), (id(3), vec![id(1)])]);
+        assert!(matches!(dependency_order([id(2)], |x| graph.get(&x).map(Vec::as_slice)), Err(WassetError::DependencyCycle(cycle)) if cycle == [id(2), id(3), id(1)]));
+    }
+
+    #[test]
+    fn manifest_ids_are_ordered_by_dependency() {
+        let module = testing::build_module_with_dependencies([(id(1), 1u32, vec![id(3)]), (id(2), 2, Vec::new()), (id(3), 3, vec![id(2)])]).unwrap();
+        let parser = WassetParser::<u32>::parse(&module).unwrap();
+        assert_eq!(parser.ids_by_dependency().unwrap(), [id(2), id(3), id(1)]);
+        assert_eq!(parser.load_order([id(3)]).unwrap(), [id(2), id(3)]);
+    }
+}
@@ -0,0 +1,100 @@
+use crate::*;
+
+/// Gets the size of the serialized asset with the given ID, as reported by the host.
+/// Returns `None` if the host does not provide the asset.
+pub fn guest_asset_len(id: WassetId) -> Option<usize> {
+    let result = unsafe { imports::asset_len(id.as_bytes().as_ptr()) };
+    usize::try_from(result).ok()
+}
+
+/// Reads the serialized bytes of one of this module's own assets from the host.
+/// Returns `None` if the host does not provide the asset.
+pub fn guest_load_raw(id: WassetId) -> Result<Option<Vec<u8>>, WassetError> {
+    let mut result = Vec::with_capacity(guest_asset_len(id).unwrap_or_default());
+    if guest_load_raw_into(id, &mut result)? {
+        Ok(Some(result))
+    }
+    else {
+        Ok(None)
+    }
+}
+
+/// Reads the serialized bytes of one of this module's own assets from the host into `buffer`,
+/// which is reused across loads. Returns `false` if the host does not provide the asset.
+pub fn guest_load_raw_into(id: WassetId, buffer: &mut Vec<u8>) -> Result<bool, WassetError> {
+    loop {
+        buffer.resize(buffer.capacity(), 0);
+        let len = check_result(id, unsafe { imports::asset_read(id.as_bytes().as_ptr(), buffer.as_mut_ptr(), buffer.len() as u32) })?;
+        let Some(len) = len else {
+            return Ok(false);
+        };
+
+        if len <= buffer.len() {
+            buffer.truncate(len);
+            return Ok(true);
+        }
+
+        buffer.reserve(len - buffer.len());
+    }
+}
+
+/// Loads one of this module's own assets from the host and deserializes it.
+/// Returns `None` if the host does not provide the asset.
+pub fn guest_load<A: AssetSchema>(id: WassetId) -> Result<Option<A>, WassetError> {
+    let Some(codec) = check_result(id, unsafe { imports::asset_codec(id.as_bytes().as_ptr()) })? else {
+        return Ok(None);
+    };
+
+    let codec = *WassetCodec::ALL.get(codec).ok_or_else(|| WassetError::from_deserialize(format!("Host reported unknown codec {codec} for asset {id:?}")))?;
+    let Some(data) = guest_load_raw(id)? else {
+        return Ok(None);
+    };
+
+    codec.deserialize(&data).map(Some)
+}
+
+/// Converts the result of a host import into a size, or `None` if the asset was missing.
+fn check_result(id: WassetId, result: i64) -> Result<Option<usize>, WassetError> {
+    match result {
+        GUEST_ASSET_MISSING => Ok(None),
+        GUEST_ASSET_FAILED => Err(WassetError::from_deserialize(format!("Host failed to load asset {id:?}"))),
+        x => usize::try_from(x).map(Some).map_err(|_| WassetError::from_deserialize(format!("Host returned invalid result {x} for asset {id:?}")))
+    }
+}
+
+#[cfg(target_family = "wasm")]
+/// The functions which the host provides for reading the module's own assets.
+mod imports {
+    #[link(wasm_import_module = "wasset")]
+    unsafe extern "C" {
+        /// Gets the size of the serialized asset whose 16-byte ID is at `id`.
+        pub fn asset_len(id: *const u8) -> i64;
+
+        /// Gets the position of the asset's codec within [`crate::WassetCodec::ALL`].
+        pub fn asset_codec(id: *const u8) -> i64;
+
+        /// Copies up to `len` bytes of the serialized asset into `buffer`, returning its full size.
+        pub fn asset_read(id: *const u8, buffer: *mut u8, len: u32) -> i64;
+    }
+}
+
+#[cfg(not(target_family = "wasm"))]
+/// Stands in for the host functions outside of WASM, where no host provides assets.
+mod imports {
+    use crate::*;
+
+    /// Reports that the asset is missing.
+    pub unsafe fn asset_len(_: *const u8) -> i64 {
+        GUEST_ASSET_MISSING
+    }
+
+    /// Reports that the asset is missing.
+    pub unsafe fn asset_codec(_: *const u8) -> i64 {
+        GUEST_ASSET_MISSING
+    }
+
+    /// Reports that the asset is missing.
+    pub unsafe fn asset_read(_: *const u8, _: *mut u8, _: u32) -> i64 {
+        GUEST_ASSET_MISSING
+    }
+}
@@ -0,0 +1,44 @@
+use crate::*;
+
+impl<'a, A: AssetSchema> WassetParser<'a, A> {
+    /// Implements the `asset_len` function which guests built with the `guest` feature import from
+    /// the [`GUEST_IMPORT_MODULE`]. Gets the size of the serialized asset with the given ID, or
+    /// [`GUEST_ASSET_MISSING`] if it does not exist. For compressed assets whose size was not recorded,
+    /// this loads the asset, and returns [`GUEST_ASSET_FAILED`] if loading fails.
+    pub fn serve_asset_len(&self, id: WassetId) -> i64 {
+        match self.manifest().get(id).map(WassetManifestEntry::uncompressed_size) {
+            Some(Some(size)) => size as i64,
+            Some(None) => self.serve_asset_read(id, &mut []),
+            None => GUEST_ASSET_MISSING
+        }
+    }
+
+    /// Implements the `asset_codec` function which guests import from the [`GUEST_IMPORT_MODULE`].
+    /// Gets the position of the asset's codec within [`WassetCodec::ALL`], or [`GUEST_ASSET_MISSING`]
+    /// if it does not exist.
+    pub fn serve_asset_codec(&self, id: WassetId) -> i64 {
+        self.manifest().get(id)
+            .and_then(|entry| WassetCodec::ALL.iter().position(|x| *x == entry.codec()))
+            .map_or(GUEST_ASSET_MISSING, |x| x as i64)
+    }
+
+    /// Implements the `asset_read` function which guests import from the [`GUEST_IMPORT_MODULE`].
+    /// Copies as much of the serialized asset as fits into `buffer`, and returns its full size,
+    /// so that a guest whose buffer was too small may grow it and try again. Returns
+    /// [`GUEST_ASSET_MISSING`] if the asset does not exist, or [`GUEST_ASSET_FAILED`] if it could not be loaded.
+    pub fn serve_asset_read(&self, id: WassetId, buffer: &mut [u8]) -> i64 {
+        match self.load_raw(id) {
+            Ok(Some(data)) => {
+                let len = data.len().min(buffer.len());
+                buffer[..len].copy_from_slice(&data[..len]);
+                data.len() as i64
+            },
+            Ok(None) => GUEST_ASSET_MISSING,
+            Err(_error) => {
+                #[cfg(feature = "tracing")]
+                tracing::warn!(?id, error = %_error, "failed to serve asset to guest");
+                GUEST_ASSET_FAILED
+            }
+        }
+    }
+}
@@ -0,0 +1,354 @@
+use crate::*;
+#[cfg(feature = "parse")]
+use std::marker::*;
+
+/// A read-only lookup table over the assets of one manifest, which is stored alongside the
+/// manifest when encoding with `ManifestLayout::PerfectHash`. The table is built around a
+/// minimal perfect hash of the asset IDs, so every lookup inspects exactly one slot without
+/// probing. The table is read in place, which allows it to be used directly from a
+/// memory-mapped module without deserializing anything.
+#[derive(Copy, Clone, Debug)]
+pub struct WassetIndex<'a> {
+    /// The displacement chosen for each bucket of IDs.
+    displacements: &'a [u8],
+    /// The slots which hold each asset, in hash order.
+    slots: &'a [u8]
+}
+
+impl<'a> WassetIndex<'a> {
+    /// Interprets the given bytes as an index, failing if they are not a well-formed table.
+    pub fn new(bytes: &'a [u8]) -> Result<Self, WassetError> {
+        let header = bytes.get(..INDEX_HEADER_SIZE)
+            .filter(|x| x[..4] == INDEX_MAGIC)
+            .ok_or_else(|| WassetError::from_deserialize("Asset index has an invalid header"))?;
+        let slot_count = read_u32(header, 4) as usize;
+        let bucket_count = read_u32(header, 8) as usize;
+
+        let slot_start = bucket_count.checked_mul(4).and_then(|x| x.checked_add(INDEX_HEADER_SIZE));
+        let end = slot_start.and_then(|x| INDEX_SLOT_SIZE.checked_mul(slot_count)?.checked_add(x));
+        let Some(slot_start) = slot_start.filter(|_| end == Some(bytes.len()) && (bucket_count > 0 || slot_count == 0)) else {
+            return Err(WassetError::from_deserialize("Asset index has an invalid length"));
+        };
+
+        Ok(Self {
+            displacements: &bytes[INDEX_HEADER_SIZE..slot_start],
+            slots: &bytes[slot_start..]
+        })
+    }
+
+    /// Gets the number of assets in the index.
+    pub fn len(&self) -> usize {
+        self.slots.len() / INDEX_SLOT_SIZE
+    }
+
+    /// Whether the index holds no assets.
+    pub fn is_empty(&self) -> bool {
+        self.slots.is_empty()
+    }
+
+    /// Looks up the location of the asset with the given ID.
+    pub fn get(&self, id: WassetId) -> Option<WassetIndexEntry> {
+        if self.is_empty() {
+            return None;
+        }
+
+        let bucket = (index_hash(&id, 0) % (self.displacements.len() / 4) as u64) as usize;
+        let displacement = read_u32(self.displacements, 4 * bucket);
+        let slot = (index_hash(&id, u64::from(displacement) + 1) % self.len() as u64) as usize;
+        self.slot(slot).filter(|(x, _)| *x == id).map(|(_, entry)| entry)
+    }
+
+    /// Gets an iterator over all assets in the index, in no particular order.
+    pub fn iter(&self) -> impl '_ + Iterator<Item = (WassetId, WassetIndexEntry)> {
+        (0..self.len()).filter_map(|x| self.slot(x))
+    }
+
+    /// Reads the asset stored in the slot with the given index.
+    fn slot(&self, index: usize) -> Option<(WassetId, WassetIndexEntry)> {
+        let slot = &self.slots[INDEX_SLOT_SIZE * index..INDEX_SLOT_SIZE * (index + 1)];
+        let id = WassetId::from_bytes(slot[..16].try_into().expect("Slot had incorrect size"));
+        let compression = *WassetCompression::ALL.get(slot[24] as usize)?;
+        let codec = *WassetCodec::ALL.get(slot[26] as usize)?;
+        Some((id, WassetIndexEntry { start: read_u32(slot, 16), end: read_u32(slot, 20), compression, encrypted: slot[25] & ENCRYPTED_FLAG != 0, codec }))
+    }
+}
+
+/// Describes where an asset is stored, as recorded by a [`WassetIndex`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct WassetIndexEntry {
+    /// The offset of the first byte of the asset, relative to the start of its data section.
+    pub start: u32,
+    /// The offset one past the last byte of the asset, relative to the start of its data section.
+    pub end: u32,
+    /// The compression scheme applied to the stored bytes.
+    pub compression: WassetCompression,
+    /// Whether the stored bytes were encrypted after compression.
+    pub encrypted: bool,
+    /// The format in which the asset was serialized.
+    pub codec: WassetCodec
+}
+
+impl WassetIndexEntry {
+    /// The range of bytes that the asset occupies within its data section.
+    pub fn range(&self) -> Range<u32> {
+        self.start..self.end
+    }
+}
+
+/// Reads assets from a module through the perfect-hash indices stored alongside its manifests,
+/// rather than by deserializing the manifests. This makes opening a module and looking up assets
+/// nearly free, at the cost of the metadata which only the manifests hold, such as names and
+/// dependencies. Every manifest in the module must have an index.
+#[cfg(feature = "parse")]
+pub struct WassetIndexedModule<'a, A: AssetSchema> {
+    /// The index and data contents of each section pair.
+    sections: Vec<(WassetIndex<'a>, &'a [u8])>,
+    /// The options used to read assets.
+    options: WassetParseOptions,
+    /// A marker type for `A`.
+    marker: PhantomData<fn(A)>
+}
+
+#[cfg(feature = "parse")]
+impl<'a, A: AssetSchema> WassetIndexedModule<'a, A> {
+    /// Locates the asset indices within the given module.
+    pub fn parse(module: &'a [u8]) -> Result<Self, WassetError> {
+        Self::parse_with_options(module, WassetParseOptions::default())
+    }
+
+    /// Locates the asset indices within the given module, using the provided options.
+    pub fn parse_with_options(module: &'a [u8], options: WassetParseOptions) -> Result<Self, WassetError> {
+        let mut sections = Vec::new();
+        for (section_id, offsets) in read_asset_sections(module, &options.section_prefix)? {
+            let (_, data) = offsets.complete(section_id)?;
+            let index = offsets.index
+                .ok_or_else(|| WassetError::from_deserialize(format!("Asset manifest {section_id} has no matching index section")))?;
+
+            sections.push((WassetIndex::new(index)?, &module[data.offset as usize..(data.offset + data.len) as usize]));
+        }
+
+        Ok(Self { sections, options, marker: PhantomData })
+    }
+
+    /// Gets the options used to read assets.
+    pub fn options(&self) -> &WassetParseOptions {
+        &self.options
+    }
+
+    /// Whether the module provides an asset with the given ID.
+    pub fn contains(&self, id: WassetId) -> bool {
+        self.sections.iter().any(|(index, _)| index.get(id).is_some())
+    }
+
+    /// Gets an iterator over the IDs of all assets in the module.
+    pub fn ids(&self) -> impl '_ + Iterator<Item = WassetId> {
+        self.sections.iter().flat_map(|(index, _)| index.iter().map(|(id, _)| id))
+    }
+
+    /// Loads the raw data of an asset, decrypting and decompressing it if necessary,
+    /// or returns `None` if the module does not provide it.
+    pub fn load_raw(&self, id: WassetId) -> Result<Option<WassetItem<'a, A>>, WassetError> {
+        let Some((entry, data)) = self.sections.iter().find_map(|(index, data)| index.get(id).map(|x| (x, *data))) else {
+            return Ok(None);
+        };
+
+        let stored = data.get(entry.start as usize..entry.end as usize)
+            .ok_or_else(|| WassetError::from_deserialize(format!("Asset {id:?} lies outside of its data section")))?;
+        let size = stored.len() as u64;
+        if size > self.options.max_asset_size {
+            return Err(WassetError::LimitExceeded { size, limit: self.options.max_asset_size });
+        }
+
+        let item = if entry.compression.is_none() && !entry.encrypted {
+            WassetItem::from(stored)
+        }
+        else {
+            let stored = decrypt_stored(entry.encrypted, stored, &self.options)?;
+            WassetItem::from(entry.compression.decompress(&stored, self.options.max_asset_size)?)
+        };
+
+        Ok(Some(item.with_codec(entry.codec)))
+    }
+
+    /// Loads and deserializes an asset, or returns `None` if the module does not provide it.
+    pub fn load(&self, id: WassetId) -> Result<Option<A>, WassetError> {
+        self.load_raw(id)?.map(|x| x.deserialize()).transpose()
+    }
+}
+
+#[cfg(feature = "parse")]
+impl<'a, A: AssetSchema> WassetSource<A> for WassetIndexedModule<'a, A> {
+    fn ids(&self) -> Box<dyn '_ + Iterator<Item = WassetId>> {
+        Box::new(WassetIndexedModule::ids(self))
+    }
+
+    fn contains(&self, id: WassetId) -> bool {
+        WassetIndexedModule::contains(self, id)
+    }
+
+    fn load(&self, id: WassetId) -> Result<Option<A>, WassetError> {
+        WassetIndexedModule::load(self, id)
+    }
+}
+
+/// Builds a perfect-hash index over the entries of a manifest, whose ranges must
+/// be relative to the start of their data section.
+pub(crate) fn build_index(manifest: &WassetManifest) -> Vec<u8> {
+    let slot_count = manifest.assets.len();
+    let bucket_count = slot_count.div_ceil(INDEX_BUCKET_SIZE).max(1);
+
+    let mut buckets = vec![Vec::new(); bucket_count];
+    for (id, entry) in &manifest.assets {
+        buckets[(index_hash(id, 0) % bucket_count as u64) as usize].push((*id, entry));
+    }
+
+    // Placing the largest buckets first, while most slots are free, keeps the displacement search short.
+    let mut order = (0..bucket_count).collect::<Vec<_>>();
+    order.sort_by_key(|x| std::cmp::Reverse(buckets[*x].len()));
+
+    let mut displacements = vec![0u32; bucket_count];
+    let mut slots = vec![None; slot_count];
+    for bucket in order.into_iter().filter(|x| !buckets[*x].is_empty()) {
+        let mut positions = Vec::with_capacity(buckets[bucket].len());
+        let displacement = (0u32..).find(|displacement| {
+            positions.clear();
+            buckets[bucket].iter().all(|(id, _)| {
+                let slot = (index_hash(id, u64::from(*displacement) + 1) % slot_count as u64) as usize;
+                let free = slots[slot].is_none() && !positions.contains(&slot);
+                positions.push(slot);
+                free
+            })
+        }).expect("Failed to find displacement for asset index");
+
+        displacements[bucket] = displacement;
+        for (&slot, asset) in positions.iter().zip(&buckets[bucket]) {
+            slots[slot] = Some(*asset);
+        }
+    }
+
+    let mut result = Vec::with_capacity(INDEX_HEADER_SIZE + 4 * bucket_count + INDEX_SLOT_SIZE * slot_count);
+    result.extend_from_slice(&INDEX_MAGIC);
+    result.extend_from_slice(&(slot_count as u32).to_le_bytes());
+    result.extend_from_slice(&(bucket_count as u32).to_le_bytes());
+    result.extend(displacements.iter().flat_map(|x| x.to_le_bytes()));
+    for (id, entry) in slots.into_iter().map(|x| x.expect("Asset index slot was not filled")) {
+        let compression = WassetCompression::ALL.iter().position(|x| *x == entry.compression()).unwrap_or_default() as u8;
+        result.extend_from_slice(id.as_bytes());
+        result.extend_from_slice(&entry.start.to_le_bytes());
+        result.extend_from_slice(&entry.end.to_le_bytes());
+        let flags = if entry.is_encrypted() { ENCRYPTED_FLAG } else { 0 };
+        let codec = WassetCodec::ALL.iter().position(|x| *x == entry.codec()).unwrap_or_default() as u8;
+        result.extend_from_slice(&[compression, flags, codec, 0]);
+    }
+
+    result
+}
+
+/// The bytes which begin every serialized index.
+const INDEX_MAGIC: [u8; 4] = *b"WSIX";
+
+/// The size of the index header, which holds the magic bytes, slot count, and bucket count.
+const INDEX_HEADER_SIZE: usize = 12;
+
+/// The size of each index slot, which holds an ID, a range, a compression scheme, flags, and a codec.
+const INDEX_SLOT_SIZE: usize = 28;
+
+/// The bit of a slot's flags which is set for encrypted assets.
+const ENCRYPTED_FLAG: u8 = 1;
+
+/// The average number of IDs in each bucket of the perfect hash.
+const INDEX_BUCKET_SIZE: usize = 2;
+
+/// Hashes an asset ID with the given seed. IDs are already random, so this only
+/// needs to mix their bits well.
+fn index_hash(id: &WassetId, seed: u64) -> u64 {
+    let bytes = id.as_bytes();
+    let low = u64::from_le_bytes(bytes[..8].try_into().expect("ID had incorrect size"));
+    let high = u64::from_le_bytes(bytes[8..].try_into().expect("ID had incorrect size"));
+    mix_bits(low ^ mix_bits(high ^ seed))
+}
+
+/// Applies the finalizer of the SplitMix64 generator, which spreads every input bit across the output.
+pub(crate) fn mix_bits(mut x: u64) -> u64 {
+    x = (x ^ (x >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94d049bb133111eb);
+    x ^ (x >> 31)
+}
+
+/// Reads a little-endian integer at the given offset.
+fn read_u32(bytes: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes(bytes[offset..offset + 4].try_into().expect("Slice had incorrect size"))
+}
+
+#[cfg(all(test, feature = "testing"))]
+mod tests {
+    use super::*;
+
+    /// Gets reproducible IDs which are spread across the ID space.
+    fn test_ids(count: u32) -> Vec<WassetId> {
+        (0..count).map(|x| WassetId::from_bytes(u128::from(x + 1).wrapping_mul(0x9e3779b97f4a7c15f39cc0605cedc835).to_le_bytes())).collect()
+    }
+
+    /// Encodes the given number of assets with an index.
+    fn encode_indexed(count: u32) -> (Vec<WassetId>, EncodedAssets) {
+        let ids = test_ids(count);
+        let options = WassetEncodeOptions { manifest_layout: ManifestLayout::PerfectHash, ..Default::default() };
+        let assets = encode_assets(ids.iter().copied().zip(0u32..), &options).unwrap();
+        (ids, assets)
+    }
+
+    #[test]
+    fn index_finds_every_asset() {
+        for count in [0, 1, 2, 3, 50, 1000] {
+            let (ids, assets) = encode_indexed(count);
+            let manifest = WassetManifest::from_bytes(&assets.manifest).unwrap();
+            let index = WassetIndex::new(&assets.index).unwrap();
+
+            assert_eq!(index.len(), count as usize);
+            assert_eq!(index.iter().count(), count as usize);
+            for id in &ids {
+                let entry = manifest.get(*id).unwrap();
+                let indexed = index.get(*id).unwrap();
+                assert_eq!(indexed.range(), entry.range());
+                assert_eq!(indexed.compression, entry.compression());
+                assert_eq!(indexed.codec, entry.codec());
+            }
+
+            assert!(test_ids(count + 10)[count as usize..].iter().all(|id| index.get(*id).is_none()));
+        }
+    }
+
+    #[test]
+    fn indexed_module_loads_assets() {
+        let (ids, assets) = encode_indexed(20);
+        let module = WassetModuleBuilder::new().add_assets(assets).build();
+        let indexed = WassetIndexedModule::<u32>::parse(&module).unwrap();
+
+        for (id, value) in ids.iter().zip(0u32..) {
+            assert_eq!(indexed.load(*id).unwrap(), Some(value));
+        }
+
+        assert_eq!(indexed.load(test_ids(21)[20]).unwrap(), None);
+    }
+
+    #[test]
+    fn malformed_index_is_rejected() {
+        let (_, assets) = encode_indexed(20);
+        let index = assets.index;
+        let with_header = |offset: usize, value: u32| {
+            let mut result = index.clone();
+            result[offset..offset + 4].copy_from_slice(&value.to_le_bytes());
+            result
+        };
+
+        assert!(WassetIndex::new(&index[..INDEX_HEADER_SIZE - 1]).is_err());
+        assert!(WassetIndex::new(&index[..index.len() - 1]).is_err());
+        assert!(WassetIndex::new(&[index.as_slice(), &[0]].concat()).is_err());
+        assert!(WassetIndex::new(&with_header(0, 0)).is_err());
+        assert!(WassetIndex::new(&with_header(4, u32::MAX)).is_err());
+        assert!(WassetIndex::new(&with_header(8, u32::MAX)).is_err());
+
+        let without_buckets = [&INDEX_MAGIC[..], &1u32.to_le_bytes(), &0u32.to_le_bytes(), &[0; INDEX_SLOT_SIZE]].concat();
+        assert!(WassetIndex::new(&without_buckets).is_err());
+    }
+}
@@ -5,7 +5,7 @@
 //! `wasset` is meant to be a foundation for game asset systems. It differs from using `include_bytes!()` to include assets in the following ways:
 //! 
 //! - The host can read a WASM module's assets without loading the WASM itself. This allows a game engine to preload or lazily load all assets, before instantiating the WASM modules.
-//! - Separate WASM modules can reference and share assets by `WassetId`.
+//! - Separate WASM modules can reference and share assets by `WassetId`. A `WassetRegistry`, or a `WassetStore` for modules keyed by path, combines the parsers of many modules and resolves each ID to the module that provides it.
 //! - Because assets are stored in a custom section, it's not necessary to load all assets into memory when instantiating the WASM module. This can conserve memory for WASM modules that include many assets.
 //! 
 //! ---
@@ -18,6 +18,12 @@
 //! - Implementing `AssetEncoder` to determine how files are turned into assets
 //! - Re-exporting the `wasset::include_assets::<A: AssetEncoder>(path)` macro with the appropriate asset encoder type
 //! 
+//! For projects that only need to embed and read files, the `wasset_raw_macro` crate provides a ready-made `include_raw_assets!(path)` macro, which stores each file as a `RawAsset` along with its MIME type.
+//! 
+//! To refer to assets that another crate already embeds, such as a shared content plugin, `wasset::reference_assets` generates the same `WassetId` constants from that crate's built `.wasm` module without embedding the data again. `wasset_raw_macro` re-exports it as `reference_raw_assets!(path)`.
+//! 
+//! The `wasset_cli` crate provides a `wasset` command for inspecting and editing the assets of compiled modules, with `list`, `manifest`, `extract`, `strip`, `inject`, and `validate` subcommands.
+//! 
 //! [A complete example is available here.](/wasset_example/) Once the asset type and macro have been defined, they may be used from within WASM as follows:
 //! 
 //! ```ignore
@@ -30,15 +36,17 @@
 //! 
 //! /// Gets a list of all assets from this module.
 //! pub fn list_all_assets() -> &'static [WassetId] {
-//!     &[
-//!         // The macro has defined these for us.
-//!         assets::SOME_BINARY,
-//!         assets::SOME_TEXT,
-//!         assets::submodule::MORE_TEXT
-//!     ]
+//!     // The macro has defined this for us.
+//!     assets::ALL_ASSETS
 //! }
 //! ```
 //! 
+//! Alongside each ID, the macro also defines a constant holding the size of the serialized asset in bytes, such as `assets::SOME_TEXT_LEN`, so that guests can preallocate buffers for asset data.
+//! 
+//! Every generated module also holds an `ALL` slice with the IDs of the assets within it and its submodules, such as `assets::submodule::ALL`, and the top-level module holds `ALL_ASSETS`, which lists every embedded asset.
+//! 
+//! Assets may be compressed as they are embedded by passing `WassetEncodeOptions` with a `compression` mode to `wasset::include_assets_with_options`. LZ4 and Zstandard are available behind the **lz4** and **zstd** features. Compression is transparent to the host: `WassetParser::load` and `WassetParser::load_raw` always return the decompressed asset data.
+//! 
 //! Then, the asset data for this WASM plugin may be examined from the host:
 //! 
 //! ```ignore
@@ -61,11 +69,32 @@
 //! 
 //! ## Optional features
 //! 
+//! - **audit** - allows for recording which modules request which assets from a registry.
+//! - **bevy** - provides a Bevy `AssetReader`, so that `wasset://` paths resolve to the assets of a module.
+//! - **bincode** - enables the `bincode` codec for serializing assets.
 //! - **bytemuck** - implements the `Pod` and `Zeroable` attributes on relevant types.
 //! - **encode** - allows for serializing a folder of assets into memory.
 //! - **encode_macro** - exposes a generic macro that, when instantiated, will embed a folder of assets into a WASM module.
+//! - **encoders** - provides ready-made `AssetEncoder`s for text, binary, JSON, TOML, and image files.
+//! - **encryption** - allows for encrypting asset payloads with a symmetric key during encoding, and decrypting them when loading.
+//! - **guest** - allows a WASM module to read its own embedded assets at runtime, through functions imported from a host which implements them with `WassetParser`.
+//! - **json** - enables the JSON codec for serializing assets, and allows for exporting and importing manifests as JSON.
+//! - **lz4** - enables LZ4 compression of stored assets.
+//! - **manifest_cache** - allows for caching parsed manifests on disk, so that repeated parsing of the same module is faster.
+//! - **parallel** - reads and encodes asset files on a pool of threads.
 //! - **parse** - exposes the ability to read a WASM module's assets.
+//! - **postcard** - enables the `postcard` codec for serializing assets.
 //! - **relative_path** - (requires nightly) makes the `encode_macro` use relative paths rather than paths from the project root.
+//! - **rmpv** - allows for loading assets as untyped MessagePack values, for inspection and debugging.
+//! - **signing** - allows for signing asset sections with an ed25519 key during encoding, and verifying them when parsing.
+//! - **tar** - allows for encoding assets directly from `.tar` archives.
+//! - **testing** - exposes utilities for building asset-bearing WASM modules in memory, for use in tests.
+//! - **tracing** - instruments parsing, loading, encoding, and stripping with `tracing` spans and events.
+//! - **verify** - allows for checking loaded assets against the content hashes recorded during encoding.
+//! - **wasmtime** - allows for defining the functions through which guests read their own assets on a `wasmtime::Linker`.
+//! - **watch** - allows for watching an asset folder and re-encoding it whenever its files change.
+//! - **zip** - allows for encoding assets directly from `.zip` archives.
+//! - **zstd** - enables Zstandard compression of stored assets.
 
 #![deny(warnings)]
 #![warn(clippy::missing_docs_in_private_items)]
@@ -73,11 +102,59 @@
 #![cfg_attr(all(unstable, feature = "encode_macro"), feature(track_path))]
 #![cfg_attr(feature = "relative_path", feature(proc_macro_span))]
 
+#[cfg(feature = "audit")]
+pub use crate::audit::*;
+#[cfg(feature = "bevy")]
+pub use crate::bevy::*;
+#[cfg(feature = "manifest_cache")]
+pub use crate::cache::*;
+pub use crate::codec::*;
+#[cfg(feature = "parse")]
+pub use crate::collector::*;
+#[cfg(feature = "parse")]
+pub use crate::collisions::*;
+#[cfg(all(feature = "encode", feature = "parse"))]
+pub use crate::combine::*;
+pub use crate::compression::*;
 #[cfg(feature = "encode")]
 pub use crate::encode::*;
 
+#[cfg(feature = "parse")]
+pub use crate::edit::*;
+#[cfg(feature = "encryption")]
+pub use crate::encryption::*;
+#[cfg(feature = "parse")]
+pub use crate::extract::*;
+#[cfg(feature = "parse")]
+pub use crate::fetch::*;
+#[cfg(feature = "guest")]
+pub use crate::guest::*;
+use crate::graph::*;
+#[cfg(any(feature = "encode", feature = "parse"))]
+pub use crate::index::*;
+#[cfg(feature = "wasmtime")]
+pub use crate::linker::*;
+#[cfg(any(feature = "encode", feature = "parse"))]
+pub use crate::manifest_format::*;
+#[cfg(feature = "parse")]
+pub use crate::owned::*;
+#[cfg(any(feature = "encode", feature = "parse"))]
+pub use crate::pack::*;
 #[cfg(feature = "parse")]
 pub use crate::parse::*;
+pub use crate::raw::*;
+#[cfg(feature = "parse")]
+pub use crate::reader::*;
+#[cfg(feature = "parse")]
+pub use crate::registry::*;
+#[cfg(feature = "parse")]
+pub use crate::split::*;
+#[cfg(feature = "parse")]
+pub use crate::stats::*;
+#[cfg(feature = "parse")]
+pub use crate::untyped::*;
+#[cfg(feature = "parse")]
+pub use crate::validate::*;
 
 #[cfg(feature = "bytemuck")]
 use bytemuck::*;
@@ -87,19 +164,222 @@ use std::borrow::*;
 use std::ops::*;
 use uuid::*;
 
+#[cfg(feature = "audit")]
+/// Records which modules request which assets from a registry.
+mod audit;
+
+#[cfg(feature = "bevy")]
+/// Serves assets to Bevy's asset server.
+mod bevy;
+
+#[cfg(feature = "manifest_cache")]
+/// Implements an on-disk cache of parsed manifests.
+mod cache;
+
+/// Implements the formats in which assets are serialized.
+mod codec;
+
+#[cfg(feature = "parse")]
+/// Gathers asset sections from payloads produced by an existing module parser.
+mod collector;
+
+#[cfg(feature = "parse")]
+/// Finds asset IDs which collide between modules.
+mod collisions;
+
+#[cfg(all(feature = "encode", feature = "parse"))]
+/// Implements combining the assets of several modules into one.
+mod combine;
+
+/// Implements compression schemes for stored assets.
+mod compression;
+
+#[cfg(feature = "parse")]
+/// Implements removal of assets from existing modules, compaction of their data, and repair of incomplete sections.
+mod edit;
+
 #[cfg(feature = "encode")]
 /// Implements the ability to write assets from a directory.
 mod encode;
 
+#[cfg(feature = "encryption")]
+/// Implements symmetric encryption of asset payloads.
+mod encryption;
+
+#[cfg(feature = "parse")]
+/// Allows for writing assets back out to files.
+mod extract;
+
+/// Orders assets by their dependencies.
+mod graph;
+
+#[cfg(feature = "parse")]
+/// Plans the byte ranges needed to download assets from remotely-hosted modules.
+mod fetch;
+
+#[cfg(feature = "guest")]
+/// Allows a module to read its own assets through functions imported from the host.
+mod guest;
+
+#[cfg(feature = "parse")]
+/// Implements the host side of the functions through which guests read their own assets.
+mod host;
+
+#[cfg(any(feature = "encode", feature = "parse"))]
+/// Implements perfect-hash indices for looking up assets without deserializing manifests.
+mod index;
+
+#[cfg(feature = "wasmtime")]
+/// Serves assets to guests which run on `wasmtime`.
+mod linker;
+
+#[cfg(any(feature = "encode", feature = "parse"))]
+/// Serializes manifests in either MessagePack or a compact columnar encoding.
+mod manifest_format;
+
+#[cfg(all(feature = "encode", feature = "parse"))]
+/// Implements the ability to re-encode the assets of an existing WASM module.
+mod migrate;
+
+#[cfg(feature = "parse")]
+/// Implements a parser which owns its module.
+mod owned;
+
+#[cfg(any(feature = "encode", feature = "parse"))]
+/// Implements standalone asset packs, which hold assets without a WASM module.
+mod pack;
+
 #[cfg(feature = "parse")]
 /// Implements the ability to read assets from a WASM module.
 mod parse;
 
+/// Defines a general-purpose asset schema for raw files.
+mod raw;
+
+#[cfg(feature = "parse")]
+/// Implements the ability to read assets from a module on disk without loading it into memory.
+mod reader;
+
+#[cfg(feature = "parse")]
+/// Combines the assets of many modules into one collection.
+mod registry;
+
+#[cfg(all(feature = "signing", any(feature = "encode", feature = "parse")))]
+/// Signs sets of asset sections and verifies their signatures.
+mod signing;
+
+#[cfg(feature = "parse")]
+/// Implements the separation of modules from their assets.
+mod split;
+
+#[cfg(feature = "parse")]
+/// Summarizes the assets of modules for dashboards and size budgets.
+mod stats;
+
+#[cfg(feature = "testing")]
+/// Provides utilities for testing code which reads assets.
+pub mod testing;
+
+#[cfg(feature = "parse")]
+/// Implements a parser which reads assets without knowledge of their schema.
+mod untyped;
+
+#[cfg(feature = "parse")]
+/// Checks modules for wasted space and broken references among their assets.
+mod validate;
+
+/// The largest alignment that may be requested for asset payloads. Data sections in modules
+/// built by this crate always begin at a multiple of this alignment.
+pub const MAX_PAYLOAD_ALIGNMENT: u32 = 16;
+
+/// The default prefix for the names of custom sections which hold assets.
+pub const DEFAULT_SECTION_PREFIX: &str = "__wasset";
+
+#[cfg(any(feature = "encode", feature = "parse"))]
+/// The text which follows the section prefix in the names of serialized manifests.
+pub(crate) const MANIFEST_SECTION_SUFFIX: &str = "_manifest:";
+#[cfg(any(feature = "encode", feature = "parse"))]
+/// The text which follows the section prefix in the names of serialized asset data.
+pub(crate) const DATA_SECTION_SUFFIX: &str = "_data:";
+#[cfg(any(feature = "encode", feature = "parse"))]
+/// The text which follows the section prefix in the names of perfect-hash manifest indices.
+pub(crate) const INDEX_SECTION_SUFFIX: &str = "_index:";
+#[cfg(any(feature = "encode", feature = "parse"))]
+/// The text which follows the section prefix in the names of asset previews.
+pub(crate) const PREVIEW_SECTION_SUFFIX: &str = "_preview:";
+#[cfg(any(feature = "encode", feature = "parse"))]
+/// The text which follows the section prefix in the names of asset section signatures.
+pub(crate) const SIGNATURE_SECTION_SUFFIX: &str = "_signature:";
+
+/// Gets an unnamed custom section which, when written at `position`, causes the contents of the
+/// given section to begin at a multiple of [`MAX_PAYLOAD_ALIGNMENT`]. Returns `None` if the
+/// contents would already be aligned.
+#[cfg(any(feature = "encode", feature = "parse"))]
+pub(crate) fn alignment_padding(position: usize, section: &wasm_encoder::CustomSection) -> Option<wasm_encoder::CustomSection<'static>> {
+    use wasm_encoder::Section;
+
+    let mut encoded = Vec::new();
+    section.append_to(&mut encoded);
+
+    let alignment = MAX_PAYLOAD_ALIGNMENT as usize;
+    let misalignment = (position + encoded.len() - section.data.len()) % alignment;
+    if misalignment == 0 {
+        return None;
+    }
+
+    // The smallest custom section occupies three bytes: its ID, its size, and an empty name.
+    let padding = (alignment - misalignment) + if alignment - misalignment < 3 { alignment } else { 0 };
+    Some(wasm_encoder::CustomSection {
+        name: "".into(),
+        data: vec![0; padding - 3].into()
+    })
+}
+
+/// Whether the given custom section is one which [`alignment_padding`] produced.
+#[cfg(feature = "parse")]
+pub(crate) fn is_alignment_padding(section: &wasmparser::CustomSectionReader) -> bool {
+    section.name().is_empty() && section.data().iter().all(|&byte| byte == 0)
+}
+
+/// Gets the number of zero bytes to write before a payload at offset `position` of a new data section,
+/// so that it keeps the offset modulo `alignment` which it had at `start` of its original data section.
+#[cfg(any(feature = "encode", feature = "parse"))]
+pub(crate) fn payload_padding(position: usize, start: u32, alignment: u32) -> usize {
+    let alignment = alignment as usize;
+    (alignment + start as usize % alignment - position % alignment) % alignment
+}
+
+/// The name of the module from which guests import the functions that read their own assets.
+/// Hosts implement `asset_len`, `asset_codec`, and `asset_read` within this module by calling
+/// [`WassetParser::serve_asset_len`], [`WassetParser::serve_asset_codec`], and [`WassetParser::serve_asset_read`].
+/// Each function receives a pointer to the 16 bytes of a [`WassetId`] in guest memory as its first argument.
+pub const GUEST_IMPORT_MODULE: &str = "wasset";
+
+/// The value returned by the guest import functions when the requested asset does not exist.
+pub const GUEST_ASSET_MISSING: i64 = -1;
+
+/// The value returned by the guest import functions when the requested asset could not be loaded.
+pub const GUEST_ASSET_FAILED: i64 = -2;
+
 /// Represents an asset type which may be stored and loaded from WASM.
 pub trait AssetSchema: 'static + Send + Sync + Serialize + for<'de> Deserialize<'de> {}
 
 impl<T: 'static + Send + Sync + Serialize + for<'de> Deserialize<'de>> AssetSchema for T {}
 
+/// Represents a collection of assets which may be enumerated and loaded by ID. This allows
+/// for writing code that works with a [`WassetParser`], or with an in-memory substitute in tests.
+pub trait WassetSource<A: AssetSchema> {
+    /// Gets an iterator over the IDs of all assets in the source.
+    fn ids(&self) -> Box<dyn '_ + Iterator<Item = WassetId>>;
+
+    /// Determines whether the source holds an asset with the given ID.
+    fn contains(&self, id: WassetId) -> bool;
+
+    /// Loads the provided asset from the source, returning `None` if it
+    /// did not exist.
+    fn load(&self, id: WassetId) -> Result<Option<A>, WassetError>;
+}
+
 /// A unique ID associated with an asset.
 #[derive(Copy, Clone, Debug, Serialize, Deserialize, Hash, PartialEq, Eq, PartialOrd, Ord)]
 #[repr(transparent)]
@@ -141,17 +421,528 @@ unsafe impl Pod for WassetId {}
 #[cfg(feature = "bytemuck")]
 unsafe impl Zeroable for WassetId {}
 
+/// A hash of an asset's serialized contents. Assets with equal hashes are byte-identical.
+/// Hashes are serialized as hexadecimal strings in human-readable formats such as JSON.
+#[derive(Copy, Clone, Hash, PartialEq, Eq, PartialOrd, Ord)]
+#[repr(transparent)]
+pub struct WassetHash([u8; 32]);
+
+impl WassetHash {
+    /// Creates a new hash from the given group of bytes.
+    pub const fn from_bytes(bytes: [u8; 32]) -> Self {
+        Self(bytes)
+    }
+
+    /// Gets a representation of this hash as bytes.
+    pub const fn as_bytes(&self) -> &[u8; 32] {
+        &self.0
+    }
+}
+
+impl std::fmt::Debug for WassetHash {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("WassetHash").field(&format_args!("{self}")).finish()
+    }
+}
+
+impl std::fmt::Display for WassetHash {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.iter().try_for_each(|x| write!(f, "{x:02x}"))
+    }
+}
+
+impl std::str::FromStr for WassetHash {
+    type Err = WassetError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut result = [0; 32];
+        if s.len() != 2 * result.len() || !s.is_ascii() {
+            return Err(WassetError::from_deserialize(format!("Invalid asset hash {s:?}")));
+        }
+
+        for (byte, digits) in result.iter_mut().zip(s.as_bytes().chunks_exact(2)) {
+            *byte = std::str::from_utf8(digits).ok().and_then(|x| u8::from_str_radix(x, 16).ok())
+                .ok_or_else(|| WassetError::from_deserialize(format!("Invalid asset hash {s:?}")))?;
+        }
+
+        Ok(Self(result))
+    }
+}
+
+impl Serialize for WassetHash {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            serializer.collect_str(self)
+        }
+        else {
+            serializer.serialize_newtype_struct("WassetHash", &self.0)
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for WassetHash {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        /// The binary representation of a hash.
+        #[derive(Deserialize)]
+        #[serde(rename = "WassetHash")]
+        struct RawHash([u8; 32]);
+
+        if deserializer.is_human_readable() {
+            String::deserialize(deserializer)?.parse().map_err(::serde::de::Error::custom)
+        }
+        else {
+            RawHash::deserialize(deserializer).map(|x| Self(x.0))
+        }
+    }
+}
+
+#[cfg(feature = "bytemuck")]
+unsafe impl Pod for WassetHash {}
+
+#[cfg(feature = "bytemuck")]
+unsafe impl Zeroable for WassetHash {}
+
 /// A list which describes the list of assets present in a WASM module.
-#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct WassetManifest {
-    /// A mapping from asset IDs to offsets within a custom data section.
-    asset_ranges: FxHashMap<WassetId, Range<u32>>
+    /// The revision of the format in which the manifest was read. Manifests which predate
+    /// versioning read as version zero, and manifests are always written in the current version.
+    #[serde(default, rename = "format_version", serialize_with = "serialize_format_version")]
+    version: u32,
+    /// A mapping from asset IDs to their entries.
+    #[serde(alias = "asset_ranges")]
+    assets: FxHashMap<WassetId, WassetManifestEntry>,
+    /// The name of the top-level subfolder from which the assets were encoded,
+    /// if they were split into their own sections by subfolder.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    partition: Option<String>,
+    /// The fingerprint of the asset schema with which the assets were encoded, if one was given.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    schema: Option<String>,
+    /// Whether the data section which this manifest describes was removed from the module,
+    /// such that the assets must be read from elsewhere.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    external_data: bool
 }
 
 impl WassetManifest {
+    /// The revision of the manifest format which this crate writes, and the newest that it reads.
+    /// The version covers both the layout of manifests and that of the data sections which they
+    /// describe, and increases whenever either changes in a way that older parsers cannot read.
+    pub const FORMAT_VERSION: u32 = 1;
+
+    /// Gets the revision of the format in which this manifest was read. This is zero for manifests
+    /// written before formats were versioned, and [`Self::FORMAT_VERSION`] for new manifests.
+    pub fn format_version(&self) -> u32 {
+        self.version
+    }
+
     /// Gets an iterator over the IDs of all assets stored in the module.
     pub fn ids(&self) -> impl '_ + Iterator<Item = WassetId> {
-        self.asset_ranges.keys().copied()
+        self.assets.keys().copied()
+    }
+
+    /// Gets the entry associated with the given asset, if any.
+    pub fn get(&self, id: WassetId) -> Option<&WassetManifestEntry> {
+        self.assets.get(&id)
+    }
+
+    /// Gets the entries of the given assets, sorted by the offset of their data. IDs which
+    /// are not in the manifest are skipped, and repeated IDs appear once.
+    #[cfg(feature = "parse")]
+    pub(crate) fn entries_by_offset(&self, ids: &[WassetId]) -> Vec<(WassetId, &WassetManifestEntry)> {
+        let mut entries = ids.iter().filter_map(|id| self.get(*id).map(|x| (*id, x))).collect::<Vec<_>>();
+        entries.sort_by_key(|(id, entry)| (entry.start, *id));
+        entries.dedup_by_key(|(id, _)| *id);
+        entries
+    }
+
+    /// Gets an iterator over the IDs and entries of all assets stored in the module.
+    pub fn iter(&self) -> impl '_ + Iterator<Item = (WassetId, &WassetManifestEntry)> {
+        self.assets.iter().map(|(id, entry)| (*id, entry))
+    }
+
+    /// Finds the ID of the asset with the given name, if any. The name may be given in the same
+    /// form as [`WassetManifestEntry::name`], such as `assets/textures/player`, or relative to
+    /// the root asset folder, such as `textures/player`.
+    pub fn lookup(&self, name: &str) -> Option<WassetId> {
+        self.iter().find(|(_, entry)| entry.has_name(name)).map(|(id, _)| id)
+    }
+
+    /// Gets the IDs of all assets which were declared with the given tag, sorted by ID.
+    pub fn ids_with_tag(&self, tag: &str) -> Vec<WassetId> {
+        let mut result = self.iter().filter(|(_, entry)| entry.has_tag(tag)).map(|(id, _)| id).collect::<Vec<_>>();
+        result.sort();
+        result
+    }
+
+    /// Finds the asset which holds the variant of the given asset for a locale, such as `de-AT`.
+    /// If the asset has no variant for the locale, its parent locales are tried in turn, such as
+    /// `de`, before falling back to the asset itself. Returns `None` if the asset does not exist.
+    pub fn localized_id(&self, id: WassetId, locale: &str) -> Option<WassetId> {
+        let entry = self.get(id)?;
+        let mut locale = locale;
+        loop {
+            if let Some(variant) = entry.locales.get(locale).filter(|x| self.assets.contains_key(*x)) {
+                return Some(*variant);
+            }
+
+            match locale.rfind(['-', '_']) {
+                Some(end) => locale = &locale[..end],
+                None => return Some(id)
+            }
+        }
+    }
+
+    /// Determines the order in which the given assets and all of their transitive dependencies
+    /// should be loaded, such that every asset comes after the assets upon which it depends.
+    /// Requested assets which are not in the manifest are omitted. Fails with
+    /// [`WassetError::MissingDependency`] or [`WassetError::DependencyCycle`] if the
+    /// dependencies cannot be satisfied within this manifest.
+    pub fn load_order(&self, ids: impl IntoIterator<Item = WassetId>) -> Result<Vec<WassetId>, WassetError> {
+        dependency_order(ids, |id| self.get(id).map(WassetManifestEntry::dependencies))
+    }
+
+    /// Gets the IDs of all assets in the manifest, ordered so that every asset comes after
+    /// the assets upon which it depends. Assets which are unrelated are ordered by ID.
+    pub fn ids_by_dependency(&self) -> Result<Vec<WassetId>, WassetError> {
+        let mut ids = self.ids().collect::<Vec<_>>();
+        ids.sort();
+        self.load_order(ids)
+    }
+
+    /// Gets the name of the top-level subfolder from which these assets were encoded, if
+    /// they were split by subfolder. Manifests which are merged from several sections,
+    /// such as that of a [`WassetParser`], have no partition.
+    pub fn partition(&self) -> Option<&str> {
+        self.partition.as_deref()
+    }
+
+    /// Gets the fingerprint of the asset schema with which these assets were encoded, as given
+    /// by `WassetEncodeOptions::schema`.
+    /// Manifests which are merged from several sections have a schema only if every section agrees.
+    pub fn schema(&self) -> Option<&str> {
+        self.schema.as_deref()
+    }
+
+    /// Whether the data of these assets was removed from the module, as by `WassetParser::strip_data`,
+    /// and must be read from elsewhere. Manifests which are merged from several sections have external
+    /// data if any section does.
+    pub fn has_external_data(&self) -> bool {
+        self.external_data
+    }
+}
+
+impl Default for WassetManifest {
+    fn default() -> Self {
+        Self {
+            version: Self::FORMAT_VERSION,
+            assets: FxHashMap::default(),
+            partition: None,
+            schema: None,
+            external_data: false
+        }
+    }
+}
+
+/// Serializes the format version of a manifest, which is always the current version.
+fn serialize_format_version<S: Serializer>(_: &u32, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_u32(WassetManifest::FORMAT_VERSION)
+}
+
+/// Describes where a single asset is stored, along with information about its contents.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct WassetManifestEntry {
+    /// The offset at which the asset data begins.
+    start: u32,
+    /// The offset at which the asset data ends.
+    end: u32,
+    /// The hash of the serialized asset, if it was recorded.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    hash: Option<WassetHash>,
+    /// The compression scheme applied to the serialized asset.
+    #[serde(default, skip_serializing_if = "WassetCompression::is_none")]
+    compression: WassetCompression,
+    /// Whether the stored bytes were encrypted after compression.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    encrypted: bool,
+    /// The format in which the asset was serialized.
+    #[serde(default, skip_serializing_if = "WassetCodec::is_message_pack")]
+    codec: WassetCodec,
+    /// The size of the serialized asset before compression, if it was recorded.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    uncompressed_size: Option<u32>,
+    /// The path of the asset, beginning with the root asset folder, if it was encoded from a file.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    name: Option<String>,
+    /// The other assets upon which this asset depends.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    dependencies: Vec<WassetId>,
+    /// The revision of the asset, if one was recorded.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    version: Option<u64>,
+    /// The kind of the asset, as reported by its encoder.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    kind: Option<String>,
+    /// The range of the asset's preview, relative to the start of the preview section
+    /// contents, if the encoder produced one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    preview: Option<Range<u32>>,
+    /// Arbitrary metadata attached to the asset by its encoder or `Wasset.toml` file.
+    #[serde(default, skip_serializing_if = "std::collections::BTreeMap::is_empty")]
+    metadata: std::collections::BTreeMap<String, String>,
+    /// The tags declared for the asset in its `Wasset.toml` file.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    tags: Vec<String>,
+    /// The assets which hold the variants of this asset for particular locales, keyed by locale.
+    #[serde(default, skip_serializing_if = "std::collections::BTreeMap::is_empty")]
+    locales: std::collections::BTreeMap<String, WassetId>,
+    /// The payloads of the asset at other quality tiers, each of which is described by an entry
+    /// that holds only its range, hash, compression, encryption, codec, and size.
+    #[serde(default, skip_serializing_if = "std::collections::BTreeMap::is_empty")]
+    qualities: std::collections::BTreeMap<Quality, WassetManifestEntry>,
+    /// Whether the data of the asset is absent from the module which the entry was read from.
+    #[serde(skip)]
+    external: bool
+}
+
+impl WassetManifestEntry {
+    /// Gets the byte range of the asset data. For entries obtained from a
+    /// `WassetParser`, the range is relative to the start of the module.
+    pub fn range(&self) -> Range<u32> {
+        self.start..self.end
+    }
+
+    /// Gets the hash of the serialized asset, if one was recorded during encoding.
+    /// The hash is computed before compression.
+    pub fn hash(&self) -> Option<WassetHash> {
+        self.hash
+    }
+
+    /// Gets the compression scheme applied to the serialized asset.
+    pub fn compression(&self) -> WassetCompression {
+        self.compression
+    }
+
+    /// Gets the format in which the asset was serialized.
+    pub fn codec(&self) -> WassetCodec {
+        self.codec
+    }
+
+    /// Whether the stored bytes of the asset are encrypted, in which case
+    /// loading it requires the key with which it was encoded.
+    pub fn is_encrypted(&self) -> bool {
+        self.encrypted
+    }
+
+    /// Whether the data of the asset is absent from the module from which this entry was read,
+    /// because its manifest has external data. Such assets may be inspected, but not loaded.
+    pub fn is_external(&self) -> bool {
+        self.external
+    }
+
+    /// Fails if the data of the asset is absent from the module, such that it cannot be loaded.
+    #[cfg(feature = "parse")]
+    pub(crate) fn check_local(&self) -> Result<(), WassetError> {
+        if self.external {
+            Err(WassetError::from_deserialize("Asset data is stored outside of the module"))
+        }
+        else {
+            Ok(())
+        }
+    }
+
+    /// Whether the stored bytes of the asset are exactly its serialized bytes, such that
+    /// they may be used without decompression or decryption.
+    pub(crate) fn is_stored_verbatim(&self) -> bool {
+        self.compression.is_none() && !self.encrypted
+    }
+
+    /// Gets the number of bytes that the asset occupies in the module.
+    pub fn compressed_size(&self) -> u32 {
+        self.end.saturating_sub(self.start)
+    }
+
+    /// Gets the size of the serialized asset after decompression, if it is known.
+    /// This is always known for assets which are stored verbatim.
+    pub fn uncompressed_size(&self) -> Option<u32> {
+        if self.is_stored_verbatim() {
+            Some(self.compressed_size())
+        }
+        else {
+            self.uncompressed_size
+        }
+    }
+
+    /// Gets the name of the asset, if it was encoded from a file. This is the path of the asset
+    /// beginning with the root asset folder, without an extension and using `/` as a separator,
+    /// such as `assets/submodule/more_text`.
+    /// The final component reflects any `name` override from a `Wasset.toml` file.
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    /// Whether the asset has the given name, either in the form of [`Self::name`]
+    /// or relative to the root asset folder.
+    fn has_name(&self, name: &str) -> bool {
+        self.name.as_deref().is_some_and(|x| x == name || x.split_once('/').is_some_and(|(_, rest)| rest == name))
+    }
+
+    /// Gets the IDs of the other assets upon which this asset depends, such as the
+    /// textures referenced by a material.
+    pub fn dependencies(&self) -> &[WassetId] {
+        &self.dependencies
+    }
+
+    /// Whether the asset was encoded from a file within the given folder, or any of its
+    /// subfolders. The folder is given relative to the parent of the root asset folder,
+    /// in the same form as [`Self::name`], such as `assets/textures`.
+    pub fn is_in_folder(&self, folder: &str) -> bool {
+        let folder = folder.trim_end_matches('/');
+        self.name.as_deref().and_then(|x| x.strip_prefix(folder)).is_some_and(|x| x.starts_with('/'))
+    }
+
+    /// Gets the revision of the asset, if one was recorded. Versions increase whenever the
+    /// asset changes, which allows caches of derived data to be invalidated selectively.
+    pub fn version(&self) -> Option<u64> {
+        self.version
+    }
+
+    /// Gets the kind of the asset, such as `image` or `text`, if its encoder reported one.
+    /// This allows for finding all assets of a certain kind without decoding them.
+    pub fn kind(&self) -> Option<&str> {
+        self.kind.as_deref()
+    }
+
+    /// Gets the metadata attached to the asset by its encoder or `Wasset.toml` file,
+    /// such as its author or license.
+    pub fn metadata(&self) -> &std::collections::BTreeMap<String, String> {
+        &self.metadata
+    }
+
+    /// Gets the tags declared for the asset in its `Wasset.toml` file, such as `ui` or `audio`.
+    pub fn tags(&self) -> &[String] {
+        &self.tags
+    }
+
+    /// Whether the asset was declared with the given tag.
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.tags.iter().any(|x| x == tag)
+    }
+
+    /// Gets the assets which hold the localized variants of this asset, keyed by locale, such as
+    /// `de` for a `dialog.de.txt` file. Each variant is an asset of its own, with its own ID.
+    pub fn locales(&self) -> &std::collections::BTreeMap<String, WassetId> {
+        &self.locales
+    }
+
+    /// Gets the payloads of this asset at other quality tiers, such as [`Quality::Low`] for a
+    /// `texture.low.png` file. The tiers are stored under the same ID as the asset, and each is
+    /// described by an entry which holds only the range, hash, compression, and codec of its data.
+    pub fn qualities(&self) -> &std::collections::BTreeMap<Quality, WassetManifestEntry> {
+        &self.qualities
+    }
+
+    /// Gets the entry which describes the payload of this asset at a quality tier. The asset itself
+    /// serves as its [`Quality::High`] tier unless another was stored. If the asset has no payload at
+    /// the tier, the nearest tier is used instead, preferring lower tiers to higher ones.
+    pub fn variant(&self, quality: Quality) -> &WassetManifestEntry {
+        self.qualities.iter()
+            .chain((!self.qualities.contains_key(&Quality::High)).then_some((&Quality::High, self)))
+            .min_by_key(|(tier, _)| ((**tier as i32 - quality as i32).abs(), **tier > quality))
+            .map_or(self, |(_, entry)| entry)
+    }
+
+    /// Gets an entry which describes only the stored payload of this one, without any names,
+    /// dependencies, or other information, for use as a quality tier of another asset.
+    #[cfg(feature = "encode")]
+    pub(crate) fn to_tier(&self) -> WassetManifestEntry {
+        Self {
+            start: self.start,
+            end: self.end,
+            hash: self.hash,
+            compression: self.compression,
+            encrypted: self.encrypted,
+            codec: self.codec,
+            uncompressed_size: self.uncompressed_size,
+            ..Default::default()
+        }
+    }
+
+    /// Gets the entries which describe every payload of the asset: the asset itself,
+    /// followed by its other quality tiers.
+    #[cfg(feature = "parse")]
+    pub(crate) fn payloads(&self) -> impl '_ + Iterator<Item = &WassetManifestEntry> {
+        std::iter::once(self).chain(self.qualities.values())
+    }
+
+    /// Moves every payload of the asset, including those of its other quality tiers. The
+    /// `relocate` function receives the current range of each payload, and returns its new start.
+    #[cfg(any(feature = "encode", feature = "parse"))]
+    pub(crate) fn relocate(&mut self, mut relocate: impl FnMut(Range<u32>) -> Result<u32, WassetError>) -> Result<(), WassetError> {
+        let start = relocate(self.range())?;
+        self.end = start + (self.end - self.start);
+        self.start = start;
+
+        for entry in self.qualities.values_mut() {
+            let start = relocate(entry.range())?;
+            entry.end = start + (entry.end - entry.start);
+            entry.start = start;
+        }
+
+        Ok(())
+    }
+
+    /// Whether the encoder produced a preview of the asset.
+    pub fn has_preview(&self) -> bool {
+        self.preview.is_some()
+    }
+
+    /// Copies the preview of this entry from the `source` preview section to the end of `output`,
+    /// updating the entry to refer to the copy. Previews which lie outside of `source` are dropped.
+    #[cfg(any(feature = "encode", feature = "parse"))]
+    pub(crate) fn copy_preview(&mut self, source: &[u8], output: &mut Vec<u8>) {
+        let preview = self.preview.take().and_then(|x| source.get(x.start as usize..x.end as usize));
+        if let Some(preview) = preview {
+            let start = output.len() as u32;
+            output.extend_from_slice(preview);
+            self.preview = Some(start..output.len() as u32);
+        }
+    }
+}
+
+/// A quality tier at which variants of an asset may be stored, such as textures of
+/// different resolutions for low-end and high-end targets.
+#[derive(Copy, Clone, Debug, Serialize, Deserialize, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Quality {
+    /// The lowest tier, for constrained targets.
+    Low,
+    /// The intermediate tier.
+    Medium,
+    /// The highest tier.
+    High
+}
+
+impl Quality {
+    /// All quality tiers, from lowest to highest.
+    pub const ALL: [Self; 3] = [Self::Low, Self::Medium, Self::High];
+
+    /// Gets the name of the tier, as it appears in the names of asset files such as `texture.low.png`.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Low => "low",
+            Self::Medium => "medium",
+            Self::High => "high"
+        }
+    }
+}
+
+impl std::str::FromStr for Quality {
+    type Err = WassetError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL.into_iter().find(|x| x.name() == s)
+            .ok_or_else(|| WassetError::from_serialize(format!("Unknown quality {s:?}; expected \"low\", \"medium\", or \"high\"")))
     }
 }
 
@@ -163,7 +954,81 @@ pub enum WassetError {
     Serialize(Box<dyn std::error::Error + Send + Sync>),
     /// An error was raised while reading assets.
     #[error("An error occurred during deserialization: {0}")]
-    Deserialize(Box<dyn std::error::Error + Send + Sync>)
+    Deserialize(Box<dyn std::error::Error + Send + Sync>),
+    /// An asset was larger than the configured limit allowed.
+    #[error("Asset size of {size} bytes exceeded the limit of {limit} bytes")]
+    LimitExceeded {
+        /// The size of the asset, in bytes.
+        size: u64,
+        /// The maximum allowed size, in bytes.
+        limit: u64
+    },
+    /// An asset depended upon another asset which could not be found.
+    #[error("Asset {asset:?} depends on {dependency:?}, which does not exist")]
+    MissingDependency {
+        /// The asset which declared the dependency.
+        asset: WassetId,
+        /// The asset which could not be found.
+        dependency: WassetId
+    },
+    /// The dependencies between assets formed a cycle.
+    #[error("Asset dependencies form a cycle: {0:?}")]
+    DependencyCycle(Vec<WassetId>),
+    /// A set of asset sections was encoded with a different schema fingerprint than the parser expected.
+    #[error("Asset sections {section} were encoded with schema {found:?}, but schema {expected:?} was expected")]
+    SchemaMismatch {
+        /// The ID of the set of sections.
+        section: Uuid,
+        /// The schema fingerprint that the parser expected.
+        expected: String,
+        /// The schema fingerprint recorded in the manifest, if any.
+        found: Option<String>
+    },
+    /// A manifest was written in a newer format than this version of the crate can read.
+    #[error("Asset manifest has format version {found}, but only versions up to {supported} are supported")]
+    UnsupportedVersion {
+        /// The format version of the manifest.
+        found: u32,
+        /// The newest format version that this crate reads.
+        supported: u32
+    },
+    /// More than one set of asset sections in a module provided the same asset.
+    #[error("Asset {0:?} is provided by more than one set of asset sections")]
+    DuplicateId(WassetId),
+    /// The data of an asset did not match the hash recorded in the manifest,
+    /// because the data section was truncated or modified.
+    #[error("Asset {0:?} does not match its recorded hash")]
+    IntegrityMismatch(WassetId),
+    /// A set of asset sections was only partly written, such as by an interrupted build or
+    /// post-build injection, so that a manifest lacks its data section or vice versa. Such
+    /// modules may be fixed with `repair_module`, which removes the incomplete sets.
+    #[error("Asset sections {section} are incomplete, because the {missing:?} section is missing")]
+    IncompleteSections {
+        /// The ID of the incomplete set of sections.
+        section: Uuid,
+        /// The section which is missing.
+        missing: WassetSectionPart
+    },
+    /// A set of asset sections was not signed, or its signature was not made by the expected key.
+    #[error("Asset sections {0} do not have a valid signature")]
+    InvalidSignature(Uuid),
+    /// An error was raised while processing a particular asset file or folder.
+    #[error("Failed to process {}: {source}", path.display())]
+    InFile {
+        /// The path of the file or folder that was being processed.
+        path: std::path::PathBuf,
+        /// The error that was raised.
+        source: Box<WassetError>
+    }
+}
+
+/// Identifies one of the two sections which every complete set of asset sections holds.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum WassetSectionPart {
+    /// The section which holds the serialized manifest.
+    Manifest,
+    /// The section which holds the asset data.
+    Data
 }
 
 impl WassetError {
@@ -176,4 +1041,23 @@ impl WassetError {
     pub fn from_serialize(err: impl Into<Box<dyn std::error::Error + Send + Sync>>) -> Self {
         Self::Serialize(err.into())
     }
+
+    /// Attaches the path of the file or folder that was being processed to this error.
+    /// Errors which already name a path are returned unchanged, so that the innermost
+    /// path is reported.
+    pub fn in_file(self, path: impl Into<std::path::PathBuf>) -> Self {
+        match self {
+            Self::InFile { .. } => self,
+            source => Self::InFile { path: path.into(), source: Box::new(source) }
+        }
+    }
+
+    /// Gets the path of the file or folder that was being processed when this error
+    /// was raised, if it is known.
+    pub fn path(&self) -> Option<&std::path::Path> {
+        match self {
+            Self::InFile { path, .. } => Some(path),
+            _ => None
+        }
+    }
 }
\ No newline at end of file
@@ -0,0 +1,65 @@
+use crate::*;
+use wasmtime::{Caller, Extern, Linker, Memory};
+
+/// Extends a `wasmtime` [`Linker`] with the functions through which guests built with the
+/// `guest` feature read their own assets. A `wasmtime::Module` does not retain the custom
+/// sections of its bytecode, so the parser should be created from the same bytes as the module:
+///
+/// ```no_run
+/// # use wasset::*;
+/// # use wasmtime::*;
+/// # type MyAsset = Vec<u8>;
+/// # struct HostState { assets: WassetOwnedParser<MyAsset> }
+/// # fn instantiate(engine: &Engine, module: &Module, bytes: Vec<u8>) -> wasmtime::Result<()> {
+/// // The module is compiled from the same bytes, such as with `Module::new(&engine, &bytes)`.
+/// let assets = WassetOwnedParser::<MyAsset>::parse(bytes)?;
+///
+/// let mut linker = Linker::<HostState>::new(&engine);
+/// linker.define_wasset(|state: &HostState| &state.assets)?;
+/// let instance = linker.instantiate(&mut Store::new(&engine, HostState { assets }), &module)?;
+/// # Ok(())
+/// # }
+/// ```
+pub trait WassetLinkerExt<T> {
+    /// Defines `asset_len`, `asset_codec`, and `asset_read` within the [`GUEST_IMPORT_MODULE`], serving
+    /// assets from the parser which `get` selects from the store data. The guest must export its
+    /// linear memory as `memory`.
+    fn define_wasset<A: AssetSchema>(&mut self, get: impl 'static + Copy + Send + Sync + Fn(&T) -> &WassetOwnedParser<A>) -> wasmtime::Result<&mut Self>;
+}
+
+impl<T: 'static> WassetLinkerExt<T> for Linker<T> {
+    fn define_wasset<A: AssetSchema>(&mut self, get: impl 'static + Copy + Send + Sync + Fn(&T) -> &WassetOwnedParser<A>) -> wasmtime::Result<&mut Self> {
+        self.func_wrap(GUEST_IMPORT_MODULE, "asset_len", move |mut caller: Caller<'_, T>, id: u32| {
+            let id = read_id(&mut caller, id)?;
+            Ok(get(caller.data()).parser().serve_asset_len(id))
+        })?;
+
+        self.func_wrap(GUEST_IMPORT_MODULE, "asset_codec", move |mut caller: Caller<'_, T>, id: u32| {
+            let id = read_id(&mut caller, id)?;
+            Ok(get(caller.data()).parser().serve_asset_codec(id))
+        })?;
+
+        self.func_wrap(GUEST_IMPORT_MODULE, "asset_read", move |mut caller: Caller<'_, T>, id: u32, buffer: u32, len: u32| {
+            let id = read_id(&mut caller, id)?;
+            let (memory, data) = guest_memory(&mut caller)?.data_and_store_mut(&mut caller);
+            let buffer = memory.get_mut(buffer as usize..buffer as usize + len as usize)
+                .ok_or_else(|| wasmtime::Error::msg("Asset buffer lies outside of guest memory"))?;
+            Ok(get(data).parser().serve_asset_read(id, buffer))
+        })
+    }
+}
+
+/// Gets the linear memory which the guest exports.
+fn guest_memory<T: 'static>(caller: &mut Caller<'_, T>) -> wasmtime::Result<Memory> {
+    match caller.get_export("memory") {
+        Some(Extern::Memory(memory)) => Ok(memory),
+        _ => Err(wasmtime::Error::msg("Guest does not export its memory"))
+    }
+}
+
+/// Reads the asset ID at the given address of guest memory.
+fn read_id<T: 'static>(caller: &mut Caller<'_, T>, address: u32) -> wasmtime::Result<WassetId> {
+    let mut bytes = [0; 16];
+    guest_memory(caller)?.read(&*caller, address as usize, &mut bytes)?;
+    Ok(WassetId::from_bytes(bytes))
+}
@@ -0,0 +1,460 @@
+use crate::*;
+use std::collections::BTreeMap;
+
+/// Determines how the manifest of each set of assets is serialized. Parsers detect the
+/// format of every manifest that they read, so modules may mix formats freely.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub enum ManifestFormat {
+    /// The manifest is serialized as a self-describing MessagePack map.
+    #[default]
+    MessagePack,
+    /// The manifest is serialized in a versioned, columnar encoding. Entries are sorted by ID,
+    /// and both the IDs and the data ranges are stored as variable-length deltas from the
+    /// previous entry. Hashes are stored as raw bytes, while the remaining metadata is stored
+    /// without field names. The result
+    /// is optionally compressed. This substantially reduces the size and parse time of modules
+    /// with many thousands of assets, particularly when combined with `IdStrategy::TimeOrdered`,
+    /// under which consecutive IDs and ranges are close together.
+    Compact {
+        /// The compression scheme applied to the encoded manifest.
+        compression: WassetCompression
+    }
+}
+
+impl ManifestFormat {
+    /// Determines the format of a serialized manifest.
+    pub fn detect(bytes: &[u8]) -> Self {
+        if bytes.starts_with(&COMPACT_MANIFEST_MAGIC) {
+            Self::Compact {
+                compression: bytes.get(COMPACT_MANIFEST_MAGIC.len() + 1)
+                    .and_then(|x| WassetCompression::ALL.get(*x as usize))
+                    .copied()
+                    .unwrap_or_default()
+            }
+        }
+        else {
+            Self::MessagePack
+        }
+    }
+}
+
+impl WassetManifest {
+    /// Deserializes a manifest which was stored in any [`ManifestFormat`].
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, WassetError> {
+        let Some(header) = bytes.strip_prefix(&COMPACT_MANIFEST_MAGIC) else {
+            let manifest = rmp_serde::from_slice::<Self>(bytes).map_err(|e| {
+                // The layout of a newer manifest may not deserialize at all, so its version is read alone to report it.
+                let version = rmp_serde::from_slice::<ManifestVersion>(bytes).map_or(0, |x| x.format_version);
+                check_version(version).err().unwrap_or_else(|| WassetError::from_deserialize(e))
+            })?;
+
+            check_version(manifest.version)?;
+            return Ok(manifest);
+        };
+
+        let [version, compression, body @ ..] = header else {
+            return Err(WassetError::from_deserialize("Compact asset manifest has an invalid header"));
+        };
+
+        check_version(u32::from(*version))?;
+        let compression = WassetCompression::ALL.get(*compression as usize)
+            .ok_or_else(|| WassetError::from_deserialize(format!("Compact asset manifest has unknown compression scheme {compression}")))?;
+        let mut manifest = if compression.is_none() {
+            decode_compact(body)?
+        }
+        else {
+            decode_compact(&compression.decompress(body, MAX_COMPACT_MANIFEST_SIZE)?)?
+        };
+
+        manifest.version = u32::from(*version);
+        Ok(manifest)
+    }
+
+    /// Deserializes a manifest which was exported by [`Self::to_json`].
+    #[cfg(feature = "json")]
+    pub fn from_json(json: &str) -> Result<Self, WassetError> {
+        let manifest = serde_json::from_str::<Self>(json).map_err(|e| {
+            let version = serde_json::from_str::<ManifestVersion>(json).map_or(0, |x| x.format_version);
+            check_version(version).err().unwrap_or_else(|| WassetError::from_deserialize(e))
+        })?;
+
+        check_version(manifest.version)?;
+        Ok(manifest)
+    }
+
+    /// Exports this manifest as JSON, so that tools which do not link this crate can read
+    /// the IDs, ranges, names, kinds, and sizes of the assets. IDs are written as UUID strings
+    /// and hashes as hexadecimal strings. The fields of each entry match the accessors of
+    /// [`WassetManifestEntry`], and fields without a value are omitted.
+    #[cfg(feature = "json")]
+    pub fn to_json(&self) -> Result<String, WassetError> {
+        serde_json::to_string(self).map_err(WassetError::from_serialize)
+    }
+
+    /// Serializes this manifest in the given format.
+    pub fn to_bytes(&self, format: ManifestFormat) -> Result<Vec<u8>, WassetError> {
+        match format {
+            ManifestFormat::MessagePack => rmp_serde::to_vec_named(self).map_err(WassetError::from_serialize),
+            ManifestFormat::Compact { compression } => {
+                let body = encode_compact(self)?;
+                let mut result = COMPACT_MANIFEST_MAGIC.to_vec();
+                result.push(Self::FORMAT_VERSION as u8);
+                result.push(WassetCompression::ALL.iter().position(|x| *x == compression).unwrap_or_default() as u8);
+                result.extend_from_slice(&compression.compress(&body)?);
+                Ok(result)
+            }
+        }
+    }
+}
+
+/// The format version of a MessagePack manifest, which is read on its own when the
+/// rest of the manifest cannot be.
+#[derive(Deserialize)]
+struct ManifestVersion {
+    /// The format version, which is absent from manifests that predate versioning.
+    #[serde(default)]
+    format_version: u32
+}
+
+/// The metadata of a manifest which is not stored in columns by the compact format.
+#[derive(Serialize, Deserialize)]
+struct CompactManifestTail {
+    /// The partition of the manifest.
+    partition: Option<String>,
+    /// The remaining metadata of each entry, in ID order.
+    entries: Vec<CompactManifestEntry>,
+    /// The schema fingerprint of the manifest.
+    #[serde(default)]
+    schema: Option<String>,
+    /// Whether the data section of the manifest was removed.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    external_data: bool
+}
+
+/// The metadata of an entry which is not stored in columns by the compact format.
+#[derive(Serialize, Deserialize)]
+struct CompactManifestEntry {
+    /// The compression scheme applied to the serialized asset.
+    compression: WassetCompression,
+    /// The size of the serialized asset before compression.
+    uncompressed_size: Option<u32>,
+    /// The path of the asset.
+    name: Option<String>,
+    /// The other assets upon which this asset depends.
+    dependencies: Vec<WassetId>,
+    /// The revision of the asset.
+    version: Option<u64>,
+    /// The kind of the asset.
+    kind: Option<String>,
+    /// The range of the asset's preview.
+    preview: Option<Range<u32>>,
+    /// Whether the stored bytes are encrypted.
+    #[serde(default)]
+    encrypted: bool,
+    /// The format in which the asset was serialized.
+    #[serde(default)]
+    codec: WassetCodec,
+    /// The metadata attached to the asset.
+    #[serde(default)]
+    metadata: BTreeMap<String, String>,
+    /// The tags declared for the asset.
+    #[serde(default)]
+    tags: Vec<String>,
+    /// The localized variants of the asset.
+    #[serde(default)]
+    locales: BTreeMap<String, WassetId>,
+    /// The payloads of the asset at other quality tiers.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    qualities: BTreeMap<Quality, CompactQualityTier>
+}
+
+/// The payload of an asset at another quality tier. Tiers are rare, so their
+/// ranges are stored directly rather than in columns.
+#[derive(Serialize, Deserialize)]
+struct CompactQualityTier {
+    /// The offset at which the payload begins.
+    start: u32,
+    /// The offset at which the payload ends.
+    end: u32,
+    /// The hash of the serialized payload.
+    hash: Option<WassetHash>,
+    /// The compression scheme applied to the serialized payload.
+    compression: WassetCompression,
+    /// Whether the stored bytes are encrypted.
+    encrypted: bool,
+    /// The format in which the payload was serialized.
+    codec: WassetCodec,
+    /// The size of the serialized payload before compression.
+    uncompressed_size: Option<u32>
+}
+
+/// Fails with [`WassetError::UnsupportedVersion`] if a manifest was written
+/// in a newer format than this crate reads.
+fn check_version(version: u32) -> Result<(), WassetError> {
+    if version > WassetManifest::FORMAT_VERSION {
+        Err(WassetError::UnsupportedVersion { found: version, supported: WassetManifest::FORMAT_VERSION })
+    }
+    else {
+        Ok(())
+    }
+}
+
+/// Encodes the body of a compact manifest, before compression.
+fn encode_compact(manifest: &WassetManifest) -> Result<Vec<u8>, WassetError> {
+    let mut entries = manifest.assets.iter().collect::<Vec<_>>();
+    entries.sort_unstable_by_key(|(id, _)| **id);
+
+    let mut result = Vec::new();
+    write_varint(&mut result, entries.len() as u128);
+
+    let mut previous_id = 0;
+    for (id, _) in &entries {
+        let id = Uuid::from(**id).as_u128();
+        write_varint(&mut result, id - previous_id);
+        previous_id = id;
+    }
+
+    let mut previous_end = 0;
+    for (_, entry) in &entries {
+        let delta = i64::from(entry.start) - i64::from(previous_end);
+        write_varint(&mut result, ((delta << 1) ^ (delta >> 63)) as u64 as u128);
+        let len = entry.end.checked_sub(entry.start)
+            .ok_or_else(|| WassetError::from_serialize("Asset manifest has an entry which ends before it starts"))?;
+        write_varint(&mut result, u128::from(len));
+        previous_end = entry.end;
+    }
+
+    for (_, entry) in &entries {
+        match &entry.hash {
+            Some(hash) => {
+                result.push(1);
+                result.extend_from_slice(hash.as_bytes());
+            },
+            None => result.push(0)
+        }
+    }
+
+    let tail = CompactManifestTail {
+        partition: manifest.partition.clone(),
+        schema: manifest.schema.clone(),
+        external_data: manifest.external_data,
+        entries: entries.into_iter().map(|(_, entry)| CompactManifestEntry {
+            compression: entry.compression,
+            uncompressed_size: entry.uncompressed_size,
+            name: entry.name.clone(),
+            dependencies: entry.dependencies.clone(),
+            version: entry.version,
+            kind: entry.kind.clone(),
+            preview: entry.preview.clone(),
+            encrypted: entry.encrypted,
+            codec: entry.codec,
+            metadata: entry.metadata.clone(),
+            tags: entry.tags.clone(),
+            locales: entry.locales.clone(),
+            qualities: entry.qualities.iter().map(|(quality, tier)| (*quality, CompactQualityTier {
+                start: tier.start,
+                end: tier.end,
+                hash: tier.hash,
+                compression: tier.compression,
+                encrypted: tier.encrypted,
+                codec: tier.codec,
+                uncompressed_size: tier.uncompressed_size
+            })).collect()
+        }).collect()
+    };
+
+    rmp_serde::encode::write(&mut result, &tail).map_err(WassetError::from_serialize)?;
+    Ok(result)
+}
+
+/// Decodes the body of a compact manifest, after decompression.
+fn decode_compact(mut bytes: &[u8]) -> Result<WassetManifest, WassetError> {
+    let count = read_varint(&mut bytes)?;
+    // Every entry occupies at least three bytes, which bounds the allocations below for untrusted input.
+    if count > bytes.len() as u128 / 3 {
+        return Err(WassetError::from_deserialize("Compact asset manifest has an invalid entry count"));
+    }
+
+    let mut ids = Vec::with_capacity(count as usize);
+    let mut previous_id = 0u128;
+    for _ in 0..count {
+        previous_id = previous_id.checked_add(read_varint(&mut bytes)?)
+            .ok_or_else(|| WassetError::from_deserialize("Compact asset manifest has an invalid ID"))?;
+        ids.push(WassetId::from(Uuid::from_u128(previous_id)));
+    }
+
+    let mut ranges = Vec::with_capacity(count as usize);
+    let mut previous_end = 0u32;
+    for _ in 0..count {
+        let delta = u64::try_from(read_varint(&mut bytes)?).unwrap_or(u64::MAX);
+        let start = i64::from(previous_end).checked_add(((delta >> 1) as i64) ^ -((delta & 1) as i64));
+        let start = start.and_then(|x| u32::try_from(x).ok());
+        let end = start.zip(u32::try_from(read_varint(&mut bytes)?).ok()).and_then(|(start, len)| start.checked_add(len));
+        let (Some(start), Some(end)) = (start, end) else {
+            return Err(WassetError::from_deserialize("Compact asset manifest has an invalid range"));
+        };
+
+        ranges.push(start..end);
+        previous_end = end;
+    }
+
+    let mut hashes = Vec::with_capacity(count as usize);
+    for _ in 0..count {
+        let (&present, rest) = bytes.split_first()
+            .ok_or_else(|| WassetError::from_deserialize("Compact asset manifest ended unexpectedly"))?;
+        bytes = rest;
+        hashes.push(match present {
+            0 => None,
+            1 => {
+                let (hash, rest) = bytes.split_first_chunk::<32>()
+                    .ok_or_else(|| WassetError::from_deserialize("Compact asset manifest ended unexpectedly"))?;
+                bytes = rest;
+                Some(WassetHash::from_bytes(*hash))
+            },
+            _ => return Err(WassetError::from_deserialize("Compact asset manifest has an invalid hash marker"))
+        });
+    }
+
+    let tail = rmp_serde::from_slice::<CompactManifestTail>(bytes).map_err(WassetError::from_deserialize)?;
+    if tail.entries.len() != ids.len() {
+        return Err(WassetError::from_deserialize("Compact asset manifest has mismatched entry metadata"));
+    }
+
+    let mut manifest = WassetManifest { partition: tail.partition, schema: tail.schema, external_data: tail.external_data, ..Default::default() };
+    manifest.assets.reserve(ids.len());
+    for (((id, range), hash), entry) in ids.into_iter().zip(ranges).zip(hashes).zip(tail.entries) {
+        manifest.assets.insert(id, WassetManifestEntry {
+            start: range.start,
+            end: range.end,
+            hash,
+            compression: entry.compression,
+            encrypted: entry.encrypted,
+            codec: entry.codec,
+            uncompressed_size: entry.uncompressed_size,
+            name: entry.name,
+            dependencies: entry.dependencies,
+            version: entry.version,
+            kind: entry.kind,
+            preview: entry.preview,
+            metadata: entry.metadata,
+            tags: entry.tags,
+            locales: entry.locales,
+            qualities: entry.qualities.into_iter().map(|(quality, tier)| (quality, WassetManifestEntry {
+                start: tier.start,
+                end: tier.end,
+                hash: tier.hash,
+                compression: tier.compression,
+                encrypted: tier.encrypted,
+                codec: tier.codec,
+                uncompressed_size: tier.uncompressed_size,
+                ..Default::default()
+            })).collect(),
+            external: false
+        });
+    }
+
+    Ok(manifest)
+}
+
+/// Appends an unsigned LEB128 integer to `output`.
+fn write_varint(output: &mut Vec<u8>, mut value: u128) {
+    while value >= 0x80 {
+        output.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    output.push(value as u8);
+}
+
+/// Reads an unsigned LEB128 integer from the start of `bytes`, advancing past it.
+fn read_varint(bytes: &mut &[u8]) -> Result<u128, WassetError> {
+    let mut result = 0u128;
+    for shift in (0..128).step_by(7) {
+        let (&byte, rest) = bytes.split_first()
+            .ok_or_else(|| WassetError::from_deserialize("Compact asset manifest ended unexpectedly"))?;
+        *bytes = rest;
+        result |= u128::from(byte & 0x7f).checked_shl(shift).filter(|x| x >> shift == u128::from(byte & 0x7f))
+            .ok_or_else(|| WassetError::from_deserialize("Compact asset manifest has an oversized integer"))?;
+        if byte & 0x80 == 0 {
+            return Ok(result);
+        }
+    }
+
+    Err(WassetError::from_deserialize("Compact asset manifest has an oversized integer"))
+}
+
+/// The bytes which begin every manifest in the compact format. A MessagePack
+/// manifest always begins with a map marker, so the two cannot be confused.
+const COMPACT_MANIFEST_MAGIC: [u8; 4] = *b"WSMC";
+
+/// The largest size to which a compressed manifest may expand.
+const MAX_COMPACT_MANIFEST_SIZE: u64 = 1 << 30;
+
+#[cfg(all(test, feature = "testing"))]
+mod tests {
+    use super::*;
+
+    /// Encodes a small folder whose entries use most manifest fields, returning its manifest.
+    fn sample_manifest() -> WassetManifest {
+        let files = [
+            ("Wasset.toml", "[\"greeting.txt\"]\nkind = \"note\"\nversion = 2\ndependencies = [\"farewell\"]\ntags = [\"ui\"]\nmetadata = { author = \"me\" }\n"),
+            ("greeting.txt", "hello"),
+            ("greeting.de.txt", "hallo"),
+            ("farewell.txt", "goodbye"),
+            ("texture.txt", "high"),
+            ("texture.low.txt", "low")
+        ];
+        let options = WassetEncodeOptions {
+            locales: vec![String::from("de")],
+            quality_variants: true,
+            schema: Some(String::from("v1")),
+            ..Default::default()
+        };
+
+        WassetManifest::from_bytes(&testing::encode_text_files(files, &options).unwrap().manifest).unwrap()
+    }
+
+    /// Ensures that two manifests describe the same assets.
+    fn assert_same_manifest(expected: &WassetManifest, actual: &WassetManifest) {
+        assert_eq!(expected.schema(), actual.schema());
+        assert_eq!(expected.partition(), actual.partition());
+        assert_eq!(expected.ids().count(), actual.ids().count());
+        for (id, entry) in expected.iter() {
+            assert_eq!(actual.get(id), Some(entry));
+        }
+    }
+
+    #[test]
+    fn compact_manifest_round_trips() {
+        let manifest = sample_manifest();
+        assert!(manifest.iter().any(|(_, entry)| entry.kind().is_some() && entry.version().is_some() && !entry.dependencies().is_empty()));
+        assert!(manifest.iter().any(|(_, entry)| !entry.qualities().is_empty()));
+        assert!(manifest.iter().any(|(_, entry)| !entry.locales().is_empty()));
+        assert!(manifest.iter().any(|(_, entry)| !entry.tags().is_empty() && !entry.metadata().is_empty()));
+
+        for compression in WassetCompression::ALL.into_iter().filter(WassetCompression::is_available) {
+            let bytes = manifest.to_bytes(ManifestFormat::Compact { compression }).unwrap();
+            assert_eq!(ManifestFormat::detect(&bytes), ManifestFormat::Compact { compression });
+            assert_same_manifest(&manifest, &WassetManifest::from_bytes(&bytes).unwrap());
+        }
+
+        let bytes = manifest.to_bytes(ManifestFormat::MessagePack).unwrap();
+        assert_eq!(ManifestFormat::detect(&bytes), ManifestFormat::MessagePack);
+        assert_same_manifest(&manifest, &WassetManifest::from_bytes(&bytes).unwrap());
+    }
+
+    #[test]
+    fn malformed_compact_manifest_is_rejected() {
+        let bytes = sample_manifest().to_bytes(ManifestFormat::Compact { compression: WassetCompression::None }).unwrap();
+        for length in [COMPACT_MANIFEST_MAGIC.len(), COMPACT_MANIFEST_MAGIC.len() + 2, bytes.len() / 2, bytes.len() - 1] {
+            assert!(WassetManifest::from_bytes(&bytes[..length]).is_err());
+        }
+
+        let mut unknown_compression = bytes.clone();
+        unknown_compression[COMPACT_MANIFEST_MAGIC.len() + 1] = WassetCompression::ALL.len() as u8;
+        assert!(WassetManifest::from_bytes(&unknown_compression).is_err());
+
+        let mut newer = bytes;
+        newer[COMPACT_MANIFEST_MAGIC.len()] = WassetManifest::FORMAT_VERSION as u8 + 1;
+        assert!(matches!(WassetManifest::from_bytes(&newer), Err(WassetError::UnsupportedVersion { found, .. }) if found == WassetManifest::FORMAT_VERSION + 1));
+    }
+}
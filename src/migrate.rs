@@ -0,0 +1,54 @@
+use crate::*;
+
+impl<'a, A: AssetSchema> WassetParser<'a, A> {
+    /// Creates a copy of the module in which every asset has been re-encoded by `reencode`.
+    /// The callback receives the raw data of each asset, which may be deserialized as `A` or
+    /// decoded by other means, and returns the migrated asset, or `None` if the asset should be
+    /// dropped. The migrated assets keep their IDs, names, dependencies, versions, kinds, previews,
+    /// metadata, tags, and locale variants, and are stored in a single new set of custom sections
+    /// using the provided `options`, which allows for changing the schema or compression of a module
+    /// without access to its original asset files. Each quality tier is re-encoded by `reencode`
+    /// along with the asset that holds it, and is dropped if the callback returns `None`. Locale
+    /// variants which refer to dropped assets are removed.
+    pub fn reencode<B: AssetSchema>(&self, mut reencode: impl FnMut(WassetId, WassetItem<'_, A>) -> Result<Option<B>, WassetError>, options: &WassetEncodeOptions) -> Result<Vec<u8>, WassetError> {
+        let mut data = Vec::new();
+        let mut payloads = WrittenPayloads::default();
+        let mut previews = Vec::new();
+        let mut manifest = WassetManifest::default();
+        for (id, old_entry) in self.manifest().iter() {
+            let item = self.load_raw(id)?.ok_or_else(|| WassetError::from_deserialize(format!("Asset {id:?} is missing from the manifest")))?;
+            if let Some(asset) = reencode(id, item)? {
+                let mut qualities = std::collections::BTreeMap::new();
+                for (quality, tier) in old_entry.qualities() {
+                    if let Some(tier_asset) = reencode(id, self.load_entry(tier)?)? {
+                        // The entry for the tier is replaced when the asset itself is written below.
+                        let entry = write_asset(&mut data, &mut payloads, &mut manifest, id, &tier_asset, options, &mut EncodeTimings::default())?;
+                        qualities.insert(*quality, entry.to_tier());
+                    }
+                }
+
+                let entry = write_asset(&mut data, &mut payloads, &mut manifest, id, &asset, options, &mut EncodeTimings::default())?;
+                entry.name = old_entry.name.clone();
+                entry.dependencies = old_entry.dependencies.clone();
+                entry.version = old_entry.version;
+                entry.kind = old_entry.kind.clone();
+                entry.preview = old_entry.preview.clone();
+                entry.metadata = old_entry.metadata.clone();
+                entry.tags = old_entry.tags.clone();
+                entry.locales = old_entry.locales.clone();
+                entry.qualities = qualities;
+                entry.copy_preview(self.module, &mut previews);
+            }
+        }
+
+        let kept = manifest.assets.keys().copied().collect::<FxHashSet<_>>();
+        for entry in manifest.assets.values_mut() {
+            entry.locales.retain(|_, id| kept.contains(id));
+        }
+
+        let encoded = EncodedAssets::from_parts(data, previews, manifest, FxHashMap::default(), options)?;
+        let mut output = self.strip_module()?;
+        append_asset_sections(&mut output, &encoded, &options.section_prefix);
+        Ok(output)
+    }
+}
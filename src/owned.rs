@@ -0,0 +1,115 @@
+use crate::*;
+use std::marker::*;
+use std::sync::*;
+
+/// Parses all assets from a WASM module which it owns, rather than borrows. This allows the
+/// parser to be stored in long-lived structures, such as the asset caches of an engine, without
+/// tying them to the lifetime of the module bytes. Cloning the parser is cheap, because the
+/// module and manifest are shared between clones. Any operation of [`WassetParser`] may be
+/// performed through [`WassetOwnedParser::parser`].
+///
+/// ```no_run
+/// # use wasset::*;
+/// # type MyAsset = Vec<u8>;
+/// # mod assets { pub const SOME_ASSET: wasset::WassetId = wasset::WassetId::from_bytes([1; 16]); }
+/// let parser = WassetParser::<MyAsset>::parse_owned(std::fs::read("plugin.wasm")?)?;
+/// let asset = parser.load(assets::SOME_ASSET)?;
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+pub struct WassetOwnedParser<A: AssetSchema> {
+    /// The module data itself.
+    module: Arc<[u8]>,
+    /// The manifest associated with the module.
+    manifest: Arc<WassetManifest>,
+    /// The options used to read assets.
+    options: Arc<WassetParseOptions>,
+    /// A marker type for `A`.
+    marker: PhantomData<fn(A)>
+}
+
+impl<A: AssetSchema> WassetOwnedParser<A> {
+    /// Attempts to parse the asset list from the given module.
+    pub fn parse(module: impl Into<Arc<[u8]>>) -> Result<Self, WassetError> {
+        Self::parse_with_options(module, WassetParseOptions::default())
+    }
+
+    /// Attempts to parse the asset list from the given module, using the provided options
+    /// when reading assets.
+    pub fn parse_with_options(module: impl Into<Arc<[u8]>>, options: WassetParseOptions) -> Result<Self, WassetError> {
+        let module = module.into();
+        let (manifest, options) = WassetParser::<A>::parse_with_options(&module, options)?.into_shared();
+        Ok(Self::from_parts(module, manifest, options))
+    }
+
+    /// Creates a parser from its constituent parts, which must all describe the same module.
+    pub(crate) fn from_parts(module: Arc<[u8]>, manifest: Arc<WassetManifest>, options: Arc<WassetParseOptions>) -> Self {
+        Self {
+            module,
+            manifest,
+            options,
+            marker: PhantomData
+        }
+    }
+
+    /// Gets a parser which borrows the module from this one, sharing its manifest.
+    pub fn parser(&self) -> WassetParser<'_, A> {
+        WassetParser::from_shared(&self.module, self.manifest.clone(), self.options.clone())
+    }
+
+    /// Gets an iterator over the IDs of all assets stored in the module.
+    pub fn ids(&self) -> impl '_ + Iterator<Item = WassetId> {
+        self.manifest.ids()
+    }
+
+    /// Loads the provided asset from the module, returning `None` if it
+    /// did not exist.
+    pub fn load(&self, id: WassetId) -> Result<Option<A>, WassetError> {
+        self.parser().load(id)
+    }
+
+    /// Loads the asset with the given ID and deserializes it as `T`, rather than the asset schema.
+    pub fn load_as<T: for<'de> Deserialize<'de>>(&self, id: WassetId) -> Result<Option<T>, WassetError> {
+        self.parser().load_as(id)
+    }
+
+    /// Loads the raw data associated with the given ID, returning `None` if it
+    /// did not exist.
+    pub fn load_raw(&self, id: WassetId) -> Result<Option<WassetItem<'_, A>>, WassetError> {
+        self.parser().load_raw(id)
+    }
+
+    /// Gets a reference to the module manifest.
+    pub fn manifest(&self) -> &WassetManifest {
+        &self.manifest
+    }
+
+    /// Gets the options that this parser uses to read assets.
+    pub fn options(&self) -> &WassetParseOptions {
+        &self.options
+    }
+
+    /// Gets the bytecode of the module.
+    pub fn module(&self) -> &Arc<[u8]> {
+        &self.module
+    }
+}
+
+impl<A: AssetSchema> Clone for WassetOwnedParser<A> {
+    fn clone(&self) -> Self {
+        Self::from_parts(self.module.clone(), self.manifest.clone(), self.options.clone())
+    }
+}
+
+impl<A: AssetSchema> WassetSource<A> for WassetOwnedParser<A> {
+    fn ids(&self) -> Box<dyn '_ + Iterator<Item = WassetId>> {
+        Box::new(self.manifest.ids())
+    }
+
+    fn contains(&self, id: WassetId) -> bool {
+        self.manifest.get(id).is_some()
+    }
+
+    fn load(&self, id: WassetId) -> Result<Option<A>, WassetError> {
+        WassetOwnedParser::load(self, id)
+    }
+}
@@ -0,0 +1,73 @@
+use crate::*;
+
+/// The bytes with which every standalone asset pack begins.
+pub const WASSET_PACK_MAGIC: [u8; 8] = *b"\0wasset\0";
+
+/// The revision of the asset pack container which this crate writes, and the newest that it reads.
+pub const WASSET_PACK_VERSION: u32 = 1;
+
+/// Appends a section to an asset pack which is being written. The section holds the length of
+/// its name, the name, and the length of its contents as little-endian integers, followed by the
+/// contents, which are padded to begin at a multiple of [`MAX_PAYLOAD_ALIGNMENT`].
+#[cfg(feature = "encode")]
+pub(crate) fn append_pack_section(pack: &mut Vec<u8>, name: &str, data: &[u8]) {
+    pack.extend_from_slice(&(name.len() as u32).to_le_bytes());
+    pack.extend_from_slice(name.as_bytes());
+    pack.extend_from_slice(&(data.len() as u32).to_le_bytes());
+    pack.resize(pack.len().next_multiple_of(MAX_PAYLOAD_ALIGNMENT as usize), 0);
+    pack.extend_from_slice(data);
+}
+
+/// Creates an empty asset pack, which holds only its header.
+#[cfg(feature = "encode")]
+pub(crate) fn new_pack() -> Vec<u8> {
+    let mut result = WASSET_PACK_MAGIC.to_vec();
+    result.extend_from_slice(&WASSET_PACK_VERSION.to_le_bytes());
+    result
+}
+
+/// Whether the given bytes hold a standalone asset pack, rather than a WASM module.
+#[cfg(feature = "parse")]
+pub(crate) fn is_asset_pack(bytes: &[u8]) -> bool {
+    bytes.starts_with(&WASSET_PACK_MAGIC)
+}
+
+/// Finds the asset manifest and data sections with the given prefix in a standalone asset pack,
+/// grouping them by the ID of each section pair.
+#[cfg(feature = "parse")]
+pub(crate) fn read_pack_sections<'a>(pack: &'a [u8], prefix: &str) -> Result<FxHashMap<Uuid, WassetOffsets<'a>>, WassetError> {
+    let mut rest = &pack[WASSET_PACK_MAGIC.len()..];
+    let version = take_u32(&mut rest)?;
+    if version > WASSET_PACK_VERSION {
+        return Err(WassetError::UnsupportedVersion { found: version, supported: WASSET_PACK_VERSION });
+    }
+
+    let mut offsets = FxHashMap::default();
+    while !rest.is_empty() {
+        let name_len = take_u32(&mut rest)?;
+        let name = std::str::from_utf8(take(&mut rest, name_len as usize)?).map_err(WassetError::from_deserialize)?;
+        let data_len = take_u32(&mut rest)?;
+
+        let position = pack.len() - rest.len();
+        take(&mut rest, position.next_multiple_of(MAX_PAYLOAD_ALIGNMENT as usize) - position)?;
+
+        let offset = pack.len() - rest.len();
+        parse_asset_section(name, take(&mut rest, data_len as usize)?, offset, prefix, &mut offsets)?;
+    }
+
+    Ok(offsets)
+}
+
+/// Removes the given number of bytes from the front of a pack which is being read.
+#[cfg(feature = "parse")]
+fn take<'a>(rest: &mut &'a [u8], len: usize) -> Result<&'a [u8], WassetError> {
+    let (result, remainder) = rest.split_at_checked(len).ok_or_else(|| WassetError::from_deserialize("Asset pack is truncated"))?;
+    *rest = remainder;
+    Ok(result)
+}
+
+/// Removes a little-endian integer from the front of a pack which is being read.
+#[cfg(feature = "parse")]
+fn take_u32(rest: &mut &[u8]) -> Result<u32, WassetError> {
+    take(rest, 4).map(|x| u32::from_le_bytes([x[0], x[1], x[2], x[3]]))
+}
@@ -1,28 +1,63 @@
 use crate::*;
+use ::serde::de::*;
+use std::collections::BTreeMap;
 use std::marker::*;
 use std::mem::*;
+use std::sync::*;
 use wasm_encoder::*;
 use wasmparser::*;
 
-/// References the raw data representing an asset from within a WASM module.
-#[derive(Copy, Clone, Debug, Default, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash)]
+/// References the raw data representing an asset from within a WASM module. Compressed
+/// assets are decompressed into an owned buffer.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct WassetItem<'a, A: AssetSchema> {
     /// The inner data.
-    data: &'a [u8],
+    data: Cow<'a, [u8]>,
+    /// The format in which the data was serialized.
+    codec: WassetCodec,
     /// A marker type for `A`.
     marker: PhantomData<fn(A)>
 }
 
 impl<'a, A: AssetSchema> WassetItem<'a, A> {
+    /// Gets the format in which the data was serialized. Items created from raw bytes
+    /// are assumed to hold MessagePack.
+    pub fn codec(&self) -> WassetCodec {
+        self.codec
+    }
+
+    /// Marks the data as serialized with the given codec.
+    pub fn with_codec(self, codec: WassetCodec) -> Self {
+        Self { codec, ..self }
+    }
+
     /// Deserializes the provided bytes as an asset.
     pub fn deserialize(&self) -> Result<A, WassetError> {
-        rmp_serde::from_slice(self.data).map_err(WassetError::from_deserialize)
+        self.deserialize_as()
+    }
+
+    /// Deserializes the provided bytes as an arbitrary type, rather than the asset schema.
+    pub fn deserialize_as<T: for<'de> Deserialize<'de>>(&self) -> Result<T, WassetError> {
+        self.codec.deserialize(&self.data)
+    }
+
+    /// Deserializes the provided bytes as a type which borrows from them, such as a schema
+    /// with `&str` or `&[u8]` fields. For assets which are stored verbatim, the result borrows
+    /// directly from the module.
+    pub fn deserialize_borrowed<'de, T: Deserialize<'de>>(&'de self) -> Result<T, WassetError> {
+        self.codec.deserialize(&self.data)
+    }
+
+    /// Deserializes the provided bytes using a stateful seed. This allows for decoding assets
+    /// into arena-allocated or interned representations, rather than fresh owned values.
+    pub fn deserialize_seed<'de, S: DeserializeSeed<'de>>(&'de self, seed: S) -> Result<S::Value, WassetError> {
+        self.codec.deserialize_seed(&self.data, seed)
     }
 }
 
 impl<'a, A: AssetSchema> Deref for WassetItem<'a, A> {
     type Target = [u8];
-    
+
     fn deref(&self) -> &Self::Target {
         &self.data
     }
@@ -31,78 +66,268 @@ impl<'a, A: AssetSchema> Deref for WassetItem<'a, A> {
 impl<'a, A: AssetSchema> From<&'a [u8]> for WassetItem<'a, A> {
     fn from(value: &'a [u8]) -> Self {
         Self {
-            data: value,
+            data: Cow::Borrowed(value),
+            codec: WassetCodec::default(),
             marker: PhantomData
         }
     }
 }
 
+impl<'a, A: AssetSchema> From<Vec<u8>> for WassetItem<'a, A> {
+    fn from(value: Vec<u8>) -> Self {
+        Self {
+            data: Cow::Owned(value),
+            codec: WassetCodec::default(),
+            marker: PhantomData
+        }
+    }
+}
+
+/// Configures how a [`WassetParser`] reads assets from a module.
+#[derive(Clone, Debug)]
+pub struct WassetParseOptions {
+    /// The maximum decoded size of a single asset, in bytes. Loading a larger asset
+    /// fails with [`WassetError::LimitExceeded`], which protects hosts from untrusted
+    /// modules that attempt to exhaust memory. The limit applies to both the stored and
+    /// decompressed size of each asset.
+    pub max_asset_size: u64,
+    /// Determines what happens when several sets of asset sections provide the same asset.
+    pub duplicate_ids: DuplicateIdPolicy,
+    /// The key with which encrypted assets are decrypted. Loading an encrypted asset
+    /// without the key that was used to encode it fails.
+    #[cfg(feature = "encryption")]
+    pub encryption_key: Option<WassetEncryptionKey>,
+    /// The schema fingerprint which every manifest must record, if any. Parsing fails with
+    /// [`WassetError::SchemaMismatch`] if the assets of a module were encoded with a different
+    /// `WassetEncodeOptions::schema`. Indexed modules do not read manifests, so they are not checked.
+    pub schema: Option<String>,
+    /// The prefix of the custom section names from which assets are read. Only sections
+    /// written with the same prefix are recognized, which allows independent asset systems
+    /// to coexist within one module.
+    pub section_prefix: String
+}
+
+impl WassetParseOptions {
+    /// The default maximum asset size of 256 MiB.
+    pub const DEFAULT_MAX_ASSET_SIZE: u64 = 256 * 1024 * 1024;
+}
+
+impl Default for WassetParseOptions {
+    fn default() -> Self {
+        Self {
+            max_asset_size: Self::DEFAULT_MAX_ASSET_SIZE,
+            duplicate_ids: DuplicateIdPolicy::default(),
+            #[cfg(feature = "encryption")]
+            encryption_key: None,
+            schema: None,
+            section_prefix: DEFAULT_SECTION_PREFIX.to_string()
+        }
+    }
+}
+
+/// Determines which entry is kept when several sets of asset sections within a module provide
+/// the same [`WassetId`]. Sets are considered in the order in which their data sections appear
+/// in the module.
+#[derive(Copy, Clone, Debug, Default)]
+pub enum DuplicateIdPolicy {
+    /// Parsing fails with [`WassetError::DuplicateId`].
+    #[default]
+    Error,
+    /// The entry from the set which appears first is kept.
+    FirstWins,
+    /// The entry from the set which appears last is kept.
+    LastWins,
+    /// The given function is called with the ID, the entry kept so far, and the entry from the
+    /// later set. It returns whether the later entry should replace the earlier one, or an
+    /// error which causes parsing to fail.
+    Custom(fn(WassetId, &WassetManifestEntry, &WassetManifestEntry) -> Result<bool, WassetError>)
+}
+
+impl DuplicateIdPolicy {
+    /// Determines whether the `later` entry for an asset should replace the `earlier` one.
+    pub(crate) fn replaces(&self, id: WassetId, earlier: &WassetManifestEntry, later: &WassetManifestEntry) -> Result<bool, WassetError> {
+        match self {
+            Self::Error => Err(WassetError::DuplicateId(id)),
+            Self::FirstWins => Ok(false),
+            Self::LastWins => Ok(true),
+            Self::Custom(resolve) => resolve(id, earlier, later)
+        }
+    }
+}
+
+/// Configures which custom sections are removed by [`WassetParser::strip_module_with_options`].
+/// The default options remove every asset section and keep all other custom sections.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct WassetStripOptions {
+    /// Whether asset manifests, along with their indices, should be kept while their data
+    /// sections are removed. Note that a [`WassetParser`] cannot load a module whose manifests
+    /// are missing their data, so this is intended for tooling which inspects manifests directly.
+    /// [`WassetParser::strip_data`] produces a module whose manifests remain parseable.
+    pub keep_manifests: bool,
+    /// The IDs of the asset section pairs to remove, as returned by [`WassetParser::section_ids`].
+    /// If this is `None`, all asset sections are removed.
+    pub sections: Option<Vec<Uuid>>,
+    /// Determines which non-asset custom sections, such as the `name` section
+    /// or DWARF `.debug_*` sections, are removed.
+    pub other_sections: CustomSectionFilter
+}
+
+impl WassetStripOptions {
+    /// Determines whether the asset section set with the given ID is selected for removal.
+    pub(crate) fn is_selected(&self, id: &str) -> bool {
+        self.sections.as_ref().is_none_or(|x| Uuid::try_parse(id).is_ok_and(|id| x.contains(&id)))
+    }
+}
+
+/// Selects custom sections by name prefix.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub enum CustomSectionFilter {
+    /// All sections are kept.
+    #[default]
+    KeepAll,
+    /// All sections are removed.
+    DropAll,
+    /// Sections whose names begin with any of the given prefixes are removed.
+    Drop(Vec<String>),
+    /// Only sections whose names begin with any of the given prefixes are kept.
+    Keep(Vec<String>)
+}
+
 /// Parses all assets from a WASM module.
 pub struct WassetParser<'a, A: AssetSchema> {
-    /// The manifest associated with the module.
-    manifest: WassetManifest,
+    /// The manifest associated with the module, which is shared with any owned parser.
+    manifest: Arc<WassetManifest>,
     /// The module data itself.
-    module: &'a [u8],
+    pub(crate) module: &'a [u8],
+    /// The options used to read assets, which are shared with any owned parser.
+    options: Arc<WassetParseOptions>,
     /// A marker type for `A`.
     marker: PhantomData<fn(A)>
 }
 
 impl<'a, A: AssetSchema> WassetParser<'a, A> {
-    /// The custom section name prefix for serialized manifests.
-    const ASSET_MANIFEST_SECTION_PREFIX: &'static str = "__wasset_manifest:";
-    /// The custom section name prefix for serialized asset data.
-    const ASSET_DATA_SECTION_PREFIX: &'static str = "__wasset_data:";
-
-    /// Attempts to parse the asset list from the given module.
+    /// Attempts to parse the asset list from the given module. The module may also be a
+    /// standalone asset pack, as produced by `WassetModuleBuilder::build_pack`, or a component,
+    /// in which case the assets of every core module nested within it are read.
     pub fn parse(module: &'a [u8]) -> Result<Self, WassetError> {
-        let mut contents = module;
-        let mut parser = Parser::new(0);
-        let mut offsets = FxHashMap::default();
-
-        loop {
-            let payload = match parser.parse(contents, true).map_err(WassetError::from_deserialize)? {
-                Chunk::Parsed { consumed, payload } => {
-                    contents = &contents[consumed..];
-                    payload
-                }
-                // this state isn't possible with `eof = true`
-                Chunk::NeedMoreData(_) => unreachable!(),
-            };
+        Self::parse_with_options(module, WassetParseOptions::default())
+    }
 
-            match payload {
-                Payload::CodeSectionStart { size, .. } => {
-                    parser.skip_section();
-                    contents = &contents[size as usize..];
-                }
-                Payload::CustomSection(c) => Self::parse_module_custom_section(c, &mut offsets)?,
-                Payload::End(_) => break,
-                _ => {}
-            }
-        }
+    /// Attempts to parse the asset list from the given module, using the provided options
+    /// when reading assets.
+    #[cfg_attr(feature = "tracing", tracing::instrument(name = "wasset::parse", level = "debug", skip_all, fields(module_size = module.len())))]
+    pub fn parse_with_options(module: &'a [u8], options: WassetParseOptions) -> Result<Self, WassetError> {
+        let offsets = read_asset_sections(module, &options.section_prefix)?;
+        let manifest = Self::collect_manifests(offsets, &options)?;
+
+        #[cfg(feature = "tracing")]
+        tracing::debug!(assets = manifest.assets.len(), "parsed asset manifests");
 
-        let manifest = Self::collect_manifests(offsets)?;
         Ok(Self {
-            manifest,
+            manifest: Arc::new(manifest),
             module,
+            options: Arc::new(options),
             marker: PhantomData
         })
     }
 
+    /// Creates a parser from a manifest that was previously collected from the same module.
+    pub(crate) fn from_manifest(module: &'a [u8], manifest: WassetManifest, options: WassetParseOptions) -> Self {
+        Self::from_shared(module, Arc::new(manifest), Arc::new(options))
+    }
+
+    /// Creates a parser from a manifest and options that are shared with another parser over the same module.
+    pub(crate) fn from_shared(module: &'a [u8], manifest: Arc<WassetManifest>, options: Arc<WassetParseOptions>) -> Self {
+        Self {
+            manifest,
+            module,
+            options,
+            marker: PhantomData
+        }
+    }
+
+    /// Gets the manifest and options of this parser, so that they may be shared with another parser over the same module.
+    pub(crate) fn into_shared(self) -> (Arc<WassetManifest>, Arc<WassetParseOptions>) {
+        (self.manifest, self.options)
+    }
+
+    /// Attempts to parse the asset list from the given module, producing a parser which
+    /// owns the module rather than borrowing it.
+    pub fn parse_owned(module: impl Into<Arc<[u8]>>) -> Result<WassetOwnedParser<A>, WassetError> {
+        WassetOwnedParser::parse(module)
+    }
+
+    /// Copies the module into a parser which owns it, reusing the manifest of this parser.
+    pub fn to_owned_parser(&self) -> WassetOwnedParser<A> {
+        WassetOwnedParser::from_parts(self.module.into(), self.manifest.clone(), self.options.clone())
+    }
+
     /// Gets an iterator over the IDs of all assets stored in the module.
     pub fn ids(&self) -> impl '_ + Iterator<Item = WassetId> {
-        self.manifest.asset_ranges.keys().copied()
+        self.manifest.ids()
     }
 
     /// Creates an iterator over the IDs and assets in this parser.
-    pub fn iter(&self) -> WassetIter<A> {
+    pub fn iter(&self) -> WassetIter<'_, A> {
         self.into_iter()
     }
 
     /// Loads the provided asset from the module, returning `None` if it
     /// did not exist.
     pub fn load(&self, id: WassetId) -> Result<Option<A>, WassetError> {
-        if let Some(range) = self.manifest.asset_ranges.get(&id) {
-            Ok(Some(self.load_by_range(range.clone())?.deserialize()?))
+        self.load_as(id)
+    }
+
+    /// Loads the asset with the given ID as a type which borrows from the module, such as a
+    /// schema with `&'a str` or `&'a [u8]` fields, without copying any of its data. The asset
+    /// must be stored uncompressed and unencrypted; other assets may be loaded with
+    /// [`Self::load_raw`] and [`WassetItem::deserialize_borrowed`] instead. Returns `None`
+    /// if the asset did not exist.
+    pub fn load_borrowed<T: Deserialize<'a>>(&self, id: WassetId) -> Result<Option<T>, WassetError> {
+        let Some(entry) = self.manifest.get(id) else {
+            return Ok(None);
+        };
+
+        if !entry.is_stored_verbatim() {
+            return Err(WassetError::from_deserialize(format!("Asset {id:?} is compressed or encrypted and cannot be borrowed from the module")));
+        }
+
+        entry.codec().deserialize(self.stored_data(entry)?).map(Some)
+    }
+
+    /// Loads the asset with the given ID as an untyped MessagePack value. This allows for
+    /// inspecting the structure of an asset without access to its schema, such as when
+    /// debugging schema mismatches.
+    #[cfg(feature = "rmpv")]
+    pub fn load_value(&self, id: WassetId) -> Result<Option<rmpv::Value>, WassetError> {
+        if let Some(entry) = self.manifest.get(id) {
+            if !entry.codec().is_message_pack() {
+                return Err(WassetError::from_deserialize(format!("Asset {id:?} was not serialized as MessagePack")));
+            }
+
+            let item = self.load_entry(entry)?;
+            Ok(Some(rmpv::decode::read_value(&mut &item[..]).map_err(WassetError::from_deserialize)?))
+        }
+        else {
+            Ok(None)
+        }
+    }
+
+    /// Loads every asset with one of the given IDs, visiting them in the order in which they
+    /// are stored so that the module is read sequentially. This suits preloading many assets
+    /// at once. IDs which do not exist are omitted from the result.
+    pub fn load_many(&self, ids: &[WassetId]) -> Result<FxHashMap<WassetId, A>, WassetError> {
+        self.manifest.entries_by_offset(ids).into_iter()
+            .map(|(id, entry)| Ok((id, self.load_entry(entry)?.deserialize()?)))
+            .collect()
+    }
+
+    /// Loads the asset with the given ID and deserializes it as `T`, rather than the asset schema.
+    /// This allows hosts which store different concrete types for different kinds of assets
+    /// to decode each asset directly into its target type.
+    pub fn load_as<T: for<'de> Deserialize<'de>>(&self, id: WassetId) -> Result<Option<T>, WassetError> {
+        if let Some(entry) = self.manifest.get(id) {
+            Ok(Some(self.load_entry(entry)?.deserialize_as()?))
         }
         else {
             Ok(None)
@@ -111,107 +336,704 @@ impl<'a, A: AssetSchema> WassetParser<'a, A> {
 
     /// Loads the raw data associated with the given ID, returning `None` if it
     /// did not exist.
-    pub fn load_raw(&self, id: WassetId) -> Result<Option<WassetItem<A>>, WassetError> {
-        if let Some(range) = self.manifest.asset_ranges.get(&id) {
-            Ok(Some(self.load_by_range(range.clone())?))
+    pub fn load_raw(&self, id: WassetId) -> Result<Option<WassetItem<'a, A>>, WassetError> {
+        if let Some(entry) = self.manifest.get(id) {
+            Ok(Some(self.load_entry(entry)?))
         }
         else {
             Ok(None)
         }
     }
 
+    /// Gets the metadata attached to the asset with the given ID by its encoder or `Wasset.toml` file,
+    /// without loading the asset. Returns `None` if the asset did not exist.
+    pub fn metadata(&self, id: WassetId) -> Option<&BTreeMap<String, String>> {
+        self.manifest.get(id).map(WassetManifestEntry::metadata)
+    }
+
+    /// Gets the IDs of all assets which were declared with the given tag in their `Wasset.toml` files,
+    /// sorted by ID. This allows for preloading a category of assets, such as all UI textures,
+    /// without decoding any of them.
+    pub fn ids_with_tag(&self, tag: &str) -> Vec<WassetId> {
+        self.manifest.ids_with_tag(tag)
+    }
+
+    /// Loads the variant of the asset with the given ID for a locale, such as `de-AT`. If the asset
+    /// has no variant for the locale, its parent locales are tried in turn, such as `de`, before
+    /// falling back to the asset itself. Returns `None` if the asset did not exist.
+    pub fn load_localized(&self, id: WassetId, locale: &str) -> Result<Option<A>, WassetError> {
+        match self.localized_id(id, locale) {
+            Some(id) => self.load(id),
+            None => Ok(None)
+        }
+    }
+
+    /// Loads the asset with the given ID at a quality tier, so that a single module may serve both
+    /// low-end and high-end targets. The tiers are stored as separate ranges under the same ID, and
+    /// the tier is chosen as by [`WassetManifestEntry::variant`]. Returns `None` if the asset did not exist.
+    pub fn load_variant(&self, id: WassetId, quality: Quality) -> Result<Option<A>, WassetError> {
+        if let Some(entry) = self.manifest.get(id) {
+            Ok(Some(self.load_entry(entry.variant(quality))?.deserialize()?))
+        }
+        else {
+            Ok(None)
+        }
+    }
+
+    /// Finds the asset which [`Self::load_localized`] would load for a locale, without loading it.
+    pub fn localized_id(&self, id: WassetId, locale: &str) -> Option<WassetId> {
+        self.manifest.localized_id(id, locale)
+    }
+
+    /// Gets the hash of the serialized asset with the given ID, returning `None` if the
+    /// asset did not exist or no hash was recorded. Assets with equal hashes are
+    /// byte-identical, even if they have different IDs or reside in different modules.
+    pub fn content_hash(&self, id: WassetId) -> Option<WassetHash> {
+        self.manifest.get(id).and_then(WassetManifestEntry::hash)
+    }
+
+    /// Checks that the asset with the given ID is intact by hashing its serialized data and comparing
+    /// the result against the hash recorded during encoding. This allows for detecting truncated or
+    /// tampered modules, such as those received over a network, before any asset is deserialized.
+    /// Every quality tier of the asset is checked. Fails with [`WassetError::IntegrityMismatch`] if
+    /// the data does not match, or with another error if compressed data cannot be decompressed.
+    /// Returns `false` if the asset did not exist or has no recorded hash, in which case it cannot
+    /// be verified.
+    #[cfg(feature = "verify")]
+    pub fn verify(&self, id: WassetId) -> Result<bool, WassetError> {
+        self.verify_into(id, &mut Vec::new())
+    }
+
+    /// Checks every asset in the module as by [`Self::verify`], failing upon the first asset
+    /// which does not match its recorded hash. Returns the number of assets which were verified.
+    #[cfg(feature = "verify")]
+    pub fn verify_all(&self) -> Result<usize, WassetError> {
+        let mut buffer = Vec::new();
+        let mut verified = 0;
+        for id in self.ids() {
+            verified += self.verify_into(id, &mut buffer)? as usize;
+        }
+
+        Ok(verified)
+    }
+
+    /// Gets the name under which the asset with the given ID was encoded, returning `None`
+    /// if the asset did not exist or was not encoded from a file.
+    pub fn name(&self, id: WassetId) -> Option<&str> {
+        self.manifest.get(id).and_then(WassetManifestEntry::name)
+    }
+
+    /// Finds the ID of the asset with the given name, such as `textures/player`, returning `None`
+    /// if no asset has that name. See [`WassetManifest::lookup`] for the accepted forms.
+    pub fn lookup(&self, name: &str) -> Option<WassetId> {
+        self.manifest.lookup(name)
+    }
+
+    /// Loads the raw data of the asset with the given ID into `buffer`, replacing its contents.
+    /// Returns `false` if the asset did not exist. Reusing the same buffer across loads avoids
+    /// allocating for each asset.
+    pub fn load_raw_into(&self, id: WassetId, buffer: &mut Vec<u8>) -> Result<bool, WassetError> {
+        let Some(entry) = self.manifest.get(id) else {
+            return Ok(false);
+        };
+
+        self.load_entry_into(entry, buffer)?;
+        Ok(true)
+    }
+
+    /// Loads the raw data of the asset with the given ID into `buffer`, and then deserializes it
+    /// using a stateful seed. The result may borrow from `buffer`, which is reused across loads.
+    pub fn load_seed_into<'b, S: DeserializeSeed<'b>>(&self, id: WassetId, buffer: &'b mut Vec<u8>, seed: S) -> Result<Option<S::Value>, WassetError> {
+        let Some(codec) = self.manifest.get(id).map(WassetManifestEntry::codec) else {
+            return Ok(None);
+        };
+
+        self.load_raw_into(id, buffer)?;
+        codec.deserialize_seed(buffer, seed).map(Some)
+    }
+
+    /// Views the payload of the asset with the given ID as a slice of plain-old-data values,
+    /// without copying or decoding it. The asset must be stored uncompressed, and must have been
+    /// serialized as a MessagePack byte array, such as by a schema that uses `serde_bytes`. Fails
+    /// if the length or alignment of the payload within the module does not suit `T`. Returns
+    /// `None` if the asset did not exist.
+    #[cfg(feature = "bytemuck")]
+    pub fn load_pod<T: Pod>(&self, id: WassetId) -> Result<Option<&'a [T]>, WassetError> {
+        self.pod_payload(id)?.map(|x| try_cast_slice(x).map_err(|e| WassetError::from_deserialize(format!("Asset {id:?} cannot be viewed as {}: {e}", std::any::type_name::<T>())))).transpose()
+    }
+
+    /// Views the payload of the asset with the given ID as a single plain-old-data value, without
+    /// copying or decoding it. The same requirements apply as for [`Self::load_pod`], and the
+    /// payload must additionally be exactly the size of `T`.
+    #[cfg(feature = "bytemuck")]
+    pub fn load_pod_value<T: Pod>(&self, id: WassetId) -> Result<Option<&'a T>, WassetError> {
+        self.pod_payload(id)?.map(|x| try_from_bytes(x).map_err(|e| WassetError::from_deserialize(format!("Asset {id:?} cannot be viewed as {}: {e}", std::any::type_name::<T>())))).transpose()
+    }
+
+    /// Gets the bytes within the MessagePack byte array that an asset stored verbatim holds.
+    #[cfg(feature = "bytemuck")]
+    fn pod_payload(&self, id: WassetId) -> Result<Option<&'a [u8]>, WassetError> {
+        let Some(entry) = self.manifest.get(id) else {
+            return Ok(None);
+        };
+
+        if !entry.is_stored_verbatim() {
+            return Err(WassetError::from_deserialize(format!("Asset {id:?} is compressed or encrypted and cannot be viewed in place")));
+        }
+
+        if !entry.codec().is_message_pack() {
+            return Err(WassetError::from_deserialize(format!("Asset {id:?} was not serialized as MessagePack")));
+        }
+
+        let stored = self.stored_data(entry)?;
+        let (header, len) = match stored {
+            [0xc4, len, ..] => (2, *len as usize),
+            [0xc5, a, b, ..] => (3, u16::from_be_bytes([*a, *b]) as usize),
+            [0xc6, a, b, c, d, ..] => (5, u32::from_be_bytes([*a, *b, *c, *d]) as usize),
+            _ => return Err(WassetError::from_deserialize(format!("Asset {id:?} was not serialized as a byte array")))
+        };
+
+        stored.get(header..).filter(|x| x.len() == len).map(Some)
+            .ok_or_else(|| WassetError::from_deserialize(format!("Asset {id:?} has a malformed byte array")))
+    }
+
+    /// Gets the version of the asset with the given ID, returning `None` if the
+    /// asset did not exist or no version was recorded.
+    pub fn version(&self, id: WassetId) -> Option<u64> {
+        self.manifest.get(id).and_then(WassetManifestEntry::version)
+    }
+
+    /// Gets the preview of the asset with the given ID, which was produced by
+    /// the asset's encoder. Returns `None` if the asset did not exist or has no preview.
+    pub fn preview(&self, id: WassetId) -> Option<&'a [u8]> {
+        let preview = self.manifest.get(id)?.preview.clone()?;
+        self.module.get(preview.start as usize..preview.end as usize)
+    }
+
+    /// Gets the kind of the asset with the given ID, returning `None` if the
+    /// asset did not exist or its encoder did not report a kind.
+    pub fn kind(&self, id: WassetId) -> Option<&str> {
+        self.manifest.get(id).and_then(WassetManifestEntry::kind)
+    }
+
+    /// Gets an iterator over the IDs of all assets of the given kind.
+    pub fn ids_of_kind<'b>(&'b self, kind: &'b str) -> impl 'b + Iterator<Item = WassetId> {
+        self.manifest.iter().filter(move |(_, entry)| entry.kind() == Some(kind)).map(|(id, _)| id)
+    }
+
+    /// Gets the IDs of the assets upon which the asset with the given ID depends,
+    /// returning `None` if the asset did not exist.
+    pub fn dependencies(&self, id: WassetId) -> Option<&[WassetId]> {
+        self.manifest.get(id).map(WassetManifestEntry::dependencies)
+    }
+
+    /// Determines the order in which the given assets and all of their transitive dependencies
+    /// should be loaded, such that every asset comes after the assets upon which it depends.
+    /// This allows, for instance, the textures of a material to be preloaded before the material.
+    /// See [`WassetManifest::load_order`] for details.
+    pub fn load_order(&self, ids: impl IntoIterator<Item = WassetId>) -> Result<Vec<WassetId>, WassetError> {
+        self.manifest.load_order(ids)
+    }
+
+    /// Gets the IDs of all assets in the module, ordered so that every asset comes after
+    /// the assets upon which it depends.
+    pub fn ids_by_dependency(&self) -> Result<Vec<WassetId>, WassetError> {
+        self.manifest.ids_by_dependency()
+    }
+
+    /// Gets the number of bytes that the asset with the given ID occupies in the module,
+    /// returning `None` if the asset did not exist.
+    pub fn compressed_size(&self, id: WassetId) -> Option<u32> {
+        self.manifest.get(id).map(WassetManifestEntry::compressed_size)
+    }
+
+    /// Gets the size of the asset with the given ID after decompression. This is the length
+    /// of the data returned by [`Self::load_raw`]. Returns `None` if the asset did not exist
+    /// or its size was not recorded.
+    pub fn uncompressed_size(&self, id: WassetId) -> Option<u32> {
+        self.manifest.get(id).and_then(WassetManifestEntry::uncompressed_size)
+    }
+
     /// Gets a reference to the module manifest.
     pub fn manifest(&self) -> &WassetManifest {
         &self.manifest
     }
 
-    /// Returns the WASM module bytecode with any custom asset sections removed.
-    pub fn strip_module(&self) -> Result<Vec<u8>, WassetError> {
-        let mut output = Vec::new();
-        let mut stack = Vec::new();
+    /// Gets the options that this parser uses to read assets.
+    pub fn options(&self) -> &WassetParseOptions {
+        &self.options
+    }
 
-        for payload in Parser::new(0).parse_all(self.module) {
-            let payload = payload.map_err(WassetError::from_deserialize)?;
+    /// Gets the IDs of all asset section pairs in the module. Each pair corresponds to
+    /// one set of encoded assets, such as a single `include_assets` invocation.
+    pub fn section_ids(&self) -> Result<Vec<Uuid>, WassetError> {
+        Ok(read_asset_sections(self.module, &self.options.section_prefix)?.into_keys().collect())
+    }
 
-            // Track nesting depth, so that we don't mess with inner producer sections:
-            match payload {
-                Payload::Version { .. } => output.extend_from_slice(&Module::HEADER),
-                Payload::ModuleSection { .. } => {
-                    stack.push(take(&mut output));
-                    continue;
-                }
-                Payload::End { .. } => {
-                    let mut parent = match stack.pop() {
-                        Some(c) => c,
-                        None => break,
-                    };
-                    
-                    parent.push(ComponentSectionId::CoreModule as u8);
-                    output.encode(&mut parent);
-                    
-                    output = parent;
+    /// Gets the IDs of the asset section pairs which hold the assets of the given top-level
+    /// subfolder, as encoded with `split_subfolders`. These may be passed to
+    /// [`WassetStripOptions::sections`] in order to remove the subfolder from the module.
+    pub fn partition_sections(&self, partition: &str) -> Result<Vec<Uuid>, WassetError> {
+        let mut result = Vec::new();
+        for (section_id, offsets) in read_asset_sections(self.module, &self.options.section_prefix)? {
+            if let Some(manifest) = offsets.manifest {
+                let manifest = WassetManifest::from_bytes(manifest)?;
+                if manifest.partition() == Some(partition) {
+                    result.push(section_id);
                 }
-                _ => {}
             }
+        }
 
-            match &payload {
-                Payload::CustomSection(c) => {
-                    if c.name().starts_with(Self::ASSET_MANIFEST_SECTION_PREFIX)
-                        || c.name().starts_with(Self::ASSET_DATA_SECTION_PREFIX) {
-                        continue;
-                    }
-                }
-                _ => {}
-            }
+        Ok(result)
+    }
 
-            if let Some((id, range)) = payload.as_section() {
-                RawSection {
-                    id,
-                    data: &self.module[range],
-                }.append_to(&mut output);
+    /// Returns the WASM module bytecode with any custom asset sections removed.
+    pub fn strip_module(&self) -> Result<Vec<u8>, WassetError> {
+        self.strip_module_with_options(&WassetStripOptions::default())
+    }
+
+    /// Returns the WASM module bytecode with the custom sections selected by `options` removed.
+    #[cfg_attr(feature = "tracing", tracing::instrument(name = "wasset::strip_module", level = "debug", skip_all, fields(module_size = self.module.len())))]
+    pub fn strip_module_with_options(&self, options: &WassetStripOptions) -> Result<Vec<u8>, WassetError> {
+        rewrite_custom_sections(self.module, |section| (!self.should_strip(section.name(), options)).then_some(Cow::Borrowed(section.data())))
+    }
+
+    /// Determines whether the custom section with the given name should be removed during stripping.
+    pub(crate) fn should_strip(&self, name: &str, options: &WassetStripOptions) -> bool {
+        if let Some((kind, id)) = parse_section_name(name, &self.options.section_prefix) {
+            options.is_selected(id) && !(matches!(kind, AssetSectionKind::Manifest | AssetSectionKind::Index) && options.keep_manifests)
+        }
+        else {
+            match &options.other_sections {
+                CustomSectionFilter::KeepAll => false,
+                CustomSectionFilter::DropAll => true,
+                CustomSectionFilter::Drop(prefixes) => prefixes.iter().any(|x| name.starts_with(x.as_str())),
+                CustomSectionFilter::Keep(prefixes) => !prefixes.iter().any(|x| name.starts_with(x.as_str()))
             }
         }
+    }
+
+    /// Loads an asset from the module, decrypting and decompressing it if necessary.
+    #[cfg_attr(feature = "tracing", tracing::instrument(name = "wasset::load", level = "trace", skip_all, fields(size = entry.compressed_size(), compression = ?entry.compression())))]
+    pub(crate) fn load_entry(&self, entry: &WassetManifestEntry) -> Result<WassetItem<'a, A>, WassetError> {
+        let slice = self.stored_data(entry)?;
+        if entry.is_stored_verbatim() {
+            return Ok(WassetItem::from(slice).with_codec(entry.codec()));
+        }
 
-        Ok(output)
+        let mut data = Vec::new();
+        decompress_entry(entry, slice, &self.options, &mut data)?;
+        Ok(WassetItem::from(data).with_codec(entry.codec()))
     }
 
-    /// Loads an asset from the provided byte range in the module.
-    fn load_by_range(&self, range: Range<u32>) -> Result<WassetItem<A>, WassetError> {
-        if let Some(slice) = self.module.get(range.start as usize..range.end as usize) {
-            Ok(WassetItem::from(slice))
+    /// Loads the raw data of an asset into `buffer`, replacing its contents, and decrypting
+    /// and decompressing it if necessary.
+    fn load_entry_into(&self, entry: &WassetManifestEntry, buffer: &mut Vec<u8>) -> Result<(), WassetError> {
+        let stored = self.stored_data(entry)?;
+        if entry.is_stored_verbatim() {
+            buffer.clear();
+            buffer.extend_from_slice(stored);
+            Ok(())
         }
         else {
-            Err(WassetError::from_deserialize("index out of range"))
+            decompress_entry(entry, stored, &self.options, buffer)
+        }
+    }
+
+    /// Verifies the asset with the given ID as by [`Self::verify`], decompressing it into `buffer`.
+    /// The payloads of the asset's other quality tiers are verified as well.
+    #[cfg(feature = "verify")]
+    fn verify_into(&self, id: WassetId, buffer: &mut Vec<u8>) -> Result<bool, WassetError> {
+        let Some(entry) = self.manifest.get(id) else {
+            return Ok(false);
+        };
+
+        let mut verified = false;
+        for (payload, hash) in entry.payloads().filter_map(|x| x.hash().map(|hash| (x, hash))) {
+            self.load_entry_into(payload, buffer)?;
+            if hash.as_bytes() != blake3::hash(buffer).as_bytes() {
+                return Err(WassetError::IntegrityMismatch(id));
+            }
+
+            verified = true;
         }
+
+        Ok(verified)
+    }
+
+    /// Gets the stored bytes of an asset, ensuring that they do not exceed the size limit.
+    pub(crate) fn stored_data(&self, entry: &WassetManifestEntry) -> Result<&'a [u8], WassetError> {
+        entry.check_local()?;
+        let range = entry.range();
+        let size = range.len() as u64;
+        if size > self.options.max_asset_size {
+            return Err(WassetError::LimitExceeded { size, limit: self.options.max_asset_size });
+        }
+
+        self.module.get(range.start as usize..range.end as usize)
+            .ok_or_else(|| WassetError::from_deserialize("index out of range"))
     }
 
     /// Folds all of the manifest data into one big manifest, taking the offset
-    /// of each custom section into account.
-    fn collect_manifests(offsets: FxHashMap<Uuid, WassetOffsets>) -> Result<WassetManifest, WassetError> {
+    /// of each custom section into account. Manifests and data sections may appear
+    /// in any order within the module, so this only runs after every section has been seen.
+    /// Assets provided by more than one set of sections are resolved as the options dictate, and
+    /// every manifest must record the schema fingerprint that the options expect.
+    pub(crate) fn collect_manifests(offsets: FxHashMap<Uuid, WassetOffsets>, options: &WassetParseOptions) -> Result<WassetManifest, WassetError> {
+        let mut sets = offsets.into_iter()
+            .map(|(section_id, manifest_offset)| {
+                let manifest_data = manifest_offset.manifest
+                    .ok_or(WassetError::IncompleteSections { section: section_id, missing: WassetSectionPart::Manifest })?;
+                let manifest_instance = WassetManifest::from_bytes(manifest_data)?;
+                if manifest_offset.data.is_none() && !manifest_instance.external_data {
+                    return Err(WassetError::IncompleteSections { section: section_id, missing: WassetSectionPart::Data });
+                }
+
+                Ok((section_id, manifest_offset, manifest_instance))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        sets.sort_by_key(|(_, offsets, _)| offsets.data.map(|x| x.offset));
+
         let mut manifest = WassetManifest::default();
-        for manifest_offset in offsets.into_values() {
-            let manifest_instance = rmp_serde::from_slice::<WassetManifest>(manifest_offset.manifest).map_err(WassetError::from_deserialize)?;
-            for (id, range) in manifest_instance.asset_ranges {
-                manifest.asset_ranges.insert(id, range.start + manifest_offset.data_offset..range.end + manifest_offset.data_offset);
+        let mut schema = None;
+        for (section_id, manifest_offset, manifest_instance) in sets {
+            if let Some(expected) = options.schema.as_ref().filter(|x| manifest_instance.schema.as_ref() != Some(*x)) {
+                return Err(WassetError::SchemaMismatch { section: section_id, expected: expected.clone(), found: manifest_instance.schema });
+            }
+
+            schema = match schema {
+                None => Some(manifest_instance.schema),
+                Some(x) if x == manifest_instance.schema => Some(x),
+                Some(_) => Some(None)
+            };
+
+            manifest.external_data |= manifest_instance.external_data;
+            for (id, mut entry) in manifest_instance.assets {
+                match manifest_offset.data {
+                    Some(data) => entry.relocate(|range| {
+                        if range.start > range.end || range.end > data.len {
+                            return Err(WassetError::from_deserialize(format!("Asset {id:?} lies outside of data section {section_id}")));
+                        }
+
+                        Ok(range.start + data.offset)
+                    })?,
+                    None => {
+                        entry.external = true;
+                        entry.qualities.values_mut().for_each(|x| x.external = true);
+                    }
+                }
+
+                if let Some(preview) = &mut entry.preview {
+                    let section = manifest_offset.preview.filter(|x| preview.start <= preview.end && preview.end <= x.len)
+                        .ok_or_else(|| WassetError::from_deserialize(format!("Preview of asset {id:?} lies outside of preview section {section_id}")))?;
+                    *preview = preview.start + section.offset..preview.end + section.offset;
+                }
+
+                if let Some(earlier) = manifest.assets.get(&id) {
+                    if !options.duplicate_ids.replaces(id, earlier, &entry)? {
+                        continue;
+                    }
+                }
+                manifest.assets.insert(id, entry);
             }
         }
+
+        manifest.schema = schema.flatten();
         Ok(manifest)
     }
+}
+
+/// Decrypts and decompresses the stored bytes of an asset into `output`, replacing its contents,
+/// and ensuring that the result does not exceed the size limit of the given options.
+pub(crate) fn decompress_entry(entry: &WassetManifestEntry, stored: &[u8], options: &WassetParseOptions, output: &mut Vec<u8>) -> Result<(), WassetError> {
+    let expected_size = entry.uncompressed_size();
+    if let Some(size) = expected_size.map(u64::from).filter(|x| *x > options.max_asset_size) {
+        return Err(WassetError::LimitExceeded { size, limit: options.max_asset_size });
+    }
+
+    let stored = decrypt_stored(entry.is_encrypted(), stored, options)?;
+    entry.compression().decompress_into(&stored, options.max_asset_size, output)?;
+    if expected_size.is_some_and(|x| x as usize != output.len()) {
+        return Err(WassetError::from_deserialize("decompressed size did not match manifest"));
+    }
+
+    #[cfg(feature = "tracing")]
+    tracing::trace!(size = output.len(), "decompressed asset");
+
+    Ok(())
+}
+
+/// Copies the bytecode of a module, replacing the contents of each custom section with the result
+/// of `rewrite`, or removing the section if `rewrite` returns `None`. Sections which are left unchanged
+/// are spliced from the original module without being decoded, so the cost of this depends on the
+/// number of sections rather than their size. Components are re-encoded section by section instead.
+///
+/// Since earlier sections may grow or shrink, the existing alignment padding is dropped and recomputed
+/// in front of every asset data section, so that their contents still begin at a multiple of
+/// [`MAX_PAYLOAD_ALIGNMENT`].
+pub(crate) fn rewrite_custom_sections<'a, 'b>(module: &'a [u8], mut rewrite: impl FnMut(&CustomSectionReader<'a>) -> Option<Cow<'b, [u8]>>) -> Result<Vec<u8>, WassetError> {
+    if Parser::is_component(module) {
+        return rewrite_component_custom_sections(module, rewrite);
+    }
+
+    let mut output = Vec::with_capacity(module.len());
+    let mut contents = module;
+    let mut parser = Parser::new(0);
+
+    // The range of the original module which is kept, and has yet to be copied to the output:
+    let mut kept = 0..0;
+
+    loop {
+        let payload = match parser.parse(contents, true).map_err(WassetError::from_deserialize)? {
+            Chunk::Parsed { consumed, payload } => {
+                contents = &contents[consumed..];
+                payload
+            }
+            // this state isn't possible with `eof = true`
+            Chunk::NeedMoreData(_) => unreachable!(),
+        };
+
+        match payload {
+            Payload::CodeSectionStart { size, .. } => {
+                parser.skip_section();
+                contents = &contents[size as usize..];
+            }
+            Payload::CustomSection(c) => match (rewrite(&c), c.name().contains(DATA_SECTION_SUFFIX)) {
+                (Some(Cow::Borrowed(data)), false) if !is_alignment_padding(&c) && std::ptr::eq(data, c.data()) => {},
+                (data, is_data) => {
+                    output.extend_from_slice(&module[kept]);
+                    if let Some(data) = data.filter(|_| !is_alignment_padding(&c)) {
+                        let section = CustomSection {
+                            name: c.name().into(),
+                            data
+                        };
+
+                        if is_data {
+                            if let Some(padding) = alignment_padding(output.len(), &section) {
+                                padding.append_to(&mut output);
+                            }
+                        }
+
+                        section.append_to(&mut output);
+                    }
+
+                    let position = module.len() - contents.len();
+                    kept = position..position;
+                    continue;
+                }
+            },
+            Payload::End(_) => break,
+            _ => {}
+        }
+
+        kept.end = module.len() - contents.len();
+    }
+
+    output.extend_from_slice(&module[kept]);
+    Ok(output)
+}
+
+/// Copies the bytecode of a component, replacing the contents of each custom section with the result
+/// of `rewrite`, or removing the section if `rewrite` returns `None`. Every section is re-encoded,
+/// so that the sizes of nested modules reflect their new contents.
+fn rewrite_component_custom_sections<'a, 'b>(module: &'a [u8], mut rewrite: impl FnMut(&CustomSectionReader<'a>) -> Option<Cow<'b, [u8]>>) -> Result<Vec<u8>, WassetError> {
+    let mut output = Vec::new();
+    let mut stack = Vec::new();
+
+    for payload in Parser::new(0).parse_all(module) {
+        let payload = payload.map_err(WassetError::from_deserialize)?;
+
+        // Track nesting depth, so that we don't mess with inner producer sections:
+        match payload {
+            Payload::Version { ref range, .. } => output.extend_from_slice(&module[range.clone()]),
+            Payload::ModuleSection { .. } => {
+                stack.push((take(&mut output), ComponentSectionId::CoreModule));
+                continue;
+            }
+            Payload::ComponentSection { .. } => {
+                stack.push((take(&mut output), ComponentSectionId::Component));
+                continue;
+            }
+            Payload::End { .. } => {
+                let (mut parent, id) = match stack.pop() {
+                    Some(c) => c,
+                    None => break,
+                };
+
+                parent.push(id as u8);
+                output.encode(&mut parent);
 
-    /// Parses a WASM module's custom section, checking whether it holds an asset manifest or data.
-    fn parse_module_custom_section(reader: CustomSectionReader<'a>, offsets: &mut FxHashMap<Uuid, WassetOffsets<'a>>) -> Result<(), WassetError> {
-        if reader.name().starts_with(Self::ASSET_MANIFEST_SECTION_PREFIX) {
-            let id = Uuid::try_parse(&reader.name()[Self::ASSET_MANIFEST_SECTION_PREFIX.len()..])
-                .map_err(WassetError::from_deserialize)?;
-            offsets.entry(id).or_default().manifest = reader.data();
+                output = parent;
+            }
+            _ => {}
         }
-        else if reader.name().starts_with(Self::ASSET_DATA_SECTION_PREFIX) {
-            let id = Uuid::try_parse(&reader.name()[Self::ASSET_DATA_SECTION_PREFIX.len()..])
-                .map_err(WassetError::from_deserialize)?;
-                offsets.entry(id).or_default().data_offset = reader.data_offset() as u32;
+
+        if let Payload::CustomSection(c) = &payload {
+            if let Some(data) = rewrite(c).filter(|_| !is_alignment_padding(c)) {
+                let section = CustomSection {
+                    name: c.name().into(),
+                    data
+                };
+
+                if c.name().contains(DATA_SECTION_SUFFIX) {
+                    if let Some(padding) = alignment_padding(output.len(), &section) {
+                        padding.append_to(&mut output);
+                    }
+                }
+
+                section.append_to(&mut output);
+            }
+
+            continue;
         }
 
-        Ok(())
+        if let Some((id, range)) = payload.as_section() {
+            RawSection {
+                id,
+                data: &module[range],
+            }.append_to(&mut output);
+        }
+    }
+
+    Ok(output)
+}
+
+/// Finds the asset manifest and data sections with the given prefix in a WASM module
+/// or standalone asset pack, grouping them by the ID of each section pair. The sections of
+/// every core module and component nested within a component are included.
+pub(crate) fn read_asset_sections<'a>(module: &'a [u8], prefix: &str) -> Result<FxHashMap<Uuid, WassetOffsets<'a>>, WassetError> {
+    if is_asset_pack(module) {
+        return read_pack_sections(module, prefix);
+    }
+
+    let mut contents = module;
+    let mut parser = Parser::new(0);
+    let mut stack = Vec::new();
+    let mut offsets = FxHashMap::default();
+
+    loop {
+        let payload = match parser.parse(contents, true).map_err(WassetError::from_deserialize)? {
+            Chunk::Parsed { consumed, payload } => {
+                contents = &contents[consumed..];
+                payload
+            }
+            // this state isn't possible with `eof = true`
+            Chunk::NeedMoreData(_) => unreachable!(),
+        };
+
+        match payload {
+            Payload::CodeSectionStart { size, .. } => {
+                parser.skip_section();
+                contents = &contents[size as usize..];
+            }
+            Payload::CustomSection(c) => parse_module_custom_section(c, prefix, &mut offsets)?,
+            Payload::ModuleSection { parser: nested, .. } | Payload::ComponentSection { parser: nested, .. } => {
+                stack.push(std::mem::replace(&mut parser, nested));
+            }
+            Payload::End(_) => match stack.pop() {
+                Some(parent) => parser = parent,
+                None => break
+            },
+            _ => {}
+        }
+    }
+
+    Ok(offsets)
+}
+
+/// Parses a WASM module's custom section, checking whether it holds an asset manifest or data.
+pub(crate) fn parse_module_custom_section<'a>(reader: CustomSectionReader<'a>, prefix: &str, offsets: &mut FxHashMap<Uuid, WassetOffsets<'a>>) -> Result<(), WassetError> {
+    parse_asset_section(reader.name(), reader.data(), reader.data_offset(), prefix, offsets)
+}
+
+/// Checks whether the section with the given name holds an asset manifest or data, recording its
+/// contents and the offset of its contents from the start of the module if so.
+pub(crate) fn parse_asset_section<'a>(name: &str, data: &'a [u8], data_offset: usize, prefix: &str, offsets: &mut FxHashMap<Uuid, WassetOffsets<'a>>) -> Result<(), WassetError> {
+    match parse_section_name(name, prefix) {
+        Some((AssetSectionKind::Manifest, id)) => {
+            let id = Uuid::try_parse(id).map_err(WassetError::from_deserialize)?;
+            #[cfg(feature = "tracing")]
+            tracing::trace!(%id, size = data.len(), "found asset manifest section");
+
+            if offsets.entry(id).or_default().manifest.replace(data).is_some() {
+                return Err(WassetError::from_deserialize(format!("Duplicate asset manifest section {id}")));
+            }
+        },
+        Some((AssetSectionKind::Index, id)) => {
+            let id = Uuid::try_parse(id).map_err(WassetError::from_deserialize)?;
+            if offsets.entry(id).or_default().index.replace(data).is_some() {
+                return Err(WassetError::from_deserialize(format!("Duplicate asset index section {id}")));
+            }
+        },
+        Some((AssetSectionKind::Signature, id)) => {
+            let id = Uuid::try_parse(id).map_err(WassetError::from_deserialize)?;
+            if offsets.entry(id).or_default().signature.replace(data).is_some() {
+                return Err(WassetError::from_deserialize(format!("Duplicate asset signature section {id}")));
+            }
+        },
+        Some((AssetSectionKind::Preview, id)) => {
+            let id = Uuid::try_parse(id).map_err(WassetError::from_deserialize)?;
+            let preview = WassetDataSection {
+                offset: data_offset as u32,
+                len: data.len() as u32
+            };
+
+            if offsets.entry(id).or_default().preview.replace(preview).is_some() {
+                return Err(WassetError::from_deserialize(format!("Duplicate asset preview section {id}")));
+            }
+        },
+        Some((AssetSectionKind::Data, id)) => {
+            let id = Uuid::try_parse(id).map_err(WassetError::from_deserialize)?;
+            let section = WassetDataSection {
+                offset: data_offset as u32,
+                len: data.len() as u32
+            };
+
+            #[cfg(feature = "tracing")]
+            tracing::trace!(%id, size = section.len, "found asset data section");
+
+            if offsets.entry(id).or_default().data.replace(section).is_some() {
+                return Err(WassetError::from_deserialize(format!("Duplicate asset data section {id}")));
+            }
+        },
+        None => {}
+    }
+
+    Ok(())
+}
+
+/// Determines whether a custom section name refers to asset data with the given prefix. If so,
+/// returns the kind of section, along with the ID of the section pair.
+pub(crate) fn parse_section_name<'a>(name: &'a str, prefix: &str) -> Option<(AssetSectionKind, &'a str)> {
+    let rest = name.strip_prefix(prefix)?;
+    if let Some(id) = rest.strip_prefix(MANIFEST_SECTION_SUFFIX) {
+        Some((AssetSectionKind::Manifest, id))
+    }
+    else if let Some(id) = rest.strip_prefix(INDEX_SECTION_SUFFIX) {
+        Some((AssetSectionKind::Index, id))
+    }
+    else if let Some(id) = rest.strip_prefix(PREVIEW_SECTION_SUFFIX) {
+        Some((AssetSectionKind::Preview, id))
+    }
+    else if let Some(id) = rest.strip_prefix(SIGNATURE_SECTION_SUFFIX) {
+        Some((AssetSectionKind::Signature, id))
+    }
+    else {
+        rest.strip_prefix(DATA_SECTION_SUFFIX).map(|id| (AssetSectionKind::Data, id))
+    }
+}
+
+impl<'a, A: AssetSchema> WassetSource<A> for WassetParser<'a, A> {
+    fn ids(&self) -> Box<dyn '_ + Iterator<Item = WassetId>> {
+        Box::new(self.manifest.ids())
+    }
+
+    fn contains(&self, id: WassetId) -> bool {
+        self.manifest.get(id).is_some()
+    }
+
+    fn load(&self, id: WassetId) -> Result<Option<A>, WassetError> {
+        WassetParser::load(self, id)
     }
 }
 
@@ -221,7 +1043,7 @@ impl<'a, A: AssetSchema> IntoIterator for &'a WassetParser<'a, A> {
 
     fn into_iter(self) -> Self::IntoIter {
         WassetIter {
-            iter: self.manifest.asset_ranges.iter(),
+            iter: self.manifest.assets.iter(),
             parser: self
         }
     }
@@ -230,7 +1052,7 @@ impl<'a, A: AssetSchema> IntoIterator for &'a WassetParser<'a, A> {
 /// Allows for iterating over all assets in a module.
 pub struct WassetIter<'a, A: AssetSchema> {
     /// The inner iterator.
-    iter: std::collections::hash_map::Iter<'a, WassetId, Range<u32>>,
+    iter: std::collections::hash_map::Iter<'a, WassetId, WassetManifestEntry>,
     /// The parser.
     parser: &'a WassetParser<'a, A>
 }
@@ -239,15 +1061,283 @@ impl<'a, A: AssetSchema> Iterator for WassetIter<'a, A> {
     type Item = (WassetId, Result<A, WassetError>);
 
     fn next(&mut self) -> Option<Self::Item> {
-        self.iter.next().map(|(id, range)| (*id, self.parser.load_by_range(range.clone()).and_then(|x| x.deserialize())))
+        self.iter.next().map(|(id, entry)| (*id, self.parser.load_entry(entry).and_then(|x| x.deserialize())))
     }
 }
 
-/// Describes a manifest section that must be parsed.
+/// Describes a manifest section that must be parsed, along with its data section.
+/// Either may be missing until the whole module has been read.
 #[derive(Copy, Clone, Debug, Default)]
-struct WassetOffsets<'a> {
-    /// The offset of the associated data section.
-    data_offset: u32,
+pub(crate) struct WassetOffsets<'a> {
+    /// The location of the associated data section.
+    pub data: Option<WassetDataSection>,
+    /// The serialized perfect-hash index bytes, if the manifest has an index.
+    pub index: Option<&'a [u8]>,
     /// The serialized manifest bytes.
-    manifest: &'a [u8],
+    pub manifest: Option<&'a [u8]>,
+    /// The location of the associated preview section, if any.
+    pub preview: Option<WassetDataSection>,
+    /// The signature over the other sections, if the set was signed.
+    pub signature: Option<&'a [u8]>
+}
+
+impl<'a> WassetOffsets<'a> {
+    /// Gets the manifest and data section of the set, failing with [`WassetError::IncompleteSections`]
+    /// if either is missing.
+    pub(crate) fn complete(&self, section: Uuid) -> Result<(&'a [u8], WassetDataSection), WassetError> {
+        match (self.manifest, self.data) {
+            (Some(manifest), Some(data)) => Ok((manifest, data)),
+            (None, _) => Err(WassetError::IncompleteSections { section, missing: WassetSectionPart::Manifest }),
+            (_, None) => Err(WassetError::IncompleteSections { section, missing: WassetSectionPart::Data })
+        }
+    }
+}
+
+/// Identifies the contents of an asset custom section.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub(crate) enum AssetSectionKind {
+    /// The section holds serialized asset data.
+    Data,
+    /// The section holds a perfect-hash index over a manifest.
+    Index,
+    /// The section holds a serialized manifest.
+    Manifest,
+    /// The section holds asset previews.
+    Preview,
+    /// The section holds a signature over the other sections of its set.
+    Signature
+}
+
+/// Describes the location of an asset data section within a module.
+#[derive(Copy, Clone, Debug)]
+pub(crate) struct WassetDataSection {
+    /// The offset of the section contents from the start of the module.
+    pub offset: u32,
+    /// The length of the section contents.
+    pub len: u32
+}
+
+/// Decrypts the stored bytes of an asset with the key of the given options,
+/// or borrows them if the asset is not encrypted.
+#[cfg(feature = "encryption")]
+pub(crate) fn decrypt_stored<'b>(encrypted: bool, stored: &'b [u8], options: &WassetParseOptions) -> Result<Cow<'b, [u8]>, WassetError> {
+    if !encrypted {
+        return Ok(Cow::Borrowed(stored));
+    }
+
+    let key = options.encryption_key.as_ref()
+        .ok_or_else(|| WassetError::from_deserialize("Asset is encrypted, but no encryption key was provided"))?;
+    key.decrypt(stored).map(Cow::Owned)
+}
+
+/// Borrows the stored bytes of an asset, failing if the asset is encrypted, since
+/// decryption requires the `encryption` feature.
+#[cfg(not(feature = "encryption"))]
+pub(crate) fn decrypt_stored<'b>(encrypted: bool, stored: &'b [u8], _: &WassetParseOptions) -> Result<Cow<'b, [u8]>, WassetError> {
+    if encrypted {
+        Err(WassetError::from_deserialize("Asset is encrypted, but the encryption feature is disabled"))
+    }
+    else {
+        Ok(Cow::Borrowed(stored))
+    }
+}
+
+#[cfg(all(test, feature = "testing"))]
+mod tests {
+    use super::*;
+
+    /// Builds a module with a single function and an unrelated custom section, but no assets.
+    fn plain_module() -> Vec<u8> {
+        let mut types = wasm_encoder::TypeSection::new();
+        types.function([], [wasm_encoder::ValType::I32]);
+        let mut functions = wasm_encoder::FunctionSection::new();
+        functions.function(0);
+        let mut function = wasm_encoder::Function::new([]);
+        function.instruction(&wasm_encoder::Instruction::I32Const(5));
+        function.instruction(&wasm_encoder::Instruction::End);
+        let mut code = wasm_encoder::CodeSection::new();
+        code.function(&function);
+
+        let mut module = wasm_encoder::Module::new();
+        module.section(&types)
+            .section(&functions)
+            .section(&code)
+            .section(&wasm_encoder::CustomSection { name: "note".into(), data: Cow::Borrowed(b"hello") });
+        module.finish()
+    }
+
+    /// Wraps the given core modules in a component.
+    fn wrap_in_component(modules: &[&[u8]]) -> Vec<u8> {
+        let mut result = b"\0asm\x0d\x00\x01\x00".to_vec();
+        for module in modules {
+            result.push(1);
+            module.len().encode(&mut result);
+            result.extend_from_slice(module);
+        }
+
+        result
+    }
+
+    /// Gets the names of the custom sections in a module, including those of nested modules.
+    fn custom_section_names(module: &[u8]) -> Vec<String> {
+        Parser::new(0).parse_all(module)
+            .filter_map(|x| match x.unwrap() {
+                Payload::CustomSection(section) => Some(section.name().to_owned()),
+                _ => None
+            })
+            .collect()
+    }
+
+    #[test]
+    fn asset_larger_than_limit_is_rejected() {
+        let id = WassetId::from_bytes([1; 16]);
+        let module = testing::build_module([(id, vec![7u8; 1000])]).unwrap();
+
+        assert_eq!(WassetParser::<Vec<u8>>::parse(&module).unwrap().load(id).unwrap(), Some(vec![7; 1000]));
+        let options = WassetParseOptions { max_asset_size: 100, ..Default::default() };
+        let parser = WassetParser::<Vec<u8>>::parse_with_options(&module, options).unwrap();
+        assert!(matches!(parser.load(id), Err(WassetError::LimitExceeded { limit: 100, .. })));
+        assert!(matches!(parser.load_raw(id), Err(WassetError::LimitExceeded { limit: 100, .. })));
+    }
+
+    #[cfg(feature = "lz4")]
+    #[test]
+    fn compressed_asset_is_checked_against_decompressed_size() {
+        let id = WassetId::from_bytes([1; 16]);
+        let options = WassetEncodeOptions { compression: CompressionMode::Fixed(WassetCompression::Lz4), ..Default::default() };
+        let module = testing::build_module_with_options([(id, vec![7u8; 10000])], &options).unwrap();
+
+        let options = WassetParseOptions { max_asset_size: 1000, ..Default::default() };
+        let parser = WassetParser::<Vec<u8>>::parse_with_options(&module, options).unwrap();
+        assert!(parser.manifest().get(id).unwrap().compressed_size() < 1000);
+        assert!(matches!(parser.load(id), Err(WassetError::LimitExceeded { limit: 1000, .. })));
+        assert!(matches!(parser.load_raw(id), Err(WassetError::LimitExceeded { limit: 1000, .. })));
+    }
+
+    #[cfg(feature = "bytemuck")]
+    #[test]
+    fn aligned_payload_is_viewed_in_place() {
+        let id = WassetId::from_bytes([1; 16]);
+        let asset = testing::PodBytes::from_u32s([1, 2, 3, 4]);
+        let options = WassetEncodeOptions { alignment: 4, ..Default::default() };
+        let module = testing::build_module_with_options([(id, asset)], &options).unwrap();
+
+        let parser = WassetParser::<testing::PodBytes>::parse(&module).unwrap();
+        assert_eq!(parser.load_pod::<u32>(id).unwrap(), Some(&[1, 2, 3, 4][..]));
+        assert_eq!(parser.load_pod::<u32>(WassetId::from_bytes([2; 16])).unwrap(), None);
+        assert!(parser.load_pod_value::<[u32; 4]>(id).unwrap().is_some_and(|x| *x == [1, 2, 3, 4]));
+    }
+
+    #[cfg(feature = "bytemuck")]
+    #[test]
+    fn misaligned_payload_is_rejected() {
+        // Without padding, the payload follows the two-byte header of the first byte array in the data section.
+        let id = WassetId::from_bytes([1; 16]);
+        let module = testing::build_module([(id, testing::PodBytes::from_u32s([1, 2, 3, 4]))]).unwrap();
+
+        let parser = WassetParser::<testing::PodBytes>::parse(&module).unwrap();
+        assert!(parser.load_pod::<u8>(id).unwrap().is_some());
+        assert!(parser.load_pod::<u32>(id).is_err());
+    }
+
+    #[cfg(all(feature = "bytemuck", feature = "lz4"))]
+    #[test]
+    fn compressed_payload_is_not_viewed_in_place() {
+        let id = WassetId::from_bytes([1; 16]);
+        let options = WassetEncodeOptions { compression: CompressionMode::Fixed(WassetCompression::Lz4), alignment: 4, ..Default::default() };
+        let module = testing::build_module_with_options([(id, testing::PodBytes::from_u32s([0; 64]))], &options).unwrap();
+
+        let parser = WassetParser::<testing::PodBytes>::parse(&module).unwrap();
+        assert!(parser.load_pod::<u32>(id).is_err());
+        assert_eq!(parser.load(id).unwrap(), Some(testing::PodBytes::from_u32s([0; 64])));
+    }
+
+    #[cfg(all(feature = "bytemuck", feature = "json"))]
+    #[test]
+    fn payload_of_other_codec_is_not_viewed_in_place() {
+        let id = WassetId::from_bytes([1; 16]);
+        let options = WassetEncodeOptions { codec: WassetCodec::Json, ..Default::default() };
+        let module = testing::build_module_with_options([(id, vec![1u8, 2, 3, 4])], &options).unwrap();
+
+        let parser = WassetParser::<Vec<u8>>::parse(&module).unwrap();
+        assert!(parser.load_pod::<u8>(id).is_err());
+    }
+
+    #[test]
+    fn duplicate_ids_follow_policy() {
+        let id = WassetId::from_bytes([1; 16]);
+        let options = WassetEncodeOptions::default();
+        let module = WassetModuleBuilder::new()
+            .add_assets(encode_assets([(id, String::from("longer"))], &options).unwrap())
+            .add_assets(encode_assets([(id, String::from("a"))], &options).unwrap())
+            .build();
+
+        let load = |duplicate_ids| WassetParser::<String>::parse_with_options(&module, WassetParseOptions { duplicate_ids, ..Default::default() })
+            .and_then(|parser| parser.load(id));
+        assert!(matches!(load(DuplicateIdPolicy::Error), Err(WassetError::DuplicateId(x)) if x == id));
+        assert_eq!(load(DuplicateIdPolicy::FirstWins).unwrap().as_deref(), Some("longer"));
+        assert_eq!(load(DuplicateIdPolicy::LastWins).unwrap().as_deref(), Some("a"));
+        let keep_larger = DuplicateIdPolicy::Custom(|_, earlier, later| Ok(later.compressed_size() > earlier.compressed_size()));
+        assert_eq!(load(keep_larger).unwrap().as_deref(), Some("longer"));
+        let reject = DuplicateIdPolicy::Custom(|_, _, _| Err(WassetError::from_deserialize("rejected")));
+        assert!(load(reject).is_err());
+    }
+
+    #[test]
+    fn strip_module_splices_out_asset_sections() {
+        let module = plain_module();
+        let assets = encode_assets([(WassetId::from_bytes([1; 16]), 5u32)], &WassetEncodeOptions::default()).unwrap();
+        let injected = inject_assets(&module, &assets);
+        let parser = WassetParser::<u32>::parse(&injected).unwrap();
+
+        assert_eq!(parser.strip_module().unwrap(), module);
+
+        let keep_manifests = WassetStripOptions { keep_manifests: true, ..Default::default() };
+        let names = custom_section_names(&parser.strip_module_with_options(&keep_manifests).unwrap());
+        assert_eq!(names.len(), 2);
+        assert!(names.iter().any(|x| parse_section_name(x, DEFAULT_SECTION_PREFIX).is_some_and(|(kind, _)| kind == AssetSectionKind::Manifest)));
+
+        let drop_others = WassetStripOptions { other_sections: CustomSectionFilter::DropAll, ..Default::default() };
+        let stripped = parser.strip_module_with_options(&drop_others).unwrap();
+        assert!(custom_section_names(&stripped).is_empty());
+    }
+
+    #[test]
+    fn assets_of_nested_modules_are_parsed() {
+        let first = WassetId::from_bytes([1; 16]);
+        let second = WassetId::from_bytes([2; 16]);
+        let options = WassetEncodeOptions::default();
+        let first_module = inject_assets(&plain_module(), &encode_assets([(first, 1u32)], &options).unwrap());
+        let second_module = testing::build_module([(second, 2u32)]).unwrap();
+        let component = wrap_in_component(&[&first_module, &second_module]);
+
+        let parser = WassetParser::<u32>::parse(&component).unwrap();
+        assert_eq!(parser.load(first).unwrap(), Some(1));
+        assert_eq!(parser.load(second).unwrap(), Some(2));
+
+        let stripped = parser.strip_module().unwrap();
+        assert_eq!(custom_section_names(&stripped).into_iter().filter(|x| !x.is_empty()).collect::<Vec<_>>(), ["note"]);
+        assert_eq!(WassetParser::<u32>::parse(&stripped).unwrap().manifest().ids().count(), 0);
+    }
+
+    #[test]
+    fn quality_tiers_are_stored_under_one_id() {
+        let files = [("texture.txt", "high"), ("texture.low.txt", "low"), ("icon.medium.txt", "medium")];
+        let options = WassetEncodeOptions { quality_variants: true, ..Default::default() };
+        let assets = testing::encode_text_files(files, &options).unwrap();
+        let module = WassetModuleBuilder::new().add_assets(assets).build();
+        let parser = WassetParser::<String>::parse(&module).unwrap();
+
+        let id_of = |name| parser.manifest().iter().find(|(_, entry)| entry.name() == Some(name)).map(|(id, _)| id).unwrap();
+        let texture = id_of("assets/texture");
+        let icon = id_of("assets/icon");
+        assert_eq!(parser.manifest().ids().count(), 2);
+
+        assert_eq!(parser.load(texture).unwrap().as_deref(), Some("high"));
+        assert_eq!(parser.load_variant(texture, Quality::Low).unwrap().as_deref(), Some("low"));
+        assert_eq!(parser.load_variant(texture, Quality::Medium).unwrap().as_deref(), Some("low"));
+        assert_eq!(parser.load_variant(texture, Quality::High).unwrap().as_deref(), Some("high"));
+        assert!(parser.manifest().get(icon).unwrap().qualities().is_empty());
+        assert_eq!(parser.load_variant(icon, Quality::Low).unwrap().as_deref(), Some("medium"));
+    }
 }
\ No newline at end of file
@@ -0,0 +1,97 @@
+use crate::*;
+
+/// A general-purpose asset schema which holds the contents of a file along with its MIME type.
+/// This allows for embedding and reading files without defining a custom schema or encoder.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct RawAsset {
+    /// The MIME type of the file, such as `image/png`.
+    pub mime: String,
+    /// The raw contents of the file.
+    pub bytes: Vec<u8>
+}
+
+/// Encodes every file as a [`RawAsset`], guessing its MIME type from the file extension.
+/// A string `mime` key in the file's `Wasset.toml` table overrides the guessed type.
+#[cfg(feature = "encode")]
+#[derive(Copy, Clone, Debug, Default)]
+pub struct RawAssetEncoder;
+
+#[cfg(feature = "encode")]
+impl AssetEncoder for RawAssetEncoder {
+    type Target = RawAsset;
+
+    fn encode(context: &EncodeContext, data: Vec<u8>) -> Result<Option<Self::Target>, WassetError> {
+        let mime = match context.metadata.get("mime") {
+            Some(toml::Value::String(x)) => x.clone(),
+            None => mime_for_extension(context.extension).to_string(),
+            Some(x) => return Err(WassetError::from_serialize(format!("Unexpected mime value {x:?}; expected string")))
+        };
+
+        Ok(Some(RawAsset { mime, bytes: data }))
+    }
+
+    fn kind(asset: &Self::Target) -> Option<String> {
+        asset.mime.split('/').next().map(str::to_string)
+    }
+}
+
+/// Writes every [`RawAsset`] back out as its raw contents, choosing the file extension from its MIME type.
+#[cfg(feature = "parse")]
+#[derive(Copy, Clone, Debug, Default)]
+pub struct RawAssetDecoder;
+
+#[cfg(feature = "parse")]
+impl AssetDecoder for RawAssetDecoder {
+    type Source = RawAsset;
+
+    fn decode(asset: &Self::Source) -> Result<Vec<u8>, WassetError> {
+        Ok(asset.bytes.clone())
+    }
+
+    fn extension(asset: &Self::Source) -> Option<String> {
+        MIME_TYPES.iter().find(|(_, mime)| *mime == asset.mime).map(|(extension, _)| extension.to_string())
+    }
+}
+
+/// Guesses the MIME type of a file from its extension.
+#[cfg(feature = "encode")]
+fn mime_for_extension(extension: &str) -> &'static str {
+    let extension = extension.to_ascii_lowercase();
+    MIME_TYPES.iter().find(|(x, _)| *x == extension).map_or("application/octet-stream", |(_, mime)| mime)
+}
+
+/// The MIME types of common file extensions. Where several extensions share a type,
+/// the first is preferred when extracting assets.
+#[cfg(any(feature = "encode", feature = "parse"))]
+const MIME_TYPES: &[(&str, &str)] = &[
+    ("txt", "text/plain"),
+    ("md", "text/markdown"),
+    ("csv", "text/csv"),
+    ("html", "text/html"),
+    ("htm", "text/html"),
+    ("css", "text/css"),
+    ("js", "text/javascript"),
+    ("json", "application/json"),
+    ("toml", "application/toml"),
+    ("xml", "application/xml"),
+    ("wasm", "application/wasm"),
+    ("png", "image/png"),
+    ("jpg", "image/jpeg"),
+    ("jpeg", "image/jpeg"),
+    ("gif", "image/gif"),
+    ("bmp", "image/bmp"),
+    ("webp", "image/webp"),
+    ("svg", "image/svg+xml"),
+    ("ico", "image/vnd.microsoft.icon"),
+    ("wav", "audio/wav"),
+    ("mp3", "audio/mpeg"),
+    ("ogg", "audio/ogg"),
+    ("flac", "audio/flac"),
+    ("ttf", "font/ttf"),
+    ("otf", "font/otf"),
+    ("woff", "font/woff"),
+    ("woff2", "font/woff2"),
+    ("gltf", "model/gltf+json"),
+    ("glb", "model/gltf-binary"),
+    ("bin", "application/octet-stream")
+];
@@ -0,0 +1,361 @@
+use crate::*;
+use std::io::{Read, Seek, SeekFrom};
+use std::marker::*;
+use std::sync::*;
+
+/// Reads assets from a module through any seekable source, such as a file, without holding the
+/// module in memory. Only the asset manifests are read up front, and every other section is
+/// skipped; each asset is then read from its range of the source when it is loaded. This suits
+/// hosts which open very large modules, but only use a few of their assets at a time.
+///
+/// ```no_run
+/// # use wasset::*;
+/// # use std::fs::File;
+/// # type MyAsset = Vec<u8>;
+/// # mod assets { pub const SOME_ASSET: wasset::WassetId = wasset::WassetId::from_bytes([1; 16]); }
+/// let reader = WassetReader::<MyAsset, _>::new(File::open("plugin.wasm")?)?;
+/// let asset = reader.load(assets::SOME_ASSET)?;
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+pub struct WassetReader<A: AssetSchema, R> {
+    /// The manifest associated with the module.
+    manifest: WassetManifest,
+    /// The options used to read assets.
+    options: WassetParseOptions,
+    /// The source from which the module is read.
+    source: Mutex<R>,
+    /// A marker type for `A`.
+    marker: PhantomData<fn(A)>
+}
+
+impl<A: AssetSchema, R: Read + Seek> WassetReader<A, R> {
+    /// Attempts to read the asset list from the module at the start of the given source.
+    pub fn new(source: R) -> Result<Self, WassetError> {
+        Self::with_options(source, WassetParseOptions::default())
+    }
+
+    /// Attempts to read the asset list from the module at the start of the given source,
+    /// using the provided options when reading assets.
+    pub fn with_options(mut source: R, options: WassetParseOptions) -> Result<Self, WassetError> {
+        let sections = scan_asset_sections(&mut source, &options.section_prefix)?;
+        let offsets = sections.iter()
+            .map(|(id, x)| (*id, WassetOffsets {
+                data: x.data,
+                index: None,
+                manifest: x.manifest.as_deref(),
+                preview: x.preview,
+                signature: None
+            }))
+            .collect();
+
+        Ok(Self {
+            manifest: WassetParser::<A>::collect_manifests(offsets, &options)?,
+            options,
+            source: Mutex::new(source),
+            marker: PhantomData
+        })
+    }
+
+    /// Gets an iterator over the IDs of all assets stored in the module.
+    pub fn ids(&self) -> impl '_ + Iterator<Item = WassetId> {
+        self.manifest.ids()
+    }
+
+    /// Loads the provided asset from the module, returning `None` if it
+    /// did not exist.
+    pub fn load(&self, id: WassetId) -> Result<Option<A>, WassetError> {
+        self.load_as(id)
+    }
+
+    /// Loads every asset with one of the given IDs. Requests are sorted by their location in
+    /// the source, and assets which lie close together are fetched with a single read, which
+    /// avoids seeking back and forth when many assets are preloaded at once. IDs which do not
+    /// exist are omitted from the result.
+    pub fn load_many(&self, ids: &[WassetId]) -> Result<FxHashMap<WassetId, A>, WassetError> {
+        let entries = self.manifest.entries_by_offset(ids);
+        entries.iter().try_for_each(|(_, x)| x.check_local())?;
+        if let Some(size) = entries.iter().map(|(_, x)| x.compressed_size() as u64).find(|x| *x > self.options.max_asset_size) {
+            return Err(WassetError::LimitExceeded { size, limit: self.options.max_asset_size });
+        }
+
+        let mut result = FxHashMap::default();
+        let mut batch_data = Vec::new();
+        let mut buffer = Vec::new();
+        let mut remaining = &entries[..];
+        while let Some((_, first)) = remaining.first() {
+            let start = first.start;
+            let mut end = first.end;
+            let count = remaining.iter()
+                .take_while(|(_, x)| {
+                    let close = x.start <= end.saturating_add(MAX_BATCH_GAP);
+                    if close {
+                        end = end.max(x.end);
+                    }
+                    close
+                })
+                .count();
+
+            let (batch, rest) = remaining.split_at(count);
+            self.read_range(start..end, &mut batch_data)?;
+
+            #[cfg(feature = "tracing")]
+            tracing::trace!(assets = batch.len(), bytes = end - start, "read asset batch");
+
+            for (id, entry) in batch {
+                let stored = &batch_data[(entry.start - start) as usize..(entry.end - start) as usize];
+                let item = if entry.is_stored_verbatim() {
+                    WassetItem::<A>::from(stored)
+                }
+                else {
+                    decompress_entry(entry, stored, &self.options, &mut buffer)?;
+                    WassetItem::from(&buffer[..])
+                };
+                result.insert(*id, item.with_codec(entry.codec()).deserialize()?);
+            }
+
+            remaining = rest;
+        }
+
+        Ok(result)
+    }
+
+    /// Loads the asset with the given ID and deserializes it as `T`, rather than the asset schema.
+    pub fn load_as<T: for<'de> Deserialize<'de>>(&self, id: WassetId) -> Result<Option<T>, WassetError> {
+        let Some(codec) = self.manifest.get(id).map(WassetManifestEntry::codec) else {
+            return Ok(None);
+        };
+
+        self.load_raw(id)?.map(|x| WassetItem::<A>::from(x).with_codec(codec).deserialize_as()).transpose()
+    }
+
+    /// Loads the raw data associated with the given ID, returning `None` if it
+    /// did not exist.
+    pub fn load_raw(&self, id: WassetId) -> Result<Option<Vec<u8>>, WassetError> {
+        let mut buffer = Vec::new();
+        Ok(self.load_raw_into(id, &mut buffer)?.then_some(buffer))
+    }
+
+    /// Loads the raw data of the asset with the given ID into `buffer`, replacing its contents.
+    /// Returns `false` if the asset did not exist.
+    pub fn load_raw_into(&self, id: WassetId, buffer: &mut Vec<u8>) -> Result<bool, WassetError> {
+        let Some(entry) = self.manifest.get(id) else {
+            return Ok(false);
+        };
+
+        entry.check_local()?;
+        let range = entry.range();
+        let size = range.len() as u64;
+        if size > self.options.max_asset_size {
+            return Err(WassetError::LimitExceeded { size, limit: self.options.max_asset_size });
+        }
+
+        if entry.is_stored_verbatim() {
+            self.read_range(range, buffer)?;
+        }
+        else {
+            let mut stored = Vec::new();
+            self.read_range(range, &mut stored)?;
+            decompress_entry(entry, &stored, &self.options, buffer)?;
+        }
+
+        Ok(true)
+    }
+
+    /// Reads the preview of the asset with the given ID, which was produced by
+    /// the asset's encoder. Returns `None` if the asset did not exist or has no preview.
+    pub fn preview(&self, id: WassetId) -> Result<Option<Vec<u8>>, WassetError> {
+        let Some(preview) = self.manifest.get(id).and_then(|x| x.preview.clone()) else {
+            return Ok(None);
+        };
+
+        let mut buffer = Vec::new();
+        self.read_range(preview, &mut buffer)?;
+        Ok(Some(buffer))
+    }
+
+    /// Gets a reference to the module manifest.
+    pub fn manifest(&self) -> &WassetManifest {
+        &self.manifest
+    }
+
+    /// Gets the options that this reader uses to read assets.
+    pub fn options(&self) -> &WassetParseOptions {
+        &self.options
+    }
+
+    /// Consumes the reader, returning the underlying source.
+    pub fn into_inner(self) -> R {
+        self.source.into_inner().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Reads the given range of the module into `output`, replacing its contents.
+    fn read_range(&self, range: Range<u32>, output: &mut Vec<u8>) -> Result<(), WassetError> {
+        let mut source = self.source.lock().unwrap_or_else(PoisonError::into_inner);
+        source.seek(SeekFrom::Start(range.start.into())).map_err(WassetError::from_deserialize)?;
+        output.clear();
+        output.resize(range.len(), 0);
+        source.read_exact(output).map_err(WassetError::from_deserialize)
+    }
+}
+
+impl<A: AssetSchema, R: Read + Seek> WassetSource<A> for WassetReader<A, R> {
+    fn ids(&self) -> Box<dyn '_ + Iterator<Item = WassetId>> {
+        Box::new(self.manifest.ids())
+    }
+
+    fn contains(&self, id: WassetId) -> bool {
+        self.manifest.get(id).is_some()
+    }
+
+    fn load(&self, id: WassetId) -> Result<Option<A>, WassetError> {
+        WassetReader::load(self, id)
+    }
+}
+
+/// The largest number of unrequested bytes between two assets which [`WassetReader::load_many`]
+/// reads through, rather than seeking past.
+const MAX_BATCH_GAP: u32 = 16 * 1024;
+
+/// Holds the asset sections with a single ID that were found by [`scan_asset_sections`].
+#[derive(Clone, Debug, Default)]
+struct ScannedSections {
+    /// The location of the data section.
+    data: Option<WassetDataSection>,
+    /// Whether an index section was found. Indices are not needed once the manifest is read.
+    index: bool,
+    /// The contents of the manifest section.
+    manifest: Option<Vec<u8>>,
+    /// The location of the preview section.
+    preview: Option<WassetDataSection>,
+    /// Whether a signature section was found. Signatures are not checked when reading from a source.
+    signature: bool
+}
+
+/// Walks the sections of the module at the start of `source`, reading the contents of each
+/// asset manifest, and recording the locations of the data and preview sections.
+fn scan_asset_sections<R: Read + Seek>(source: &mut R, prefix: &str) -> Result<FxHashMap<Uuid, ScannedSections>, WassetError> {
+    let len = source.seek(SeekFrom::End(0)).map_err(WassetError::from_deserialize)?;
+    source.seek(SeekFrom::Start(0)).map_err(WassetError::from_deserialize)?;
+
+    let mut header = [0; 8];
+    source.read_exact(&mut header).map_err(WassetError::from_deserialize)?;
+    if header != *b"\0asm\x01\0\0\0" {
+        return Err(WassetError::from_deserialize("Source does not begin with a WASM module header"));
+    }
+
+    let mut position = header.len() as u64;
+    let mut sections = FxHashMap::<Uuid, ScannedSections>::default();
+    while position < len {
+        let mut section_id = [0];
+        source.read_exact(&mut section_id).map_err(WassetError::from_deserialize)?;
+        let (size, size_len) = read_leb128(source)?;
+        position += 1 + size_len;
+
+        let end = position.checked_add(size).filter(|x| *x <= len)
+            .ok_or_else(|| WassetError::from_deserialize("Section extends past the end of the module"))?;
+
+        if section_id[0] == 0 {
+            let (name_len, name_len_len) = read_leb128(source)?;
+            let start = position + name_len_len + name_len;
+            if start > end {
+                return Err(WassetError::from_deserialize("Custom section name extends past the end of its section"));
+            }
+
+            let mut name = vec![0; name_len as usize];
+            source.read_exact(&mut name).map_err(WassetError::from_deserialize)?;
+            if let Some((kind, id)) = std::str::from_utf8(&name).ok().and_then(|x| parse_section_name(x, prefix)) {
+                let id = Uuid::try_parse(id).map_err(WassetError::from_deserialize)?;
+                let location = WassetDataSection {
+                    offset: u32::try_from(start).map_err(|_| WassetError::from_deserialize(format!("Asset section {id} lies beyond the first 4 GiB of the module")))?,
+                    len: (end - start) as u32
+                };
+
+                let entry = sections.entry(id).or_default();
+                let (duplicate, name) = match kind {
+                    AssetSectionKind::Data => (entry.data.replace(location).is_some(), "data"),
+                    AssetSectionKind::Index => (std::mem::replace(&mut entry.index, true), "index"),
+                    AssetSectionKind::Manifest => {
+                        let mut manifest = vec![0; location.len as usize];
+                        source.read_exact(&mut manifest).map_err(WassetError::from_deserialize)?;
+                        (entry.manifest.replace(manifest).is_some(), "manifest")
+                    },
+                    AssetSectionKind::Preview => (entry.preview.replace(location).is_some(), "preview"),
+                    AssetSectionKind::Signature => (std::mem::replace(&mut entry.signature, true), "signature")
+                };
+
+                if duplicate {
+                    return Err(WassetError::from_deserialize(format!("Duplicate asset {name} section {id}")));
+                }
+            }
+        }
+
+        source.seek(SeekFrom::Start(end)).map_err(WassetError::from_deserialize)?;
+        position = end;
+    }
+
+    Ok(sections)
+}
+
+/// Reads an unsigned 32-bit LEB128 integer from `source`, returning
+/// its value along with the number of bytes that it occupied.
+fn read_leb128<R: Read>(source: &mut R) -> Result<(u64, u64), WassetError> {
+    let mut result = 0;
+    for index in 0..5 {
+        let mut byte = [0];
+        source.read_exact(&mut byte).map_err(WassetError::from_deserialize)?;
+        result |= u64::from(byte[0] & 0x7f) << (7 * index);
+        if byte[0] & 0x80 == 0 {
+            return u32::try_from(result)
+                .map(|x| (u64::from(x), index + 1))
+                .map_err(|_| WassetError::from_deserialize("Module contains an oversized integer"));
+        }
+    }
+
+    Err(WassetError::from_deserialize("Module contains an oversized integer"))
+}
+
+#[cfg(all(test, feature = "testing"))]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    /// Builds a module holding a few text assets, returning their IDs.
+    fn sample_module() -> (Vec<WassetId>, Vec<u8>) {
+        let ids = (1..=3).map(|x| WassetId::from_bytes([x; 16])).collect::<Vec<_>>();
+        let module = testing::build_module(ids.iter().zip(["first", "second", "third"]).map(|(id, x)| (*id, x.to_string()))).unwrap();
+        (ids, module)
+    }
+
+    #[test]
+    fn reader_loads_the_same_assets_as_parser() {
+        let (ids, module) = sample_module();
+        let parser = WassetParser::<String>::parse(&module).unwrap();
+        let reader = WassetReader::<String, _>::new(Cursor::new(&module)).unwrap();
+
+        assert_eq!(reader.ids().count(), ids.len());
+        for id in &ids {
+            assert_eq!(reader.load(*id).unwrap(), parser.load(*id).unwrap());
+        }
+
+        assert_eq!(reader.load(WassetId::from_bytes([0; 16])).unwrap(), None);
+        assert_eq!(reader.into_inner().into_inner(), &module);
+    }
+
+    #[test]
+    fn reader_loads_many_assets_in_one_pass() {
+        let (ids, module) = sample_module();
+        let reader = WassetReader::<String, _>::new(Cursor::new(&module)).unwrap();
+
+        let loaded = reader.load_many(&[ids[2], ids[0], WassetId::from_bytes([0; 16])]).unwrap();
+        assert_eq!(loaded.len(), 2);
+        assert_eq!(loaded[&ids[0]], "first");
+        assert_eq!(loaded[&ids[2]], "third");
+    }
+
+    #[test]
+    fn malformed_sources_are_rejected() {
+        let (_, module) = sample_module();
+        assert!(WassetReader::<String, _>::new(Cursor::new(b"not a module")).is_err());
+        assert!(WassetReader::<String, _>::new(Cursor::new(&module[..module.len() - 1])).is_err());
+    }
+}
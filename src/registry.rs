@@ -0,0 +1,592 @@
+use crate::*;
+use std::hash::*;
+use std::num::*;
+use std::path::*;
+use std::sync::*;
+
+/// Combines the assets of many WASM modules into a single collection, in which every asset
+/// is resolvable by [`WassetId`] regardless of the module that provides it. Modules are
+/// identified by a key of type `K`, and may be replaced while the registry is in use, which
+/// allows plugin updates to be applied without restarting the host. Decoded assets are cached
+/// until the module which provides them changes.
+pub struct WassetRegistry<'a, K, A: AssetSchema> {
+    /// The modules and cached assets of the registry.
+    inner: RwLock<RegistryInner<'a, K, A>>,
+    /// The callbacks which are invoked whenever a module changes.
+    listeners: RwLock<Vec<RegistryListener<'a, K>>>
+}
+
+/// A registry whose modules are keyed by their paths, as when they are loaded with
+/// [`WassetRegistry::load_dir`]. This aggregates the modules of a plugin folder, and
+/// resolves every [`WassetId`] to the module which provides it.
+pub type WassetStore<'a, A> = WassetRegistry<'a, PathBuf, A>;
+
+impl<'a, K: Clone + Eq + Hash, A: AssetSchema> WassetRegistry<'a, K, A> {
+    /// Creates a new, empty registry.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a module to the registry, or atomically replaces the module that was previously
+    /// registered under the same key, returning the old parser. Assets which keep their IDs
+    /// across the replacement remain resolvable throughout, and their cached decodes are
+    /// invalidated if their content changed. Fails without modifying the registry if the new
+    /// module provides an asset that belongs to a different module.
+    pub fn replace_module(&self, key: K, parser: WassetParser<'a, A>) -> Result<Option<WassetParser<'a, A>>, WassetError> {
+        let change = {
+            let mut inner = self.inner.write().unwrap_or_else(PoisonError::into_inner);
+            if let Some(id) = parser.ids().find(|id| inner.owners.get(id).is_some_and(|x| *x != key)) {
+                return Err(WassetError::from_deserialize(format!("Asset {id:?} is already provided by another module")));
+            }
+
+            let mut change = WassetRegistryChange {
+                module: key.clone(),
+                added: Vec::new(),
+                changed: Vec::new(),
+                removed: Vec::new()
+            };
+
+            let old = inner.modules.remove(&key);
+            for id in parser.ids() {
+                match old.as_ref().filter(|x| x.manifest().get(id).is_some()) {
+                    Some(old) if old.content_hash(id).is_some() && old.content_hash(id) == parser.content_hash(id) => {},
+                    Some(_) => change.changed.push(id),
+                    None => change.added.push(id)
+                }
+            }
+
+            if let Some(old) = &old {
+                change.removed.extend(old.ids().filter(|id| parser.manifest().get(*id).is_none()));
+            }
+
+            for id in change.changed.iter().chain(&change.removed) {
+                inner.cache.remove(id);
+            }
+
+            for id in &change.removed {
+                inner.owners.remove(id);
+            }
+
+            for id in &change.added {
+                inner.owners.insert(*id, key.clone());
+            }
+
+            inner.modules.insert(key, parser);
+            inner.generation += 1;
+            (change, old)
+        };
+
+        self.notify(&change.0);
+        Ok(change.1)
+    }
+
+    /// Removes the module with the given key from the registry, returning its parser.
+    pub fn remove_module(&self, key: &K) -> Option<WassetParser<'a, A>> {
+        let (change, old) = {
+            let mut inner = self.inner.write().unwrap_or_else(PoisonError::into_inner);
+            let old = inner.modules.remove(key)?;
+            let removed = old.ids().collect::<Vec<_>>();
+            for id in &removed {
+                inner.owners.remove(id);
+                inner.cache.remove(id);
+            }
+
+            inner.generation += 1;
+            (WassetRegistryChange { module: key.clone(), added: Vec::new(), changed: Vec::new(), removed }, old)
+        };
+
+        self.notify(&change);
+        Some(old)
+    }
+
+    /// Gets the keys of all registered modules.
+    pub fn modules(&self) -> Vec<K> {
+        self.inner.read().unwrap_or_else(PoisonError::into_inner).modules.keys().cloned().collect()
+    }
+
+    /// Gathers statistics over every asset provided by the registered modules.
+    pub fn stats(&self) -> WassetStats {
+        let mut result = WassetStats::default();
+        for parser in self.inner.read().unwrap_or_else(PoisonError::into_inner).modules.values() {
+            result.extend(parser.manifest().iter().map(|(_, entry)| entry));
+        }
+        result
+    }
+
+    /// Gets the key of the module which provides the asset with the given ID.
+    pub fn module_of(&self, id: WassetId) -> Option<K> {
+        self.inner.read().unwrap_or_else(PoisonError::into_inner).owners.get(&id).cloned()
+    }
+
+    /// Loads the asset with the given ID from whichever module provides it, returning `None` if
+    /// no module does. The decoded asset is cached and shared until its module is replaced or removed.
+    pub fn load(&self, id: WassetId) -> Result<Option<Arc<A>>, WassetError> {
+        let inner = self.inner.read().unwrap_or_else(PoisonError::into_inner);
+        if let Some(asset) = inner.cache.get(&id) {
+            return Ok(Some(asset.clone()));
+        }
+
+        // Assets are deserialized without holding the lock, so that replacements need not wait for them.
+        let generation = inner.generation;
+        let Some(item) = inner.owners.get(&id).and_then(|x| inner.modules.get(x)).map(|x| x.load_raw(id)).transpose()?.flatten() else {
+            return Ok(None);
+        };
+        drop(inner);
+
+        let asset = Arc::new(item.deserialize()?);
+        let mut inner = self.inner.write().unwrap_or_else(PoisonError::into_inner);
+        if inner.generation == generation {
+            Ok(Some(inner.cache.entry(id).or_insert(asset).clone()))
+        }
+        else {
+            // Modules changed while the lock was released, so the decode may be stale and must not be cached.
+            Ok(Some(asset))
+        }
+    }
+
+    /// Gets the IDs of the assets upon which the asset with the given ID depends,
+    /// returning `None` if no module provides the asset.
+    pub fn dependencies(&self, id: WassetId) -> Option<Vec<WassetId>> {
+        let inner = self.inner.read().unwrap_or_else(PoisonError::into_inner);
+        inner.entry(id).map(|x| x.dependencies().to_vec())
+    }
+
+    /// Determines the order in which the given assets and all of their transitive dependencies
+    /// should be loaded, such that every asset comes after the assets upon which it depends.
+    /// Requested assets which no module provides are omitted. Fails with
+    /// [`WassetError::MissingDependency`] or [`WassetError::DependencyCycle`] if the
+    /// dependencies cannot be satisfied.
+    pub fn load_order(&self, ids: impl IntoIterator<Item = WassetId>) -> Result<Vec<WassetId>, WassetError> {
+        let inner = self.inner.read().unwrap_or_else(PoisonError::into_inner);
+        dependency_order(ids, |id| inner.entry(id).map(WassetManifestEntry::dependencies))
+    }
+
+    /// Loads the given assets and all of their transitive dependencies, in the order
+    /// given by [`WassetRegistry::load_order`].
+    pub fn load_with_dependencies(&self, ids: impl IntoIterator<Item = WassetId>) -> Result<Vec<(WassetId, Arc<A>)>, WassetError> {
+        self.load_order(ids)?.into_iter()
+            .filter_map(|id| self.load(id).transpose().map(|x| x.map(|asset| (id, asset))))
+            .collect()
+    }
+
+    /// Registers a callback which is invoked after every module replacement or removal.
+    pub fn subscribe(&self, listener: impl 'a + Send + Sync + Fn(&WassetRegistryChange<K>)) {
+        self.listeners.write().unwrap_or_else(PoisonError::into_inner).push(Box::new(listener));
+    }
+
+    /// Invokes all listeners with the given change.
+    fn notify(&self, change: &WassetRegistryChange<K>) {
+        for listener in self.listeners.read().unwrap_or_else(PoisonError::into_inner).iter() {
+            listener(change);
+        }
+    }
+}
+
+impl<'a, A: AssetSchema> WassetRegistry<'a, PathBuf, A> {
+    /// Parses every module in a directory on a pool of threads, then registers each one under
+    /// its path. This is the usual way to index a folder of plugins at startup. Modules are
+    /// registered in path order, and a module which provides an asset that is already provided
+    /// by another module is skipped rather than failing the whole load. Skipped modules and
+    /// modules which could not be parsed are described by the returned report.
+    pub fn load_dir(&self, modules: &'a WassetModuleDir, options: &WassetParseOptions) -> WassetLoadReport {
+        let threads = std::thread::available_parallelism().map(NonZeroUsize::get).unwrap_or(1);
+        let chunk_size = modules.len().div_ceil(threads).max(1);
+        let parsed = std::thread::scope(|scope| {
+            let workers = modules.modules().chunks(chunk_size)
+                .map(|chunk| scope.spawn(move || chunk.iter()
+                    .map(|(path, module)| (path, WassetParser::<A>::parse_with_options(module, options.clone())))
+                    .collect::<Vec<_>>()))
+                .collect::<Vec<_>>();
+
+            workers.into_iter()
+                .flat_map(|x| x.join().unwrap_or_else(|panic| std::panic::resume_unwind(panic)))
+                .collect::<Vec<_>>()
+        });
+
+        let mut report = WassetLoadReport::default();
+        for (path, parser) in parsed {
+            let parser = match parser {
+                Ok(parser) => parser,
+                Err(error) => {
+                    report.failed.push((path.clone(), error.in_file(path)));
+                    continue;
+                }
+            };
+
+            let conflicts = {
+                let inner = self.inner.read().unwrap_or_else(PoisonError::into_inner);
+                parser.ids()
+                    .filter_map(|id| inner.owners.get(&id).filter(|x| *x != path).map(|provider| WassetModuleConflict {
+                        id,
+                        module: path.clone(),
+                        provider: provider.clone()
+                    }))
+                    .collect::<Vec<_>>()
+            };
+
+            if !conflicts.is_empty() {
+                report.conflicts.extend(conflicts);
+                continue;
+            }
+
+            match self.replace_module(path.clone(), parser) {
+                Ok(_) => report.loaded.push(path.clone()),
+                Err(error) => report.failed.push((path.clone(), error.in_file(path)))
+            }
+        }
+
+        let inner = self.inner.read().unwrap_or_else(PoisonError::into_inner);
+        for parser in report.loaded.iter().filter_map(|x| inner.modules.get(x)) {
+            report.stats.extend(parser.manifest().iter().map(|(_, entry)| entry));
+        }
+
+        report
+    }
+}
+
+impl<'a, K: Clone + Eq + Hash, A: AssetSchema> Default for WassetRegistry<'a, K, A> {
+    fn default() -> Self {
+        Self {
+            inner: RwLock::new(RegistryInner {
+                cache: FxHashMap::default(),
+                generation: 0,
+                modules: FxHashMap::default(),
+                owners: FxHashMap::default()
+            }),
+            listeners: RwLock::default()
+        }
+    }
+}
+
+impl<'a, K: Clone + Eq + Hash, A: AssetSchema + Clone> WassetSource<A> for WassetRegistry<'a, K, A> {
+    fn ids(&self) -> Box<dyn '_ + Iterator<Item = WassetId>> {
+        Box::new(self.inner.read().unwrap_or_else(PoisonError::into_inner).owners.keys().copied().collect::<Vec<_>>().into_iter())
+    }
+
+    fn contains(&self, id: WassetId) -> bool {
+        self.inner.read().unwrap_or_else(PoisonError::into_inner).owners.contains_key(&id)
+    }
+
+    fn load(&self, id: WassetId) -> Result<Option<A>, WassetError> {
+        Ok(WassetRegistry::load(self, id)?.map(|x| (*x).clone()))
+    }
+}
+
+/// Describes how the assets of a registry changed when a module was replaced or removed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct WassetRegistryChange<K> {
+    /// The key of the module that changed.
+    pub module: K,
+    /// The assets which were not previously provided by the module.
+    pub added: Vec<WassetId>,
+    /// The assets which were provided before and after the change, but whose content may differ.
+    pub changed: Vec<WassetId>,
+    /// The assets which are no longer provided by the module.
+    pub removed: Vec<WassetId>
+}
+
+/// Holds the contents of every `.wasm` file within a directory and its subdirectories,
+/// so that they may be parsed by [`WassetRegistry::load_dir`]. Content packs built with
+/// `WassetModuleBuilder` are ordinary modules, so they are discovered as well.
+#[derive(Clone, Debug, Default)]
+pub struct WassetModuleDir {
+    /// The path and contents of each module, sorted by path.
+    modules: Vec<(PathBuf, Vec<u8>)>
+}
+
+impl WassetModuleDir {
+    /// Reads every module within the given directory.
+    pub fn read(directory: &Path) -> Result<Self, WassetError> {
+        let mut paths = Vec::new();
+        let mut pending = vec![directory.to_path_buf()];
+        while let Some(folder) = pending.pop() {
+            for entry in std::fs::read_dir(&folder).map_err(|x| WassetError::from_deserialize(x).in_file(&folder))? {
+                let path = entry.map_err(|x| WassetError::from_deserialize(x).in_file(&folder))?.path();
+                if path.is_dir() {
+                    pending.push(path);
+                }
+                else if path.extension().is_some_and(|x| x == "wasm") {
+                    paths.push(path);
+                }
+            }
+        }
+
+        paths.sort();
+        let modules = paths.into_iter()
+            .map(|path| std::fs::read(&path).map(|x| (path.clone(), x)).map_err(|x| WassetError::from_deserialize(x).in_file(path)))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Self { modules })
+    }
+
+    /// Gets the path and contents of each module, sorted by path.
+    pub fn modules(&self) -> &[(PathBuf, Vec<u8>)] {
+        &self.modules
+    }
+
+    /// The number of modules in the directory.
+    pub fn len(&self) -> usize {
+        self.modules.len()
+    }
+
+    /// Whether the directory contains no modules.
+    pub fn is_empty(&self) -> bool {
+        self.modules.is_empty()
+    }
+}
+
+/// Describes the outcome of [`WassetRegistry::load_dir`].
+#[derive(Debug, Default)]
+pub struct WassetLoadReport {
+    /// The modules which were registered, in path order.
+    pub loaded: Vec<PathBuf>,
+    /// The assets which caused modules to be skipped, because they were already provided by another module.
+    pub conflicts: Vec<WassetModuleConflict>,
+    /// The modules which could not be parsed or registered, along with the reason.
+    pub failed: Vec<(PathBuf, WassetError)>,
+    /// The statistics over every asset of the registered modules.
+    pub stats: WassetStats
+}
+
+impl WassetLoadReport {
+    /// Whether every module in the directory was registered.
+    pub fn is_complete(&self) -> bool {
+        self.conflicts.is_empty() && self.failed.is_empty()
+    }
+}
+
+/// An asset which prevented a module from being registered by [`WassetRegistry::load_dir`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct WassetModuleConflict {
+    /// The ID of the asset.
+    pub id: WassetId,
+    /// The path of the module which was skipped.
+    pub module: PathBuf,
+    /// The path of the module which already provides the asset.
+    pub provider: PathBuf
+}
+
+/// A callback which is invoked whenever a registry module changes.
+type RegistryListener<'a, K> = Box<dyn 'a + Send + Sync + Fn(&WassetRegistryChange<K>)>;
+
+/// Holds the mutable state of a registry.
+struct RegistryInner<'a, K, A: AssetSchema> {
+    /// The decoded assets, keyed by ID.
+    cache: FxHashMap<WassetId, Arc<A>>,
+    /// A counter which is incremented whenever modules change.
+    generation: u64,
+    /// The parsers for each module.
+    modules: FxHashMap<K, WassetParser<'a, A>>,
+    /// The key of the module which provides each asset.
+    owners: FxHashMap<WassetId, K>
+}
+
+impl<'a, K: Clone + Eq + Hash, A: AssetSchema> RegistryInner<'a, K, A> {
+    /// Gets the manifest entry for the asset with the given ID.
+    fn entry(&self, id: WassetId) -> Option<&WassetManifestEntry> {
+        self.owners.get(&id).and_then(|x| self.modules.get(x)).and_then(|x| x.manifest().get(id))
+    }
+}
+
+#[cfg(all(test, feature = "testing"))]
+mod tests {
+    use super::*;
+
+    /// Gets the ID of the asset with the given number.
+    fn id(x: u8) -> WassetId {
+        WassetId::from_bytes([x; 16])
+    }
+
+    /// Builds a module which holds the given assets, each stored under the ID of the same number.
+    fn module(assets: &[(u8, u32)]) -> Vec<u8> {
+        testing::build_module(assets.iter().map(|(x, value)| (id(*x), *value))).unwrap()
+    }
+
+    /// Subscribes to the changes of a registry, returning the list into which they are recorded.
+    fn record_changes<'a>(registry: &WassetRegistry<'a, &'static str, u32>) -> Arc<Mutex<Vec<WassetRegistryChange<&'static str>>>> {
+        let changes = Arc::new(Mutex::new(Vec::new()));
+        let recorded = changes.clone();
+        registry.subscribe(move |change| {
+            let mut change = change.clone();
+            for ids in [&mut change.added, &mut change.changed, &mut change.removed] {
+                ids.sort();
+            }
+            recorded.lock().unwrap().push(change);
+        });
+        changes
+    }
+
+    #[test]
+    fn replace_module_reports_changes() {
+        let first = module(&[(1, 1), (2, 2), (3, 3)]);
+        let second = module(&[(1, 1), (2, 20), (4, 4)]);
+        let registry = WassetRegistry::<&str, u32>::new();
+        let changes = record_changes(&registry);
+
+        assert!(registry.replace_module("plugin", WassetParser::parse(&first).unwrap()).unwrap().is_none());
+        assert!(registry.replace_module("plugin", WassetParser::parse(&second).unwrap()).unwrap().is_some());
+
+        assert_eq!(*changes.lock().unwrap(), [
+            WassetRegistryChange { module: "plugin", added: vec![id(1), id(2), id(3)], changed: Vec::new(), removed: Vec::new() },
+            WassetRegistryChange { module: "plugin", added: vec![id(4)], changed: vec![id(2)], removed: vec![id(3)] }
+        ]);
+        assert_eq!(registry.module_of(id(3)), None);
+        assert_eq!(registry.module_of(id(4)), Some("plugin"));
+        assert_eq!(registry.load(id(2)).unwrap().as_deref(), Some(&20));
+    }
+
+    #[test]
+    fn replace_module_rejects_assets_of_other_modules() {
+        let first = module(&[(1, 1)]);
+        let second = module(&[(1, 1), (2, 2)]);
+        let registry = WassetRegistry::<&str, u32>::new();
+        registry.replace_module("first", WassetParser::parse(&first).unwrap()).unwrap();
+
+        assert!(registry.replace_module("second", WassetParser::parse(&second).unwrap()).is_err());
+        assert_eq!(registry.modules(), ["first"]);
+        assert_eq!(registry.module_of(id(1)), Some("first"));
+        assert_eq!(registry.module_of(id(2)), None);
+    }
+
+    #[test]
+    fn remove_module_removes_its_assets() {
+        let first = module(&[(1, 1)]);
+        let second = module(&[(2, 2)]);
+        let registry = WassetRegistry::<&str, u32>::new();
+        registry.replace_module("first", WassetParser::parse(&first).unwrap()).unwrap();
+        registry.replace_module("second", WassetParser::parse(&second).unwrap()).unwrap();
+        assert_eq!(registry.load(id(1)).unwrap().as_deref(), Some(&1));
+        let changes = record_changes(&registry);
+
+        assert!(registry.remove_module(&"first").is_some());
+        assert!(registry.remove_module(&"first").is_none());
+        assert_eq!(*changes.lock().unwrap(), [WassetRegistryChange { module: "first", added: Vec::new(), changed: Vec::new(), removed: vec![id(1)] }]);
+        assert_eq!(registry.load(id(1)).unwrap(), None);
+        assert_eq!(registry.load(id(2)).unwrap().as_deref(), Some(&2));
+        assert_eq!(registry.modules(), ["second"]);
+    }
+
+    #[test]
+    fn cached_assets_are_invalidated_when_content_changes() {
+        let first = module(&[(1, 1), (2, 2)]);
+        let second = module(&[(1, 1), (2, 20)]);
+        let registry = WassetRegistry::<&str, u32>::new();
+        registry.replace_module("plugin", WassetParser::parse(&first).unwrap()).unwrap();
+
+        let unchanged = registry.load(id(1)).unwrap().unwrap();
+        let changed = registry.load(id(2)).unwrap().unwrap();
+        assert!(Arc::ptr_eq(&changed, &registry.load(id(2)).unwrap().unwrap()));
+
+        registry.replace_module("plugin", WassetParser::parse(&second).unwrap()).unwrap();
+        assert!(Arc::ptr_eq(&unchanged, &registry.load(id(1)).unwrap().unwrap()));
+        assert_eq!(*registry.load(id(2)).unwrap().unwrap(), 20);
+    }
+
+    #[test]
+    fn load_order_follows_dependencies_across_modules() {
+        let first = testing::build_module_with_dependencies([(id(1), 1u32, vec![id(2), id(3)]), (id(2), 2, vec![id(3)])]).unwrap();
+        let second = testing::build_module_with_dependencies([(id(3), 3u32, Vec::new())]).unwrap();
+        let registry = WassetRegistry::<&str, u32>::new();
+        registry.replace_module("first", WassetParser::parse(&first).unwrap()).unwrap();
+        assert!(matches!(registry.load_order([id(1)]), Err(WassetError::MissingDependency { asset, dependency }) if asset == id(2) && dependency == id(3)));
+
+        registry.replace_module("second", WassetParser::parse(&second).unwrap()).unwrap();
+        assert_eq!(registry.dependencies(id(1)), Some(vec![id(2), id(3)]));
+        assert_eq!(registry.load_order([id(1), id(4)]).unwrap(), [id(3), id(2), id(1)]);
+        let loaded = registry.load_with_dependencies([id(2)]).unwrap();
+        assert_eq!(loaded.iter().map(|(id, asset)| (*id, **asset)).collect::<Vec<_>>(), [(id(3), 3), (id(2), 2)]);
+    }
+
+    #[test]
+    fn load_order_rejects_dependency_cycles() {
+        let module = testing::build_module_with_dependencies([(id(1), 1u32, vec![id(2)]), (id(2), 2, vec![id(3)]), (id(3), 3, vec![id(2)])]).unwrap();
+        let registry = WassetRegistry::<&str, u32>::new();
+        registry.replace_module("plugin", WassetParser::parse(&module).unwrap()).unwrap();
+
+        assert!(matches!(registry.load_order([id(1)]), Err(WassetError::DependencyCycle(cycle)) if cycle == [id(2), id(3)]));
+        assert!(registry.load_with_dependencies([id(3)]).is_err());
+    }
+
+    /// Writes the given files to an empty temporary folder unique to the given test, returning its path.
+    fn temporary_folder(name: &str, files: &[(&str, &[u8])]) -> PathBuf {
+        let folder = std::env::temp_dir().join(format!("wasset-registry-{name}-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&folder);
+        for (path, contents) in files {
+            let path = folder.join(path);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, contents).unwrap();
+        }
+        folder
+    }
+
+    #[test]
+    fn load_dir_skips_conflicting_and_invalid_modules() {
+        let first = module(&[(1, 1)]);
+        let conflicting = module(&[(1, 1), (2, 2)]);
+        let nested = module(&[(3, 3)]);
+        let folder = temporary_folder("load-dir", &[("a.wasm", &first), ("b.wasm", &conflicting), ("c.wasm", b"invalid"), ("nested/d.wasm", &nested), ("notes.txt", b"ignored")]);
+
+        let modules = WassetModuleDir::read(&folder).unwrap();
+        assert_eq!(modules.len(), 4);
+        let registry = WassetRegistry::<PathBuf, u32>::new();
+        let report = registry.load_dir(&modules, &WassetParseOptions::default());
+
+        assert!(!report.is_complete());
+        assert_eq!(report.loaded, [folder.join("a.wasm"), folder.join("nested/d.wasm")]);
+        assert_eq!(report.conflicts, [WassetModuleConflict { id: id(1), module: folder.join("b.wasm"), provider: folder.join("a.wasm") }]);
+        assert_eq!(report.failed.iter().map(|(path, _)| path.clone()).collect::<Vec<_>>(), [folder.join("c.wasm")]);
+        assert_eq!(registry.load(id(3)).unwrap().as_deref(), Some(&3));
+        assert_eq!(registry.load(id(2)).unwrap(), None);
+        std::fs::remove_dir_all(folder).unwrap();
+    }
+
+    #[test]
+    fn store_resolves_assets_of_plugin_folder() {
+        let base = testing::build_module([(id(1), String::from("base"))]).unwrap();
+        let extension = testing::build_module([(id(2), String::from("extension"))]).unwrap();
+        let folder = temporary_folder("store", &[("base.wasm", &base), ("mods/extension.wasm", &extension)]);
+
+        let modules = WassetModuleDir::read(&folder).unwrap();
+        let store = WassetStore::<String>::new();
+        assert!(store.load_dir(&modules, &WassetParseOptions::default()).is_complete());
+        assert_eq!(store.module_of(id(2)), Some(folder.join("mods/extension.wasm")));
+        assert_eq!(store.load(id(1)).unwrap().as_deref().map(String::as_str), Some("base"));
+        assert_eq!(store.stats().total.count, 2);
+        std::fs::remove_dir_all(folder).unwrap();
+    }
+
+    /// Synchronizes the deserialization of [`PausingAsset`] with the test that loads it.
+    static DESERIALIZING: Barrier = Barrier::new(2);
+
+    /// An asset whose deserialization pauses at [`DESERIALIZING`] until the test has replaced its module.
+    #[derive(Debug, Serialize, PartialEq, Eq)]
+    struct PausingAsset(u32);
+
+    impl<'de> Deserialize<'de> for PausingAsset {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            let value = u32::deserialize(deserializer)?;
+            if value == 1 {
+                DESERIALIZING.wait();
+                DESERIALIZING.wait();
+            }
+
+            Ok(Self(value))
+        }
+    }
+
+    #[test]
+    fn stale_decodes_are_not_cached() {
+        let versions = [1, 2].map(|x| testing::build_module([(id(1), PausingAsset(x))]).unwrap());
+        let registry = WassetRegistry::<&str, PausingAsset>::new();
+        registry.replace_module("plugin", WassetParser::parse(&versions[0]).unwrap()).unwrap();
+
+        std::thread::scope(|scope| {
+            let stale = scope.spawn(|| registry.load(id(1)).unwrap());
+            DESERIALIZING.wait();
+            registry.replace_module("plugin", WassetParser::parse(&versions[1]).unwrap()).unwrap();
+            DESERIALIZING.wait();
+            assert_eq!(stale.join().unwrap().as_deref(), Some(&PausingAsset(1)));
+        });
+
+        assert_eq!(registry.load(id(1)).unwrap().as_deref(), Some(&PausingAsset(2)));
+    }
+}
@@ -0,0 +1,112 @@
+use crate::*;
+#[cfg(feature = "parse")]
+use ed25519_dalek::{Signature, VerifyingKey};
+#[cfg(feature = "encode")]
+use ed25519_dalek::{Signer, SigningKey};
+
+/// Distinguishes the digests of asset sections from other messages signed with the same key.
+const SIGNATURE_CONTEXT: &[u8] = b"wasset asset sections v1";
+
+#[cfg(feature = "encode")]
+impl EncodedAssets {
+    /// Signs every set of sections which holds these assets with the given key, replacing any
+    /// previous signatures. Each signature covers the manifest, data, index, and preview of its
+    /// set, and is stored in its own custom section, so that modules may be checked with
+    /// [`WassetParser::parse_verified`]. Assets are signed automatically during encoding if
+    /// [`WassetEncodeOptions::signing_key`] is set.
+    pub fn sign(&mut self, key: &SigningKey) {
+        self.signature = sign_sections(key, &self.manifest, &self.data, &self.index, &self.preview);
+        for partition in &mut self.partitions {
+            partition.signature = sign_sections(key, &partition.manifest, &partition.data, &partition.index, &partition.preview);
+        }
+    }
+}
+
+#[cfg(feature = "parse")]
+impl<'a, A: AssetSchema> WassetParser<'a, A> {
+    /// Attempts to parse the asset list from the given module, failing with
+    /// [`WassetError::InvalidSignature`] unless every set of asset sections was signed
+    /// by the holder of the private half of `key`. This ensures that the assets come from
+    /// a trusted author, and have not been modified since they were encoded.
+    pub fn parse_verified(module: &'a [u8], key: &VerifyingKey) -> Result<Self, WassetError> {
+        Self::parse_verified_with_options(module, key, WassetParseOptions::default())
+    }
+
+    /// Attempts to parse the asset list from the given module, using the provided options when
+    /// reading assets. See [`WassetParser::parse_verified`] for details.
+    pub fn parse_verified_with_options(module: &'a [u8], key: &VerifyingKey, options: WassetParseOptions) -> Result<Self, WassetError> {
+        for (section_id, offsets) in read_asset_sections(module, &options.section_prefix)? {
+            let (manifest, data) = offsets.complete(section_id)?;
+            let data = &module[data.offset as usize..(data.offset + data.len) as usize];
+            let preview = offsets.preview.map(|x| &module[x.offset as usize..(x.offset + x.len) as usize]).unwrap_or_default();
+            let digest = section_digest(manifest, data, offsets.index.unwrap_or_default(), preview);
+
+            let signature = offsets.signature.and_then(|x| Signature::from_slice(x).ok())
+                .ok_or(WassetError::InvalidSignature(section_id))?;
+            key.verify_strict(&digest, &signature).map_err(|_| WassetError::InvalidSignature(section_id))?;
+        }
+
+        Self::parse_with_options(module, options)
+    }
+}
+
+/// Signs one set of asset sections, returning the contents of its signature section.
+#[cfg(feature = "encode")]
+fn sign_sections(key: &SigningKey, manifest: &[u8], data: &[u8], index: &[u8], preview: &[u8]) -> Vec<u8> {
+    key.sign(&section_digest(manifest, data, index, preview)).to_vec()
+}
+
+/// Computes the digest which is signed for one set of asset sections. Each section is prefixed
+/// by its length, so that bytes cannot be moved between sections without changing the digest.
+fn section_digest(manifest: &[u8], data: &[u8], index: &[u8], preview: &[u8]) -> [u8; 32] {
+    let mut hasher = blake3::Hasher::new();
+    hasher.update(SIGNATURE_CONTEXT);
+    for section in [manifest, data, index, preview] {
+        hasher.update(&(section.len() as u64).to_le_bytes());
+        hasher.update(section);
+    }
+
+    *hasher.finalize().as_bytes()
+}
+
+#[cfg(all(test, feature = "testing"))]
+mod tests {
+    use super::*;
+
+    /// Builds a module holding a single asset, signed with the given key if any.
+    fn signed_module(id: WassetId, key: Option<&SigningKey>) -> Vec<u8> {
+        let options = WassetEncodeOptions { signing_key: key.cloned(), ..Default::default() };
+        testing::build_module_with_options([(id, String::from("signed"))], &options).unwrap()
+    }
+
+    #[test]
+    fn signed_module_is_verified() {
+        let id = WassetId::from_bytes([1; 16]);
+        let key = SigningKey::from_bytes(&[7; 32]);
+        let module = signed_module(id, Some(&key));
+
+        let parser = WassetParser::<String>::parse_verified(&module, &key.verifying_key()).unwrap();
+        assert_eq!(parser.load(id).unwrap().as_deref(), Some("signed"));
+
+        let mut assets = encode_assets([(id, String::from("signed"))], &WassetEncodeOptions::default()).unwrap();
+        assets.sign(&key);
+        let module = WassetModuleBuilder::new().add_assets(assets).build();
+        assert!(WassetParser::<String>::parse_verified(&module, &key.verifying_key()).is_ok());
+    }
+
+    #[test]
+    fn unsigned_or_modified_module_is_rejected() {
+        let id = WassetId::from_bytes([1; 16]);
+        let key = SigningKey::from_bytes(&[7; 32]);
+        let other_key = SigningKey::from_bytes(&[8; 32]);
+        let is_rejected = |module: &[u8]| matches!(WassetParser::<String>::parse_verified(module, &key.verifying_key()), Err(WassetError::InvalidSignature(_)));
+
+        assert!(is_rejected(&signed_module(id, None)));
+        assert!(is_rejected(&signed_module(id, Some(&other_key))));
+
+        let mut modified = signed_module(id, Some(&key));
+        let start = WassetParser::<String>::parse(&modified).unwrap().manifest().get(id).unwrap().range().start;
+        modified[start as usize] ^= 1;
+        assert!(is_rejected(&modified));
+    }
+}
@@ -0,0 +1,77 @@
+use crate::*;
+use wasm_encoder::*;
+
+/// A module whose assets were moved into a separate sidecar, as produced by [`WassetParser::split_module`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct WassetSplitModule {
+    /// The module bytecode with its asset sections removed.
+    pub module: Vec<u8>,
+    /// The asset pack which holds the removed sections. The pack is itself a WASM module with no code,
+    /// so it may be read with [`WassetParser::parse`] and any tool that reads modules.
+    pub assets: Vec<u8>
+}
+
+impl<'a, A: AssetSchema> WassetParser<'a, A> {
+    /// Separates the module into its bytecode and a sidecar asset pack, which holds the manifests and data
+    /// of every asset. This allows for shipping a lean module while hosting its assets elsewhere.
+    /// The two halves may be loaded together with [`Self::parse_split`].
+    pub fn split_module(&self) -> Result<WassetSplitModule, WassetError> {
+        self.split_module_with_options(&WassetStripOptions::default())
+    }
+
+    /// Separates the module into its bytecode and a sidecar asset pack, moving the asset sections which
+    /// `options` selects into the pack. Manifests are copied into the pack even if they are kept in the
+    /// module, and non-asset sections removed by the options are discarded.
+    #[cfg_attr(feature = "tracing", tracing::instrument(name = "wasset::split_module", level = "debug", skip_all, fields(module_size = self.module.len())))]
+    pub fn split_module_with_options(&self, options: &WassetStripOptions) -> Result<WassetSplitModule, WassetError> {
+        let prefix = &self.options().section_prefix;
+        let mut assets = Module::HEADER.to_vec();
+        let module = rewrite_custom_sections(self.module, |section| {
+            if parse_section_name(section.name(), prefix).is_some_and(|(_, id)| options.is_selected(id)) {
+                CustomSection { name: section.name().into(), data: Cow::Borrowed(section.data()) }.append_to(&mut assets);
+            }
+
+            (!self.should_strip(section.name(), options)).then_some(Cow::Borrowed(section.data()))
+        })?;
+
+        Ok(WassetSplitModule { module, assets })
+    }
+
+    /// Attempts to parse the asset list from a module which was separated by [`Self::split_module`],
+    /// reading the assets from its sidecar asset pack.
+    pub fn parse_split(module: &[u8], assets: &'a [u8]) -> Result<Self, WassetError> {
+        Self::parse_split_with_options(module, assets, WassetParseOptions::default())
+    }
+
+    /// Attempts to parse the asset list from a module which was separated by [`Self::split_module`],
+    /// using the provided options when reading assets. Any manifests which were kept in the module must
+    /// match those of the asset pack, so that a module is not paired with a pack from another build.
+    /// The module may also be one produced by [`Self::strip_data`], whose manifests differ only in
+    /// being marked as having external data.
+    /// Assets which remain in the module are not read, and may be loaded by parsing the module on its own.
+    pub fn parse_split_with_options(module: &[u8], assets: &'a [u8], options: WassetParseOptions) -> Result<Self, WassetError> {
+        let pack = read_asset_sections(assets, &options.section_prefix)?;
+        for (section_id, offsets) in read_asset_sections(module, &options.section_prefix)? {
+            let Some(manifest) = offsets.manifest else {
+                continue;
+            };
+
+            let Some(pack_manifest) = pack.get(&section_id).and_then(|x| x.manifest) else {
+                return Err(WassetError::from_deserialize(format!("Asset manifest {section_id} of the module is missing from the asset pack")));
+            };
+
+            if manifest != pack_manifest && !same_assets(manifest, pack_manifest)? {
+                return Err(WassetError::from_deserialize(format!("Asset manifest {section_id} of the module does not match the asset pack")));
+            }
+        }
+
+        Self::parse_with_options(assets, options)
+    }
+}
+
+/// Determines whether two serialized manifests describe the same assets, regardless of
+/// whether their data is external.
+fn same_assets(a: &[u8], b: &[u8]) -> Result<bool, WassetError> {
+    let (a, b) = (WassetManifest::from_bytes(a)?, WassetManifest::from_bytes(b)?);
+    Ok(a.assets == b.assets && a.partition == b.partition && a.schema == b.schema)
+}
@@ -0,0 +1,129 @@
+use crate::*;
+use std::collections::*;
+
+/// Summarizes the assets provided by a parser or registry, as read from their manifests.
+/// Gathering statistics never decodes or decompresses any asset.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct WassetStats {
+    /// The totals over every asset.
+    pub total: WassetStatsTotals,
+    /// The totals over the assets of each kind. Assets whose encoder reported no kind are omitted.
+    pub by_kind: BTreeMap<String, WassetStatsTotals>,
+    /// The totals over the assets directly within each folder, such as `assets/textures`.
+    /// Assets which were not encoded from a file are omitted.
+    pub by_folder: BTreeMap<String, WassetStatsTotals>,
+    /// The totals over the assets stored with each compression scheme.
+    pub by_compression: BTreeMap<WassetCompression, WassetStatsTotals>
+}
+
+impl WassetStats {
+    /// Gathers statistics over the entries of a manifest.
+    pub fn from_manifest(manifest: &WassetManifest) -> Self {
+        let mut result = Self::default();
+        result.extend(manifest.iter().map(|(_, entry)| entry));
+        result
+    }
+
+    /// Adds a single asset to the statistics.
+    fn add(&mut self, entry: &WassetManifestEntry) {
+        self.total.add(entry);
+        self.by_compression.entry(entry.compression()).or_default().add(entry);
+
+        if let Some(kind) = entry.kind() {
+            self.by_kind.entry(kind.to_string()).or_default().add(entry);
+        }
+
+        if let Some((folder, _)) = entry.name().and_then(|x| x.rsplit_once('/')) {
+            self.by_folder.entry(folder.to_string()).or_default().add(entry);
+        }
+    }
+}
+
+impl<'b> Extend<&'b WassetManifestEntry> for WassetStats {
+    fn extend<T: IntoIterator<Item = &'b WassetManifestEntry>>(&mut self, iter: T) {
+        for entry in iter {
+            self.add(entry);
+        }
+    }
+}
+
+/// Counts a group of assets and the space that they occupy.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct WassetStatsTotals {
+    /// The number of assets in the group.
+    pub count: usize,
+    /// The number of bytes that the assets occupy in the module, after compression.
+    pub stored_bytes: u64,
+    /// The number of bytes that the assets occupy once decompressed. Assets whose
+    /// decompressed size was not recorded contribute their stored size instead.
+    pub raw_bytes: u64,
+    /// The number of assets whose decompressed size was not recorded.
+    pub unknown_raw_size: usize
+}
+
+impl WassetStatsTotals {
+    /// The ratio of stored bytes to decompressed bytes, which is `1.0` for an empty group.
+    pub fn compression_ratio(&self) -> f64 {
+        if self.raw_bytes == 0 {
+            1.0
+        }
+        else {
+            self.stored_bytes as f64 / self.raw_bytes as f64
+        }
+    }
+
+    /// Adds a single asset to the totals.
+    fn add(&mut self, entry: &WassetManifestEntry) {
+        let stored = u64::from(entry.compressed_size());
+        self.count += 1;
+        self.stored_bytes += stored;
+        match entry.uncompressed_size() {
+            Some(size) => self.raw_bytes += u64::from(size),
+            None => {
+                self.raw_bytes += stored;
+                self.unknown_raw_size += 1;
+            }
+        }
+    }
+}
+
+impl<'a, A: AssetSchema> WassetParser<'a, A> {
+    /// Gathers statistics over every asset in the module, such as the number of assets
+    /// and bytes of each kind, for use in dashboards and size budgets.
+    pub fn stats(&self) -> WassetStats {
+        WassetStats::from_manifest(self.manifest())
+    }
+}
+
+#[cfg(all(test, feature = "testing"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stats_are_grouped_by_kind_and_folder() {
+        let files = [("Wasset.toml", "[\"a.txt\"]\nkind = \"note\""), ("a.txt", "first"), ("b.txt", "second"), ("sub/c.txt", "third")];
+        let module = WassetModuleBuilder::new().add_assets(testing::encode_text_files(files, &WassetEncodeOptions::default()).unwrap()).build();
+        let stats = WassetParser::<String>::parse(&module).unwrap().stats();
+
+        assert_eq!(stats.total.count, 3);
+        assert_eq!(stats.total.raw_bytes, stats.total.stored_bytes);
+        assert_eq!(stats.total.compression_ratio(), 1.0);
+        assert_eq!(stats.by_kind.keys().collect::<Vec<_>>(), ["note"]);
+        assert_eq!(stats.by_kind["note"].count, 1);
+        assert_eq!(stats.by_folder.iter().map(|(folder, totals)| (folder.as_str(), totals.count)).collect::<Vec<_>>(), [("assets", 2), ("assets/sub", 1)]);
+        assert_eq!(stats.by_compression[&WassetCompression::None].count, 3);
+    }
+
+    #[cfg(feature = "lz4")]
+    #[test]
+    fn compression_ratio_reflects_stored_size() {
+        let id = WassetId::from_bytes([1; 16]);
+        let options = WassetEncodeOptions { compression: CompressionMode::Fixed(WassetCompression::Lz4), ..Default::default() };
+        let module = testing::build_module_with_options([(id, vec![0u8; 10000])], &options).unwrap();
+        let stats = WassetParser::<Vec<u8>>::parse(&module).unwrap().stats();
+
+        assert_eq!(stats.by_compression[&WassetCompression::Lz4].count, 1);
+        assert!(stats.total.compression_ratio() < 0.5);
+        assert_eq!(stats.total.unknown_raw_size, 0);
+    }
+}
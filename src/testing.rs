@@ -0,0 +1,403 @@
+//! Utilities for building asset-bearing WASM modules entirely in memory, and for substituting
+//! in-memory asset sources. These allow for testing code which consumes assets without invoking
+//! the `include_assets` macro or compiling a WASM module.
+//!
+//! ```
+//! use wasset::*;
+//!
+//! let id = WassetId::from_bytes([1; 16]);
+//! let module = testing::build_module([(id, String::from("Hello world!"))]).unwrap();
+//! let parser = WassetParser::<String>::parse(&module).unwrap();
+//! assert_eq!(parser.load(id).unwrap().as_deref(), Some("Hello world!"));
+//! ```
+
+use crate::*;
+use std::collections::*;
+use wasm_encoder::*;
+
+/// Builds a minimal valid WASM module which contains nothing but the provided assets.
+pub fn build_module<A: AssetSchema>(assets: impl IntoIterator<Item = (WassetId, A)>) -> Result<Vec<u8>, WassetError> {
+    build_module_with_options(assets, &WassetEncodeOptions::default())
+}
+
+/// Builds a minimal valid WASM module which contains nothing but the provided assets,
+/// encoding them with the given options.
+pub fn build_module_with_options<A: AssetSchema>(assets: impl IntoIterator<Item = (WassetId, A)>, options: &WassetEncodeOptions) -> Result<Vec<u8>, WassetError> {
+    let encoded = encode_assets(assets, options)?;
+    Ok(WassetModuleBuilder::new()
+        .section_prefix(options.section_prefix.clone())
+        .add_assets(encoded)
+        .build())
+}
+
+/// Builds a module holding the given assets, recording that each one depends upon the listed assets.
+#[cfg(test)]
+pub(crate) fn build_module_with_dependencies<A: AssetSchema>(assets: impl IntoIterator<Item = (WassetId, A, Vec<WassetId>)>) -> Result<Vec<u8>, WassetError> {
+    let options = WassetEncodeOptions::default();
+    let mut data = Vec::new();
+    let mut payloads = WrittenPayloads::default();
+    let mut manifest = WassetManifest::default();
+    for (id, asset, dependencies) in assets {
+        write_asset(&mut data, &mut payloads, &mut manifest, id, &asset, &options, &mut EncodeTimings::default())?.dependencies = dependencies;
+    }
+
+    let encoded = EncodedAssets::from_parts(data, Vec::new(), manifest, FxHashMap::default(), &options)?;
+    Ok(WassetModuleBuilder::new().add_assets(encoded).build())
+}
+
+/// Encodes each asset file as a UTF-8 string, so that tests may encode asset folders.
+/// Files with the `toml` extension, such as `Wasset.toml`, are not accepted.
+#[cfg(test)]
+pub(crate) struct TextEncoder;
+
+#[cfg(test)]
+impl AssetEncoder for TextEncoder {
+    type Target = String;
+
+    fn encode(context: &EncodeContext, data: Vec<u8>) -> Result<Option<String>, WassetError> {
+        if context.extension == "toml" {
+            Ok(None)
+        }
+        else {
+            String::from_utf8(data).map(Some).map_err(WassetError::from_serialize)
+        }
+    }
+}
+
+/// Encodes an in-memory `assets` folder holding the given files with [`TextEncoder`].
+#[cfg(test)]
+pub(crate) fn encode_text_files<'a>(files: impl IntoIterator<Item = (&'a str, &'a str)>, options: &WassetEncodeOptions) -> Result<EncodedAssets, WassetError> {
+    let mut provider = MemoryFileProvider::new();
+    for (path, contents) in files {
+        provider.insert(std::path::Path::new("assets").join(path), contents.as_bytes().to_vec());
+    }
+
+    encode_asset_folder_from::<TextEncoder>(&provider, std::path::Path::new("assets"), options)
+}
+
+/// An asset which serializes as a MessagePack byte array, so that it may be viewed in place.
+#[cfg(all(test, feature = "bytemuck"))]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct PodBytes(pub Vec<u8>);
+
+#[cfg(all(test, feature = "bytemuck"))]
+impl PodBytes {
+    /// Creates an asset holding the little-endian bytes of the given values.
+    pub fn from_u32s(values: impl IntoIterator<Item = u32>) -> Self {
+        Self(values.into_iter().flat_map(u32::to_le_bytes).collect())
+    }
+}
+
+#[cfg(all(test, feature = "bytemuck"))]
+impl Serialize for PodBytes {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_bytes(&self.0)
+    }
+}
+
+#[cfg(all(test, feature = "bytemuck"))]
+impl<'de> Deserialize<'de> for PodBytes {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        /// Accepts a byte array.
+        struct BytesVisitor;
+
+        impl ::serde::de::Visitor<'_> for BytesVisitor {
+            type Value = PodBytes;
+
+            fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+                formatter.write_str("a byte array")
+            }
+
+            fn visit_bytes<E: ::serde::de::Error>(self, v: &[u8]) -> Result<PodBytes, E> {
+                Ok(PodBytes(v.to_vec()))
+            }
+        }
+
+        deserializer.deserialize_byte_buf(BytesVisitor)
+    }
+}
+
+/// An in-memory [`WassetSource`] backed by a map of assets. This allows for testing code
+/// written against [`WassetSource`] without constructing any WASM at all.
+#[derive(Clone, Debug)]
+pub struct MockWassetSource<A: AssetSchema + Clone> {
+    /// The assets held by this source.
+    assets: HashMap<WassetId, A>
+}
+
+impl<A: AssetSchema + Clone> MockWassetSource<A> {
+    /// Creates a new, empty source.
+    pub fn new() -> Self {
+        Self {
+            assets: HashMap::new()
+        }
+    }
+
+    /// Adds an asset to the source, returning the asset that previously had the same ID.
+    pub fn insert(&mut self, id: WassetId, asset: A) -> Option<A> {
+        self.assets.insert(id, asset)
+    }
+
+    /// Removes an asset from the source, returning it if it existed.
+    pub fn remove(&mut self, id: WassetId) -> Option<A> {
+        self.assets.remove(&id)
+    }
+}
+
+impl<A: AssetSchema + Clone> Default for MockWassetSource<A> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<A: AssetSchema + Clone> FromIterator<(WassetId, A)> for MockWassetSource<A> {
+    fn from_iter<T: IntoIterator<Item = (WassetId, A)>>(iter: T) -> Self {
+        Self {
+            assets: HashMap::from_iter(iter)
+        }
+    }
+}
+
+impl<A: AssetSchema + Clone> WassetSource<A> for MockWassetSource<A> {
+    fn ids(&self) -> Box<dyn '_ + Iterator<Item = WassetId>> {
+        Box::new(self.assets.keys().copied())
+    }
+
+    fn contains(&self, id: WassetId) -> bool {
+        self.assets.contains_key(&id)
+    }
+
+    fn load(&self, id: WassetId) -> Result<Option<A>, WassetError> {
+        Ok(self.assets.get(&id).cloned())
+    }
+}
+
+/// Generates a reproducible stream of randomized asset-bearing modules, for fuzzing and
+/// property-testing parsers and hosts. Each module holds one or more sets of asset sections
+/// with varying asset counts, sizes, encoding options, and section orderings. Some modules
+/// are then deliberately damaged, as described by their [`FuzzCorruption`]. Undamaged modules
+/// must parse and yield exactly their recorded assets, while damaged modules must at worst
+/// produce errors.
+///
+/// ```
+/// # use wasset::*;
+/// for module in testing::FuzzCorpus::new(42).take(1000) {
+///     let result = WassetParser::<Vec<u8>>::parse(&module.bytes)
+///         .and_then(|parser| module.assets.iter().map(|(id, _)| parser.load(*id)).collect::<Result<Vec<_>, _>>());
+///     assert!(module.corruption.is_some() || result.is_ok());
+/// }
+/// ```
+#[derive(Clone, Debug)]
+pub struct FuzzCorpus {
+    /// The state of the random number generator.
+    state: u64,
+    /// Bounds the shape of the generated modules.
+    options: FuzzCorpusOptions
+}
+
+impl FuzzCorpus {
+    /// Creates a new generator with default options. Generators with equal seeds produce equal modules.
+    pub fn new(seed: u64) -> Self {
+        Self::with_options(seed, FuzzCorpusOptions::default())
+    }
+
+    /// Creates a new generator with the given options. Generators with equal seeds and options produce equal modules.
+    pub fn with_options(seed: u64, options: FuzzCorpusOptions) -> Self {
+        Self { state: seed, options }
+    }
+
+    /// Gets the options which bound the shape of the generated modules.
+    pub fn options(&self) -> &FuzzCorpusOptions {
+        &self.options
+    }
+
+    /// Generates the next module in the corpus.
+    pub fn generate(&mut self) -> FuzzModule {
+        let mut assets = Vec::new();
+        let mut sections = Vec::new();
+        for _ in 0..1 + self.below(self.options.max_section_sets.max(1)) {
+            let set = (0..self.below(self.options.max_assets + 1))
+                .map(|_| {
+                    let id = WassetId::from(Uuid::from_u64_pair(self.next_u64(), self.next_u64()));
+                    let size = self.below(self.options.max_asset_size + 1);
+                    (id, (0..size).map(|_| self.next_u64() as u8).collect::<Vec<_>>())
+                })
+                .collect::<Vec<_>>();
+
+            let options = self.encode_options();
+            let encoded = encode_assets(set.iter().cloned(), &options).expect("Failed to encode fuzz assets");
+            sections.extend(encoded.to_custom_sections(Uuid::from_u64_pair(self.next_u64(), self.next_u64())));
+            assets.extend(set);
+        }
+
+        for _ in 0..self.below(3) {
+            let data = (0..self.below(64)).map(|_| self.next_u64() as u8).collect();
+            sections.push((format!("fuzz_unrelated_{}", self.next_u64()), data));
+        }
+
+        for index in (1..sections.len()).rev() {
+            sections.swap(index, self.below(index + 1));
+        }
+
+        let corruption = (self.unit() < self.options.corruption_chance)
+            .then(|| FuzzCorruption::ALL[self.below(FuzzCorruption::ALL.len())]);
+        let mut bytes = Module::new().finish();
+        match corruption {
+            Some(FuzzCorruption::MissingSection) => {
+                let index = self.below(sections.len());
+                sections.remove(index);
+            },
+            Some(FuzzCorruption::DuplicateSection) => {
+                let section = sections[self.below(sections.len())].clone();
+                sections.push(section);
+            },
+            Some(FuzzCorruption::GarbledSection) => {
+                let index = self.below(sections.len());
+                sections[index].1 = (0..self.below(256)).map(|_| self.next_u64() as u8).collect();
+            },
+            Some(FuzzCorruption::OutOfRange) => {
+                let manifests = (0..sections.len()).filter(|x| sections[*x].0.contains(MANIFEST_SECTION_SUFFIX)).collect::<Vec<_>>();
+                if let Some(&index) = manifests.get(self.below(manifests.len().max(1))) {
+                    let format = ManifestFormat::detect(&sections[index].1);
+                    let mut manifest = WassetManifest::from_bytes(&sections[index].1).expect("Failed to read fuzz manifest");
+                    let extra = 1 + self.below(1 << 16) as u32;
+                    manifest.assets.values_mut().for_each(|entry| entry.end = entry.end.saturating_add(extra));
+                    manifest.assets.insert(WassetId::from(Uuid::from_u64_pair(self.next_u64(), self.next_u64())), WassetManifestEntry {
+                        start: u32::MAX - extra,
+                        end: u32::MAX,
+                        ..Default::default()
+                    });
+                    sections[index].1 = manifest.to_bytes(format).expect("Failed to write fuzz manifest");
+                }
+            },
+            _ => {}
+        }
+
+        for (name, data) in &sections {
+            CustomSection { name: name.into(), data: data.into() }.append_to(&mut bytes);
+        }
+
+        match corruption {
+            Some(FuzzCorruption::BitFlips) => {
+                for _ in 0..1 + self.below(8) {
+                    let index = Module::HEADER.len() + self.below(bytes.len() - Module::HEADER.len());
+                    bytes[index] ^= 1 << self.below(8);
+                }
+            },
+            Some(FuzzCorruption::Truncated) => bytes.truncate(self.below(bytes.len())),
+            _ => {}
+        }
+
+        FuzzModule { bytes, assets, corruption }
+    }
+
+    /// Chooses random encoding options for one set of asset sections.
+    fn encode_options(&mut self) -> WassetEncodeOptions {
+        let compressions = WassetCompression::ALL.into_iter().filter(WassetCompression::is_available).collect::<Vec<_>>();
+        let compression = compressions[self.below(compressions.len())];
+        WassetEncodeOptions {
+            alignment: 1 << self.below(5),
+            compression: CompressionMode::Fixed(compression),
+            manifest_format: match self.below(3) {
+                0 => ManifestFormat::MessagePack,
+                1 => ManifestFormat::Compact { compression: WassetCompression::None },
+                _ => ManifestFormat::Compact { compression }
+            },
+            manifest_layout: if self.below(2) == 0 { ManifestLayout::Map } else { ManifestLayout::PerfectHash },
+            ..WassetEncodeOptions::default()
+        }
+    }
+
+    /// Gets the next random number.
+    fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e3779b97f4a7c15);
+        mix_bits(self.state)
+    }
+
+    /// Gets a random number which is less than `bound`, or zero if `bound` is zero.
+    fn below(&mut self, bound: usize) -> usize {
+        if bound == 0 {
+            0
+        }
+        else {
+            (self.next_u64() % bound as u64) as usize
+        }
+    }
+
+    /// Gets a random number between zero and one.
+    fn unit(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+impl Iterator for FuzzCorpus {
+    type Item = FuzzModule;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        Some(self.generate())
+    }
+}
+
+/// Bounds the shape of the modules produced by a [`FuzzCorpus`].
+#[derive(Clone, Debug)]
+pub struct FuzzCorpusOptions {
+    /// The largest number of sets of asset sections in each module.
+    pub max_section_sets: usize,
+    /// The largest number of assets in each set of sections.
+    pub max_assets: usize,
+    /// The largest size of each asset, in bytes.
+    pub max_asset_size: usize,
+    /// The probability, between zero and one, that each module is damaged.
+    pub corruption_chance: f64
+}
+
+impl Default for FuzzCorpusOptions {
+    fn default() -> Self {
+        Self {
+            max_section_sets: 3,
+            max_assets: 16,
+            max_asset_size: 256,
+            corruption_chance: 0.5
+        }
+    }
+}
+
+/// A module produced by a [`FuzzCorpus`].
+#[derive(Clone, Debug)]
+pub struct FuzzModule {
+    /// The WASM bytecode of the module.
+    pub bytes: Vec<u8>,
+    /// The assets that were encoded into the module, which an undamaged module must yield exactly.
+    pub assets: Vec<(WassetId, Vec<u8>)>,
+    /// How the module was damaged after encoding, if at all.
+    pub corruption: Option<FuzzCorruption>
+}
+
+impl FuzzModule {
+    /// Whether the module was left undamaged, and so must parse successfully.
+    pub fn is_valid(&self) -> bool {
+        self.corruption.is_none()
+    }
+}
+
+/// Describes how a [`FuzzModule`] was damaged after encoding.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum FuzzCorruption {
+    /// A few random bits of the module were flipped.
+    BitFlips,
+    /// The module was cut short at a random position.
+    Truncated,
+    /// One custom section was omitted.
+    MissingSection,
+    /// One custom section was written twice.
+    DuplicateSection,
+    /// The contents of one custom section were replaced with random bytes.
+    GarbledSection,
+    /// The entries of one manifest were extended beyond the end of their data section.
+    OutOfRange
+}
+
+impl FuzzCorruption {
+    /// All kinds of damage.
+    pub const ALL: [Self; 6] = [Self::BitFlips, Self::Truncated, Self::MissingSection, Self::DuplicateSection, Self::GarbledSection, Self::OutOfRange];
+}
@@ -0,0 +1,103 @@
+use crate::*;
+use std::sync::*;
+
+/// Parses the assets of a WASM module without knowledge of their schema. This allows generic
+/// tools, such as packers, inspectors, or size checks, to examine the IDs, byte ranges, and
+/// raw data of assets without depending upon the crate which defines the asset type. Any
+/// operation of [`WassetParser`] may be performed through [`RawWassetParser::parser`].
+///
+/// ```no_run
+/// # use wasset::*;
+/// let module = std::fs::read("plugin.wasm")?;
+/// let parser = RawWassetParser::parse(&module)?;
+/// for id in parser.ids() {
+///     println!("{id:?} | {:?} | {} bytes", parser.range(id), parser.stored_data(id)?.unwrap_or_default().len());
+/// }
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+#[derive(Clone)]
+pub struct RawWassetParser<'a> {
+    /// The module data itself.
+    module: &'a [u8],
+    /// The manifest associated with the module.
+    manifest: Arc<WassetManifest>,
+    /// The options used to read assets.
+    options: Arc<WassetParseOptions>
+}
+
+impl<'a> RawWassetParser<'a> {
+    /// Attempts to parse the asset list from the given module.
+    pub fn parse(module: &'a [u8]) -> Result<Self, WassetError> {
+        Self::parse_with_options(module, WassetParseOptions::default())
+    }
+
+    /// Attempts to parse the asset list from the given module, using the provided options
+    /// when reading assets.
+    pub fn parse_with_options(module: &'a [u8], options: WassetParseOptions) -> Result<Self, WassetError> {
+        Ok(WassetParser::<()>::parse_with_options(module, options)?.into())
+    }
+
+    /// Gets a parser which shares the manifest of this one. Its schema is `()`, so
+    /// it may inspect, load raw data, and strip assets, but not deserialize them.
+    pub fn parser(&self) -> WassetParser<'a, ()> {
+        WassetParser::from_shared(self.module, self.manifest.clone(), self.options.clone())
+    }
+
+    /// Gets an iterator over the IDs of all assets stored in the module.
+    pub fn ids(&self) -> impl '_ + Iterator<Item = WassetId> {
+        self.manifest.ids()
+    }
+
+    /// Gets the byte range that the asset with the given ID occupies within the module,
+    /// returning `None` if the asset did not exist.
+    pub fn range(&self, id: WassetId) -> Option<Range<u32>> {
+        self.manifest.get(id).map(WassetManifestEntry::range)
+    }
+
+    /// Gets the bytes of the asset with the given ID exactly as they are stored in the module,
+    /// without decompressing them. Returns `None` if the asset did not exist.
+    pub fn stored_data(&self, id: WassetId) -> Result<Option<&'a [u8]>, WassetError> {
+        self.manifest.get(id).map(|entry| self.parser().stored_data(entry)).transpose()
+    }
+
+    /// Loads the serialized data of the asset with the given ID, decompressing it if necessary.
+    /// The data is borrowed from the module when the asset is stored uncompressed. Returns
+    /// `None` if the asset did not exist.
+    pub fn load_raw(&self, id: WassetId) -> Result<Option<Cow<'a, [u8]>>, WassetError> {
+        let Some(entry) = self.manifest.get(id) else {
+            return Ok(None);
+        };
+
+        let stored = self.parser().stored_data(entry)?;
+        if entry.is_stored_verbatim() {
+            return Ok(Some(Cow::Borrowed(stored)));
+        }
+
+        let mut data = Vec::new();
+        decompress_entry(entry, stored, &self.options, &mut data)?;
+        Ok(Some(Cow::Owned(data)))
+    }
+
+    /// Gets a reference to the module manifest.
+    pub fn manifest(&self) -> &WassetManifest {
+        &self.manifest
+    }
+
+    /// Gets the options that this parser uses to read assets.
+    pub fn options(&self) -> &WassetParseOptions {
+        &self.options
+    }
+
+    /// Gets the bytecode of the module.
+    pub fn module(&self) -> &'a [u8] {
+        self.module
+    }
+}
+
+impl<'a, A: AssetSchema> From<WassetParser<'a, A>> for RawWassetParser<'a> {
+    fn from(value: WassetParser<'a, A>) -> Self {
+        let module = value.module;
+        let (manifest, options) = value.into_shared();
+        Self { module, manifest, options }
+    }
+}
@@ -0,0 +1,128 @@
+use crate::*;
+
+/// Describes wasted space and broken references among the asset sections of a module.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct WassetValidationReport {
+    /// Byte ranges within data sections that are not covered by any manifest entry.
+    pub orphaned_data: Vec<OrphanedData>,
+    /// Manifest entries which refer to data that does not exist.
+    pub dangling_entries: Vec<DanglingEntry>
+}
+
+impl WassetValidationReport {
+    /// The total number of bytes in data sections that could be reclaimed by repacking the module.
+    pub fn wasted_bytes(&self) -> u64 {
+        self.orphaned_data.iter().map(|x| (x.range.end - x.range.start) as u64).sum()
+    }
+
+    /// Whether the module has neither wasted space nor dangling references.
+    pub fn is_clean(&self) -> bool {
+        self.orphaned_data.is_empty() && self.dangling_entries.is_empty()
+    }
+}
+
+/// A range of bytes within a data section that no manifest entry refers to.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OrphanedData {
+    /// The ID of the data section, as it appears in the section name.
+    pub section: Uuid,
+    /// The unreferenced range, relative to the start of the section contents.
+    pub range: Range<u32>
+}
+
+/// A manifest entry that refers to data which does not exist.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DanglingEntry {
+    /// The ID of the manifest section, as it appears in the section name.
+    pub section: Uuid,
+    /// The ID of the asset.
+    pub id: WassetId,
+    /// The range that the entry refers to, relative to the start of the data section contents.
+    pub range: Range<u32>,
+    /// Whether the manifest has a matching data section at all. If so, the range lies outside of it.
+    pub has_data_section: bool
+}
+
+/// Checks the asset sections of a WASM module, using the section prefix from the provided options.
+/// Unlike [`WassetParser::parse`], this does not fail on dangling manifest entries, and instead
+/// reports them alongside any unreferenced data, so that tooling can determine what a repack would reclaim.
+/// Gaps shorter than [`MAX_PAYLOAD_ALIGNMENT`] which end where an entry begins are alignment padding,
+/// and are not reported. Manifests with external data refer to payloads stored outside of the module,
+/// so their entries are never reported as dangling.
+pub fn validate_module(module: &[u8], options: &WassetParseOptions) -> Result<WassetValidationReport, WassetError> {
+    let mut report = WassetValidationReport::default();
+
+    for (section, offsets) in read_asset_sections(module, &options.section_prefix)? {
+        let mut covered = Vec::new();
+        if let Some(manifest_data) = offsets.manifest {
+            let manifest = WassetManifest::from_bytes(manifest_data)?;
+            for (id, entry) in manifest.iter().filter(|_| !manifest.has_external_data()) {
+                for range in entry.payloads().map(WassetManifestEntry::range) {
+                    match offsets.data {
+                        Some(data) if range.start <= range.end && range.end <= data.len => covered.push(range),
+                        data => report.dangling_entries.push(DanglingEntry { section, id, range, has_data_section: data.is_some() })
+                    }
+                }
+            }
+        }
+
+        if let Some(data) = offsets.data {
+            covered.sort_by_key(|x| x.start);
+
+            let mut position = 0;
+            for range in covered {
+                if position < range.start && range.start - position >= MAX_PAYLOAD_ALIGNMENT {
+                    report.orphaned_data.push(OrphanedData { section, range: position..range.start });
+                }
+                position = position.max(range.end);
+            }
+
+            if position < data.len {
+                report.orphaned_data.push(OrphanedData { section, range: position..data.len });
+            }
+        }
+    }
+
+    Ok(report)
+}
+
+#[cfg(all(test, feature = "testing"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn built_module_is_clean() {
+        for alignment in [1, MAX_PAYLOAD_ALIGNMENT] {
+            let options = WassetEncodeOptions { alignment, ..Default::default() };
+            let module = testing::build_module_with_options((1..=3).map(|x| (WassetId::from_bytes([x; 16]), vec![x; 5 * x as usize])), &options).unwrap();
+            assert!(validate_module(&module, &WassetParseOptions::default()).unwrap().is_clean());
+        }
+    }
+
+    #[test]
+    fn removed_asset_is_orphaned() {
+        let ids = [WassetId::from_bytes([1; 16]), WassetId::from_bytes([2; 16])];
+        let module = testing::build_module(ids.iter().zip(1u8..).map(|(id, x)| (*id, vec![x; 100]))).unwrap();
+        let parser = WassetParser::<Vec<u8>>::parse(&module).unwrap();
+        let size = parser.manifest().get(ids[0]).unwrap().compressed_size();
+
+        let report = validate_module(&parser.remove_assets([ids[0]]).unwrap(), parser.options()).unwrap();
+        assert_eq!(report.orphaned_data.len(), 1);
+        assert_eq!(report.wasted_bytes(), u64::from(size));
+        assert!(report.dangling_entries.is_empty());
+    }
+
+    #[test]
+    fn entry_without_data_is_dangling() {
+        let id = WassetId::from_bytes([1; 16]);
+        let module = testing::build_module([(id, 1u32)]).unwrap();
+        let parser = WassetParser::<u32>::parse(&module).unwrap();
+
+        let without_data = parser.strip_module_with_options(&WassetStripOptions { keep_manifests: true, ..Default::default() }).unwrap();
+        let report = validate_module(&without_data, parser.options()).unwrap();
+        assert!(matches!(report.dangling_entries.as_slice(), [DanglingEntry { id: x, has_data_section: false, .. }] if *x == id));
+
+        let external = parser.strip_data().unwrap();
+        assert!(validate_module(&external, parser.options()).unwrap().is_clean());
+    }
+}
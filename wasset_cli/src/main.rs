@@ -0,0 +1,197 @@
+//! Provides the `wasset` command, which inspects and edits the assets of compiled WASM modules
+//! without writing a program against `WassetParser`.
+//!
+//! ```text
+//! wasset list <module>
+//! wasset manifest <module>
+//! wasset extract <module> <folder> [--raw]
+//! wasset strip <module> <output> [--keep-manifests]
+//! wasset inject <module> <folder> <output>
+//! wasset validate <module>
+//! ```
+
+use std::path::*;
+use std::process::*;
+use uuid::*;
+use wasset::*;
+
+/// Describes how the command is used.
+const USAGE: &str = "\
+Usage: wasset <command> [arguments]
+
+Commands:
+    list <module>                              Lists the assets embedded in a module
+    manifest <module>                          Prints the combined manifest of a module as JSON
+    extract <module> <folder> [--raw]          Writes every asset to a file in the folder. With --raw,
+                                               assets are decoded as RawAssets and their contents written
+                                               with an extension matching their MIME type
+    strip <module> <output> [--keep-manifests] Writes a copy of the module without its assets
+    inject <module> <folder> <output>          Embeds the files of a folder as RawAssets into a copy of the module
+    validate <module>                          Checks the module for wasted space and broken references";
+
+fn main() -> ExitCode {
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    match run(&args) {
+        Ok(code) => code,
+        Err(error) => {
+            eprintln!("error: {error}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Runs the command described by the given arguments.
+fn run(args: &[String]) -> Result<ExitCode, WassetError> {
+    let (flags, positional): (Vec<_>, Vec<_>) = args.iter().map(String::as_str).partition(|x| x.starts_with("--"));
+    let has_flag = |name: &str| flags.contains(&name);
+
+    match positional.as_slice() {
+        ["list", module] if flags.is_empty() => list(Path::new(module)),
+        ["manifest", module] if flags.is_empty() => manifest(Path::new(module)),
+        ["extract", module, folder] if flags.iter().all(|x| *x == "--raw") => extract(Path::new(module), Path::new(folder), has_flag("--raw")),
+        ["strip", module, output] if flags.iter().all(|x| *x == "--keep-manifests") => strip(Path::new(module), Path::new(output), has_flag("--keep-manifests")),
+        ["inject", module, folder, output] if flags.is_empty() => inject(Path::new(module), Path::new(folder), Path::new(output)),
+        ["validate", module] if flags.is_empty() => validate(Path::new(module)),
+        _ => {
+            eprintln!("{USAGE}");
+            Ok(ExitCode::FAILURE)
+        }
+    }
+}
+
+/// Prints the ID, size, compression, kind, and name of every asset in the module.
+fn list(module: &Path) -> Result<ExitCode, WassetError> {
+    let data = read(module)?;
+    let parser = RawWassetParser::parse(&data).map_err(|e| e.in_file(module))?;
+
+    let mut entries = parser.manifest().iter().collect::<Vec<_>>();
+    entries.sort_by(|(a_id, a), (b_id, b)| (a.name(), a_id).cmp(&(b.name(), b_id)));
+
+    for (id, entry) in entries {
+        let size = entry.uncompressed_size().map(|x| x.to_string()).unwrap_or_else(|| "?".to_string());
+        println!("{} {:>10} {:>10} {:<5} {:<12} {}",
+            Uuid::from(id),
+            entry.compressed_size(),
+            size,
+            format!("{:?}", entry.compression()),
+            entry.kind().unwrap_or("-"),
+            entry.name().unwrap_or("-"));
+    }
+
+    Ok(ExitCode::SUCCESS)
+}
+
+/// Prints the manifests of the module, combined into one, as JSON. The ranges of
+/// the entries are relative to the start of the module.
+fn manifest(module: &Path) -> Result<ExitCode, WassetError> {
+    let data = read(module)?;
+    let parser = RawWassetParser::parse(&data).map_err(|e| e.in_file(module))?;
+    println!("{}", parser.manifest().to_json()?);
+    Ok(ExitCode::SUCCESS)
+}
+
+/// Writes the data of every asset in the module to a file within `folder`, named after the asset.
+/// Assets without a usable name are named after their ID.
+fn extract(module: &Path, folder: &Path, raw: bool) -> Result<ExitCode, WassetError> {
+    let data = read(module)?;
+    let parser = RawWassetParser::parse(&data).map_err(|e| e.in_file(module))?;
+
+    for (id, entry) in parser.manifest().iter() {
+        let name = entry.name().and_then(safe_relative_path).unwrap_or_else(|| PathBuf::from(Uuid::from(id).to_string()));
+        let bytes = parser.load_raw(id)?.unwrap_or_default();
+
+        let (path, contents) = if raw {
+            let asset = WassetItem::<RawAsset>::from(&*bytes).with_codec(entry.codec()).deserialize().map_err(|e| e.in_file(&name))?;
+            let mut path = folder.join(&name).into_os_string();
+            if let Some(extension) = RawAssetDecoder::extension(&asset) {
+                path.push(".");
+                path.push(extension);
+            }
+            (PathBuf::from(path), asset.bytes)
+        }
+        else {
+            let mut path = folder.join(&name).into_os_string();
+            path.push(codec_extension(entry.codec()));
+            (PathBuf::from(path), bytes.into_owned())
+        };
+
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| WassetError::from_serialize(e).in_file(parent))?;
+        }
+        write(&path, &contents)?;
+    }
+
+    Ok(ExitCode::SUCCESS)
+}
+
+/// Gets the file extension for serialized data in the given format.
+fn codec_extension(codec: WassetCodec) -> &'static str {
+    match codec {
+        WassetCodec::MessagePack => ".msgpack",
+        WassetCodec::Postcard => ".postcard",
+        WassetCodec::Bincode => ".bincode",
+        WassetCodec::Json => ".json"
+    }
+}
+
+/// Writes a copy of the module without any asset sections to `output`.
+fn strip(module: &Path, output: &Path, keep_manifests: bool) -> Result<ExitCode, WassetError> {
+    let data = read(module)?;
+    let parser = RawWassetParser::parse(&data).map_err(|e| e.in_file(module))?;
+    let stripped = parser.parser().strip_module_with_options(&WassetStripOptions { keep_manifests, ..Default::default() })?;
+    write(output, &stripped)?;
+    Ok(ExitCode::SUCCESS)
+}
+
+/// Encodes the files of `folder` as [`RawAsset`]s, and writes a copy of the module which holds them to `output`.
+fn inject(module: &Path, folder: &Path, output: &Path) -> Result<ExitCode, WassetError> {
+    let data = read(module)?;
+    let assets = encode_asset_folder::<RawAssetEncoder>(folder)?;
+    for file in &assets.unaccepted_files {
+        eprintln!("warning: asset file {} was not accepted by the encoder", file.display());
+    }
+
+    write(output, &inject_assets(&data, &assets))?;
+    Ok(ExitCode::SUCCESS)
+}
+
+/// Prints any wasted space or broken references in the module, failing if there are any.
+fn validate(module: &Path) -> Result<ExitCode, WassetError> {
+    let data = read(module)?;
+    let report = validate_module(&data, &WassetParseOptions::default()).map_err(|e| e.in_file(module))?;
+
+    for orphan in &report.orphaned_data {
+        println!("orphaned data in section {}: bytes {}..{}", orphan.section, orphan.range.start, orphan.range.end);
+    }
+
+    for entry in &report.dangling_entries {
+        let reason = if entry.has_data_section { "lies outside of its data section" } else { "has no data section" };
+        println!("dangling entry {} in section {}: bytes {}..{} {reason}", Uuid::from(entry.id), entry.section, entry.range.start, entry.range.end);
+    }
+
+    if report.is_clean() {
+        println!("ok");
+        Ok(ExitCode::SUCCESS)
+    }
+    else {
+        println!("{} bytes could be reclaimed", report.wasted_bytes());
+        Ok(ExitCode::FAILURE)
+    }
+}
+
+/// Converts an asset name into a relative path, returning `None` if the name could escape
+/// the output folder.
+fn safe_relative_path(name: &str) -> Option<PathBuf> {
+    let path = PathBuf::from(name);
+    path.components().all(|x| matches!(x, Component::Normal(_))).then_some(path)
+}
+
+/// Reads the entire file at the given path.
+fn read(path: &Path) -> Result<Vec<u8>, WassetError> {
+    std::fs::read(path).map_err(|e| WassetError::from_deserialize(e).in_file(path))
+}
+
+/// Writes the given data to the file at the given path.
+fn write(path: &Path, data: &[u8]) -> Result<(), WassetError> {
+    std::fs::write(path, data).map_err(|e| WassetError::from_serialize(e).in_file(path))
+}
@@ -14,12 +14,12 @@ struct ExampleAssetEncoder;
 impl AssetEncoder for ExampleAssetEncoder {
     type Target = ExampleAsset;
 
-    fn encode(extension: &str, metadata: &Table, data: Vec<u8>) -> Result<Option<Self::Target>, WassetError> {
-        match extension {
+    fn encode(context: &EncodeContext, data: Vec<u8>) -> Result<Option<Self::Target>, WassetError> {
+        match context.extension {
             "txt" => {
                 let mut data = String::from_utf8_lossy(&data).into_owned();
-                match metadata.get("append") {
-                    Some(Value::String(x)) => data.extend(x.chars()),
+                match context.metadata.get("append") {
+                    Some(Value::String(x)) => data.push_str(x),
                     Some(x) => return Err(WassetError::from_serialize(format!("Unexpected metadata value {x:?}"))),
                     None => {},
                 }
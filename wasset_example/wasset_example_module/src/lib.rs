@@ -6,10 +6,6 @@ include_assets!("wasset_example/wasset_example_module/assets");
 
 /// Gets a list of all assets from this module.
 pub fn list_all_assets() -> &'static [WassetId] {
-    &[
-        // The macro has defined these for us.
-        assets::SOME_BINARY,
-        assets::SOME_TEXT,
-        assets::submodule::MORE_TEXT
-    ]
+    // The macro has defined this for us.
+    assets::ALL_ASSETS
 }
\ No newline at end of file
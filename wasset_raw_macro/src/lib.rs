@@ -0,0 +1,32 @@
+//! Provides the `include_raw_assets!` macro, which embeds a folder of files into a WASM module
+//! as [`wasset::RawAsset`]s. This allows for using `wasset` without defining a custom schema,
+//! encoder, or macro crate.
+//!
+//! ```ignore
+//! use wasset_raw_macro::*;
+//!
+//! include_raw_assets!("assets");
+//!
+//! // On the host, assets may be read with `WassetParser::<wasset::RawAsset>`.
+//! ```
+//!
+//! The `reference_raw_assets!` macro defines the same constants for the assets of an
+//! already-built module, so that other crates may refer to them without embedding them again.
+
+extern crate proc_macro;
+use proc_macro::*;
+use wasset::*;
+
+/// Embeds every file in the given folder as a [`RawAsset`], and defines a module
+/// hierarchy with constants for each asset's [`WassetId`].
+#[proc_macro]
+pub fn include_raw_assets(path: TokenStream) -> TokenStream {
+    wasset::include_assets::<RawAssetEncoder>(path, &quote::quote! { ::wasset::WassetId })
+}
+
+/// Reads the assets embedded in the given compiled WASM module, and defines the same module
+/// hierarchy of [`WassetId`] constants as the macro which embedded them, without embedding any data.
+#[proc_macro]
+pub fn reference_raw_assets(path: TokenStream) -> TokenStream {
+    wasset::reference_assets(path, &quote::quote! { ::wasset::WassetId })
+}